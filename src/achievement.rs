@@ -0,0 +1,235 @@
+//! Achievements reward playing the game in different ways, like walking far or building
+//! the ship. They observe game events, counting those they care about, and unlock once
+//! enough have happened. Achievements belong to the player rather than a world, so their
+//! progress is stored apart from the save slots.
+
+use event::{GameEvent, Observer};
+use std::collections::HashMap;
+
+/**
+    The kinds of event an achievement counts.
+
+    @variant Collected Items picked up or harvested.
+    @variant TileEntered Tiles walked onto.
+    @variant DamageTaken Health lost.
+    @variant IslandDiscovered Islands discovered.
+    @variant ShipBuilt Changes made to the ship.
+    @variant Recruited Islanders recruited.
+    @variant EnemyDefeated Enemies killed.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Trigger {
+    Collected,
+    TileEntered,
+    DamageTaken,
+    IslandDiscovered,
+    ShipBuilt,
+    Recruited,
+    EnemyDefeated,
+}
+
+impl Trigger {
+    /*
+        Determines how much an event counts towards the trigger.
+
+        @param event The event.
+        @return u32 The amount, 0 if the event isn't of the trigger's kind.
+    */
+    pub fn amount(&self, event: &GameEvent) -> u32 {
+        match (*self, event) {
+            (Trigger::Collected, &GameEvent::Collected(_))
+            | (Trigger::TileEntered, &GameEvent::TileEntered(_))
+            | (Trigger::IslandDiscovered, &GameEvent::IslandDiscovered(_))
+            | (Trigger::ShipBuilt, &GameEvent::ShipBuilt(_))
+            | (Trigger::Recruited, &GameEvent::Recruited(_))
+            | (Trigger::EnemyDefeated, &GameEvent::EnemyDefeated) => 1,
+            (Trigger::DamageTaken, &GameEvent::DamageTaken(damage)) => damage.max(0) as u32,
+            _ => 0,
+        }
+    }
+}
+
+/**
+    Implementation of the Achievement object.
+
+    @field name The achievement's name, used in the achievements file.
+    @field title The achievement's title, shown on the achievements page and when unlocked.
+    @field description What to do to unlock it.
+    @field trigger The kind of event counted.
+    @field goal How much must be counted to unlock it.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Achievement {
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    pub trigger: Trigger,
+    pub goal: u32,
+}
+
+impl Achievement {
+    /*
+        Achievement constructor.
+
+        @param name The achievement's name.
+        @param title The achievement's title.
+        @param description What to do to unlock it.
+        @param trigger The kind of event counted.
+        @param goal How much must be counted to unlock it.
+        @return Achievement Returns self.
+    */
+    pub fn new(name: &str, title: &str, description: &str, trigger: Trigger, goal: u32) -> Self {
+        Achievement {
+            name: name.to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+            trigger: trigger,
+            goal: goal,
+        }
+    }
+}
+
+/**
+    Implementation of the Achievements object, the registry and the player's progress.
+
+    @field registry Every achievement, in the order they're listed.
+    @field counts How much has been counted towards each achievement, keyed by name.
+    @field newly_unlocked Titles of achievements unlocked since they were last taken.
+*/
+pub struct Achievements {
+    pub registry: Vec<Achievement>,
+    pub counts: HashMap<String, u32>,
+    pub newly_unlocked: Vec<String>,
+}
+
+impl Achievements {
+    /*
+        Achievements constructor, with nothing counted yet.
+
+        @param registry Every achievement.
+        @return Achievements Returns self.
+    */
+    pub fn new(registry: Vec<Achievement>) -> Self {
+        Achievements {
+            registry: registry,
+            counts: HashMap::new(),
+            newly_unlocked: vec![],
+        }
+    }
+
+    /*
+        Determines how much has been counted towards an achievement.
+
+        @param achievement The achievement.
+        @return u32 The count, at most the achievement's goal.
+    */
+    pub fn count(&self, achievement: &Achievement) -> u32 {
+        self.counts
+            .get(&achievement.name)
+            .cloned()
+            .unwrap_or(0)
+            .min(achievement.goal)
+    }
+
+    /*
+        Determines whether an achievement is unlocked.
+
+        @param achievement The achievement.
+        @return bool Whether its goal has been reached.
+    */
+    pub fn is_unlocked(&self, achievement: &Achievement) -> bool {
+        self.count(achievement) >= achievement.goal
+    }
+
+    /*
+        Takes the titles of the achievements unlocked since this was last called.
+
+        @return Vec<String> The titles, in the order they were unlocked.
+    */
+    pub fn take_unlocked(&mut self) -> Vec<String> {
+        self.newly_unlocked.drain(..).collect()
+    }
+}
+
+impl Observer for Achievements {
+    /*
+        Counts an event towards the achievements still locked, unlocking those it completes.
+
+        @param event The event.
+    */
+    fn notify(&mut self, event: &GameEvent) {
+        for achievement in &self.registry {
+            let amount = achievement.trigger.amount(event);
+            if amount == 0 {
+                continue;
+            }
+            let count = self.counts.entry(achievement.name.clone()).or_insert(0);
+            if *count >= achievement.goal {
+                continue;
+            }
+            *count = (*count + amount).min(achievement.goal);
+            if *count >= achievement.goal {
+                self.newly_unlocked.push(achievement.title.clone());
+            }
+        }
+    }
+}
+
+/*
+    Creates the registry of every achievement in the game.
+
+    @return Vec<Achievement> The achievements, in the order shown on the achievements page.
+*/
+pub fn generate_achievements() -> Vec<Achievement> {
+    vec![
+        Achievement::new(
+            "first_steps",
+            "First steps",
+            "Walk across 100 tiles",
+            Trigger::TileEntered,
+            100,
+        ),
+        Achievement::new(
+            "packrat",
+            "Packrat",
+            "Pick up 25 items",
+            Trigger::Collected,
+            25,
+        ),
+        Achievement::new(
+            "cartographer",
+            "Cartographer",
+            "Discover 5 islands",
+            Trigger::IslandDiscovered,
+            5,
+        ),
+        Achievement::new(
+            "shipwright",
+            "Shipwright",
+            "Make 10 changes to the ship",
+            Trigger::ShipBuilt,
+            10,
+        ),
+        Achievement::new(
+            "thick_skinned",
+            "Thick skinned",
+            "Take 10 damage",
+            Trigger::DamageTaken,
+            10,
+        ),
+        Achievement::new(
+            "captain",
+            "Captain",
+            "Recruit 3 crew members",
+            Trigger::Recruited,
+            3,
+        ),
+        Achievement::new(
+            "sky_warden",
+            "Sky warden",
+            "Defeat 10 enemies",
+            Trigger::EnemyDefeated,
+            10,
+        ),
+    ]
+}
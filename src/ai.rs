@@ -0,0 +1,153 @@
+//! AI is the behaviour of creatures that aren't controlled by the player.
+//!
+//! Each hostile creature runs a small state machine. It idles and wanders around its
+//! island until the player comes into sight, then chases them, attacks once in reach,
+//! and flees when badly hurt.
+
+use misc::*;
+use rand::*;
+
+const IDLE_TIME: (f64, f64) = (1.0, 3.0);
+const WANDER_TIME: (f64, f64) = (0.5, 2.0);
+const FLEE_RANGE_FACTOR: f64 = 1.5;
+
+/**
+    The behaviours a creature can be in.
+
+    @variant Idle Standing still.
+    @variant Wander Walking in a random direction.
+    @variant Chase Walking towards the player.
+    @variant Attack Hitting the player, who is in reach.
+    @variant Flee Running away from the player.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AiState {
+    Idle,
+    Wander,
+    Chase,
+    Attack,
+    Flee,
+}
+
+/**
+    Implementation of the Ai object.
+
+    @field state The current behaviour.
+    @field timer Seconds left of idling or wandering.
+    @field wander_dir The direction being wandered in.
+    @field sight How far away the player is noticed, in pixels.
+    @field reach How close the player must be to attack, in pixels.
+    @field damage Damage done by each attack.
+    @field flee_health Health at or below which the creature flees.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ai {
+    pub state: AiState,
+    pub timer: f64,
+    pub wander_dir: Direction,
+    pub sight: f64,
+    pub reach: f64,
+    pub damage: i32,
+    pub flee_health: i32,
+}
+
+impl Ai {
+    /*
+        Ai constructor. Creatures start out idle.
+
+        @param sight How far away the player is noticed, in pixels.
+        @param reach How close the player must be to attack, in pixels.
+        @param damage Damage done by each attack.
+        @param flee_health Health at or below which the creature flees.
+        @return Ai Returns self.
+    */
+    pub fn new(sight: f64, reach: f64, damage: i32, flee_health: i32) -> Self {
+        Ai {
+            state: AiState::Idle,
+            timer: 0.0,
+            wander_dir: Direction::S,
+            sight: sight,
+            reach: reach,
+            damage: damage,
+            flee_health: flee_health,
+        }
+    }
+
+    /*
+        Chooses the next behaviour. The player decides the behaviour when they're close,
+        otherwise the creature switches between idling and wandering on a timer.
+
+        @param health The creature's health.
+        @param distance How far away the player is, or None if they can't be reached.
+        @return AiState The next behaviour.
+    */
+    pub fn decide(&self, health: i32, distance: Option<f64>) -> AiState {
+        if let Some(distance) = distance {
+            if health <= self.flee_health && distance <= self.sight * FLEE_RANGE_FACTOR {
+                return AiState::Flee;
+            }
+            if health > self.flee_health && distance <= self.reach {
+                return AiState::Attack;
+            }
+            if health > self.flee_health && distance <= self.sight {
+                return AiState::Chase;
+            }
+        }
+        match self.state {
+            AiState::Wander if self.timer > 0.0 => AiState::Wander,
+            AiState::Idle if self.timer > 0.0 => AiState::Idle,
+            AiState::Idle => AiState::Wander,
+            _ => AiState::Idle,
+        }
+    }
+
+    /*
+        Changes behaviour, starting a new timer and direction when idling or wandering.
+
+        @param state The new behaviour.
+        @param rng Random number generator for the timer and direction.
+    */
+    pub fn enter<R: Rng>(&mut self, state: AiState, rng: &mut R) {
+        if state == self.state {
+            return;
+        }
+        self.state = state;
+        match state {
+            AiState::Idle => self.timer = rng.gen_range(IDLE_TIME.0, IDLE_TIME.1),
+            AiState::Wander => {
+                self.timer = rng.gen_range(WANDER_TIME.0, WANDER_TIME.1);
+                self.wander_dir = match rng.gen_range(0, 4) {
+                    0 => Direction::N,
+                    1 => Direction::E,
+                    2 => Direction::S,
+                    _ => Direction::W,
+                };
+            }
+            _ => self.timer = 0.0,
+        }
+    }
+}
+
+/*
+    Determines which directions move from one point towards another.
+
+    @param from_x The starting x position.
+    @param from_y The starting y position.
+    @param to_x The target x position.
+    @param to_y The target y position.
+    @return Vec<Direction> The directions to move in, at most one per axis.
+*/
+pub fn directions_towards(from_x: f64, from_y: f64, to_x: f64, to_y: f64) -> Vec<Direction> {
+    let mut directions = vec![];
+    if to_x > from_x + 1.0 {
+        directions.push(Direction::E);
+    } else if to_x < from_x - 1.0 {
+        directions.push(Direction::W);
+    }
+    if to_y > from_y + 1.0 {
+        directions.push(Direction::S);
+    } else if to_y < from_y - 1.0 {
+        directions.push(Direction::N);
+    }
+    directions
+}
@@ -0,0 +1,426 @@
+//! Blueprints describe ships in plain text, so ship layouts can live in the assets folder.
+//!
+//! A blueprint has a few `key = value` settings, a legend saying which character is which
+//! tile, and the layout itself, one row per line:
+//!
+//! ```text
+//! ; Comments start with a semicolon.
+//! name = Skiff
+//! speed = 360
+//! spawn = 3 4
+//!
+//! legend:
+//! . = air
+//! # = wood_floor
+//! W = wheel
+//! P = portal
+//!
+//! layout:
+//! ..#P#..
+//! .##W##.
+//! ```
+
+use find_folder::Search;
+use ship::{is_connected, Ship};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use tile::{Tile, TileType};
+
+pub const DEFAULT_SHIP: &str = "default";
+pub const EXPORTED_SHIP: &str = "exported";
+const BLUEPRINT_EXTENSION: &str = "ship";
+const DEFAULT_SPEED: f64 = 360.0;
+const BUILT_IN_SHIP: &str = include_str!("../assets/ships/default.ship");
+
+/**
+    Errors that can occur while reading or writing a blueprint.
+
+    @variant Io The file could not be read or written.
+    @variant Malformed The blueprint is invalid, with the line, column and reason.
+*/
+#[derive(Debug)]
+pub enum BlueprintError {
+    Io(io::Error),
+    Malformed(usize, usize, String),
+}
+
+impl From<io::Error> for BlueprintError {
+    fn from(error: io::Error) -> Self {
+        BlueprintError::Io(error)
+    }
+}
+
+impl fmt::Display for BlueprintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BlueprintError::Io(ref e) => write!(f, "{}", e),
+            BlueprintError::Malformed(line, column, ref reason) => {
+                write!(f, "line {}, column {}: {}", line, column, reason)
+            }
+        }
+    }
+}
+
+/**
+    Implementation of the Blueprint object.
+
+    @field name The ship's name.
+    @field speed The ship's maximum speed, in pixels per second.
+    @field spawn The tile the player starts on, as column and row.
+    @field tiles The ship's tiles, indexed by x then y.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Blueprint {
+    pub name: String,
+    pub speed: f64,
+    pub spawn: (usize, usize),
+    pub tiles: Vec<Vec<TileType>>,
+}
+
+impl Blueprint {
+    /*
+        Reads a blueprint from the ships folder in the assets.
+
+        @param name The blueprint's file name, without extension.
+        @return Result<Blueprint, BlueprintError> The blueprint.
+    */
+    pub fn load(name: &str) -> Result<Blueprint, BlueprintError> {
+        let text = fs::read_to_string(blueprint_path(name)?)?;
+        Blueprint::parse(&text)
+    }
+
+    /*
+        Reads the default ship built into the game, for when the assets can't be read.
+
+        @return Blueprint The blueprint.
+    */
+    pub fn built_in() -> Blueprint {
+        Blueprint::parse(BUILT_IN_SHIP).expect("Error parsing built-in ship")
+    }
+
+    /*
+        Writes the blueprint to the ships folder in the assets.
+
+        @param file_name The file name, without extension.
+    */
+    pub fn save(&self, file_name: &str) -> Result<(), BlueprintError> {
+        fs::write(blueprint_path(file_name)?, self.to_text())?;
+        Ok(())
+    }
+
+    /*
+        Reads a blueprint from text.
+
+        @param text The blueprint text.
+        @return Result<Blueprint, BlueprintError> The blueprint.
+    */
+    pub fn parse(text: &str) -> Result<Blueprint, BlueprintError> {
+        let mut name = None;
+        let mut speed = DEFAULT_SPEED;
+        let mut spawn = None;
+        let mut legend: HashMap<char, TileType> = HashMap::new();
+        let mut rows: Vec<(usize, &str)> = vec![];
+        let mut section = "";
+        let mut layout_line = 0;
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let error = |column: usize, reason: &str| {
+                Err(BlueprintError::Malformed(line_number, column, reason.to_string()))
+            };
+
+            if section == "layout" {
+                if !line.trim().is_empty() {
+                    rows.push((line_number, line));
+                }
+                continue;
+            }
+            if line.trim().is_empty() || line.trim_start().starts_with(';') {
+                continue;
+            }
+            match line.trim() {
+                "legend:" => {
+                    section = "legend";
+                    continue;
+                }
+                "layout:" => {
+                    section = "layout";
+                    layout_line = line_number;
+                    continue;
+                }
+                _ => {}
+            }
+
+            // Everything else is "key = value".
+            let equals = match line.find('=') {
+                Some(equals) => equals,
+                None => return error(1, "expected \"key = value\""),
+            };
+            let key = line[..equals].trim();
+            let value = line[equals + 1..].trim();
+            let value_column = line.len() - line[equals + 1..].trim_start().len() + 1;
+
+            if section == "legend" {
+                let mut chars = key.chars();
+                let c = match (chars.next(), chars.next()) {
+                    (Some(c), None) => c,
+                    _ => return error(1, "legend keys must be one character"),
+                };
+                match tile_type_from_name(value) {
+                    Some(tile_type) => legend.insert(c, tile_type),
+                    None => return error(value_column, &format!("unknown tile {:?}", value)),
+                };
+                continue;
+            }
+
+            match key {
+                "name" => name = Some(value.to_string()),
+                "speed" => match value.parse::<f64>() {
+                    Ok(s) if s > 0.0 => speed = s,
+                    _ => return error(value_column, "speed must be a positive number"),
+                },
+                "spawn" => {
+                    let numbers: Vec<Result<usize, _>> =
+                        value.split_whitespace().map(|n| n.parse()).collect();
+                    match numbers.as_slice() {
+                        [Ok(x), Ok(y)] => spawn = Some((*x, *y, line_number, value_column)),
+                        _ => return error(value_column, "spawn must be a column and a row"),
+                    }
+                }
+                _ => return error(1, &format!("unknown setting {:?}", key)),
+            }
+        }
+
+        // Build the tile grid.
+        let last_line = text.lines().count().max(1);
+        if rows.is_empty() {
+            return Err(BlueprintError::Malformed(
+                last_line,
+                1,
+                "missing layout".to_string(),
+            ));
+        }
+        let width = rows[0].1.chars().count();
+        let height = rows.len();
+        let mut tiles = vec![vec![TileType::Air; height]; width];
+        let mut wheel = None;
+        let mut portals = 0;
+        for (y, &(line_number, row)) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(BlueprintError::Malformed(
+                    line_number,
+                    row.chars().count().min(width) + 1,
+                    format!("expected a row of {} tiles", width),
+                ));
+            }
+            for (x, c) in row.chars().enumerate() {
+                let tile_type = match legend.get(&c) {
+                    Some(tile_type) => tile_type.clone(),
+                    None => {
+                        return Err(BlueprintError::Malformed(
+                            line_number,
+                            x + 1,
+                            format!("{:?} is not in the legend", c),
+                        ))
+                    }
+                };
+                match tile_type {
+                    TileType::Wheel if wheel.is_some() => {
+                        return Err(BlueprintError::Malformed(
+                            line_number,
+                            x + 1,
+                            "a ship has exactly one wheel".to_string(),
+                        ))
+                    }
+                    TileType::Wheel => wheel = Some((x, y)),
+                    TileType::Portal => portals += 1,
+                    _ => {}
+                }
+                tiles[x][y] = tile_type;
+            }
+        }
+
+        // Validate the ship as a whole.
+        if wheel.is_none() {
+            return Err(BlueprintError::Malformed(
+                layout_line,
+                1,
+                "a ship has exactly one wheel".to_string(),
+            ));
+        }
+        if portals == 0 {
+            return Err(BlueprintError::Malformed(
+                layout_line,
+                1,
+                "a ship needs at least one portal".to_string(),
+            ));
+        }
+        let ship_tiles: Vec<Vec<Tile>> = tiles
+            .iter()
+            .map(|column| column.iter().map(|t| Tile::new(t.clone())).collect())
+            .collect();
+        if !is_connected(&ship_tiles) {
+            return Err(BlueprintError::Malformed(
+                layout_line,
+                1,
+                "a ship must be in one piece".to_string(),
+            ));
+        }
+        let spawn = match spawn {
+            Some((x, y, line_number, column)) => {
+                if x >= width || y >= height || tiles[x][y] == TileType::Air {
+                    return Err(BlueprintError::Malformed(
+                        line_number,
+                        column,
+                        "spawn must be on the ship".to_string(),
+                    ));
+                }
+                (x, y)
+            }
+            None => wheel.unwrap(),
+        };
+
+        Ok(Blueprint {
+            name: name.unwrap_or_else(|| "Unnamed".to_string()),
+            speed: speed,
+            spawn: spawn,
+            tiles: tiles,
+        })
+    }
+
+    /*
+        Creates a blueprint of an existing ship, e.g. one changed in build mode.
+
+        @param ship The ship.
+        @param name The blueprint's name.
+        @param spawn The tile the player should start on.
+        @return Blueprint The blueprint.
+    */
+    pub fn from_ship(ship: &Ship, name: &str, spawn: (usize, usize)) -> Blueprint {
+        Blueprint {
+            name: name.to_string(),
+            speed: ship.speed,
+            spawn: spawn,
+            tiles: ship.tiles
+                .iter()
+                .map(|column| column.iter().map(|t| t.tile_type.clone()).collect())
+                .collect(),
+        }
+    }
+
+    /*
+        Writes the blueprint as text, with a legend of the tiles used.
+
+        @return String The blueprint text.
+    */
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "name = {}\nspeed = {}\nspawn = {} {}\n\nlegend:\n",
+            self.name, self.speed, self.spawn.0, self.spawn.1
+        );
+        let mut used: Vec<TileType> = vec![];
+        for column in &self.tiles {
+            for tile_type in column {
+                if !used.contains(tile_type) {
+                    used.push(tile_type.clone());
+                }
+            }
+        }
+        for tile_type in &[
+            TileType::Air,
+            TileType::WoodFloor,
+            TileType::Wheel,
+            TileType::Portal,
+            TileType::Workbench,
+            TileType::Lookout,
+            TileType::Cannon,
+        ] {
+            if used.contains(tile_type) {
+                out.push_str(&format!(
+                    "{} = {}\n",
+                    legend_char(tile_type),
+                    tile_type_name(tile_type)
+                ));
+            }
+        }
+        out.push_str("\nlayout:\n");
+        for y in 0..self.tiles[0].len() {
+            for column in &self.tiles {
+                out.push(legend_char(&column[y]));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/*
+    Determines the file of a blueprint in the ships folder.
+
+    @param name The file name, without extension.
+    @return Result<PathBuf, BlueprintError> The path.
+*/
+fn blueprint_path(name: &str) -> Result<PathBuf, BlueprintError> {
+    let folder = Search::ParentsThenKids(3, 3)
+        .for_folder("ships")
+        .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "ships folder not found"))?;
+    Ok(folder.join(format!("{}.{}", name, BLUEPRINT_EXTENSION)))
+}
+
+/*
+    Finds the tile type for its name in a blueprint. Only ship tiles are allowed.
+
+    @param name The tile's name.
+    @return Option<TileType> The tile type, if the name is known.
+*/
+fn tile_type_from_name(name: &str) -> Option<TileType> {
+    match name {
+        "air" => Some(TileType::Air),
+        "wood_floor" => Some(TileType::WoodFloor),
+        "wheel" => Some(TileType::Wheel),
+        "portal" => Some(TileType::Portal),
+        "workbench" => Some(TileType::Workbench),
+        "lookout" => Some(TileType::Lookout),
+        "cannon" => Some(TileType::Cannon),
+        _ => None,
+    }
+}
+
+/*
+    Generates the name of a ship tile type in a blueprint.
+
+    @param tile_type The tile type.
+    @return &str The name.
+*/
+fn tile_type_name(tile_type: &TileType) -> &'static str {
+    match *tile_type {
+        TileType::WoodFloor => "wood_floor",
+        TileType::Wheel => "wheel",
+        TileType::Portal => "portal",
+        TileType::Workbench => "workbench",
+        TileType::Lookout => "lookout",
+        TileType::Cannon => "cannon",
+        _ => "air",
+    }
+}
+
+/*
+    Generates the character used for a tile type in exported blueprints.
+
+    @param tile_type The tile type.
+    @return char The character.
+*/
+fn legend_char(tile_type: &TileType) -> char {
+    match *tile_type {
+        TileType::WoodFloor => '#',
+        TileType::Wheel => 'W',
+        TileType::Portal => 'P',
+        TileType::Workbench => 'B',
+        TileType::Lookout => 'L',
+        TileType::Cannon => 'C',
+        _ => '.',
+    }
+}
@@ -4,8 +4,6 @@
 pub const IMAGE_SIZE: f64 = 8.0;
 pub const IMAGE_SCALE: f64 = 4.0;
 pub const IMAGE_SIZE_SCALED: f64 = IMAGE_SCALE * IMAGE_SIZE;
-pub const MAP_WIDTH: usize = 1000;
-pub const MAP_HEIGHT: usize = 1000;
 
 pub const IMG_SKY: &str = "sky";
 pub const IMG_CLOUD_1: &str = "cloud_1";
@@ -14,6 +12,9 @@ pub const IMG_CLOUD_2: &str = "cloud_2";
 pub const IMG_WOOD_FLOOR: &str = "wood_floor";
 
 pub const IMG_WHEEL: &str = "wheel";
+pub const IMG_WORKBENCH: &str = "workbench";
+pub const IMG_LOOKOUT: &str = "lookout";
+pub const IMG_CANNON: &str = "cannon";
 pub const IMG_TREE: &str = "tree";
 pub const IMG_GRUNE: &str = "grune";
 pub const IMG_PORTAL: &str = "portal";
@@ -23,6 +24,7 @@ pub const IMG_ITEM_BISKET: &str = "bisket_item";
 pub const IMG_ITEM_SWORD: &str = "sword_item";
 pub const IMG_ITEM_GRUNE: &str = "grune_item";
 pub const IMG_ITEM_LOGS: &str = "logs_item";
+pub const IMG_ITEM_PLANKS: &str = "planks_item";
 
 pub const IMG_STONE_WALL: &str = "stone_wall_1";
 pub const IMG_STONE_WALL_TEXTURE: &str = "stone_wall_2";
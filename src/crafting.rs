@@ -0,0 +1,199 @@
+//! Crafting turns resources into other items, following a registry of recipes.
+//! Some recipes can only be made while standing at a station, such as a workbench.
+
+use inventory::Inventory;
+use item::*;
+use std::collections::HashMap;
+use std::fmt;
+use tile::TileType;
+
+/**
+    Reasons crafting can fail.
+
+    @variant UnknownRecipe There is no such recipe.
+    @variant NeedsStation The recipe must be made at a station, such as a workbench.
+    @variant MissingMaterials Not enough of the recipe's inputs are carried.
+    @variant InventoryFull The outputs wouldn't fit in the inventory.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum CraftError {
+    UnknownRecipe,
+    NeedsStation(TileType),
+    MissingMaterials,
+    InventoryFull,
+}
+
+impl fmt::Display for CraftError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CraftError::UnknownRecipe => write!(f, "No such recipe"),
+            CraftError::NeedsStation(ref station) => {
+                write!(f, "Stand at a {} to make this", station_name(station))
+            }
+            CraftError::MissingMaterials => write!(f, "Not enough materials"),
+            CraftError::InventoryFull => write!(f, "No room in the inventory"),
+        }
+    }
+}
+
+/**
+    Implementation of the Recipe object.
+
+    @field name The recipe's name, shown in the crafting menu.
+    @field inputs The item types used up, and how many of each.
+    @field outputs The item types made, and how many of each.
+    @field station The tile the crafter must stand on, or None to craft anywhere.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recipe {
+    pub name: String,
+    pub inputs: Vec<(ItemType, u32)>,
+    pub outputs: Vec<(ItemType, u32)>,
+    pub station: Option<TileType>,
+}
+
+impl Recipe {
+    /*
+        Recipe constructor.
+
+        @param name The recipe's name.
+        @param inputs The item types used up, and how many of each.
+        @param outputs The item types made, and how many of each.
+        @param station The tile needed to craft, if any.
+        @return Recipe Returns self.
+    */
+    pub fn new(
+        name: &str,
+        inputs: Vec<(ItemType, u32)>,
+        outputs: Vec<(ItemType, u32)>,
+        station: Option<TileType>,
+    ) -> Self {
+        Recipe {
+            name: name.to_string(),
+            inputs: inputs,
+            outputs: outputs,
+            station: station,
+        }
+    }
+
+    /*
+        Determines if an inventory has all of the recipe's inputs.
+
+        @param inventory The inventory.
+        @return bool Whether there are enough materials.
+    */
+    pub fn has_materials(&self, inventory: &Inventory) -> bool {
+        self.inputs
+            .iter()
+            .all(|&(ref item_type, count)| inventory.count(item_type) >= count)
+    }
+
+    /*
+        Makes the recipe, swapping its inputs for its outputs in the inventory.
+        The inventory is left unchanged if crafting fails.
+
+        @param inventory The crafter's inventory.
+        @param station The tile the crafter is standing on, if any.
+        @param prototypes The item prototypes outputs are cloned from.
+        @return Result<(), CraftError> Why crafting failed, if it did.
+    */
+    pub fn craft(
+        &self,
+        inventory: &mut Inventory,
+        station: Option<&TileType>,
+        prototypes: &HashMap<String, Item>,
+    ) -> Result<(), CraftError> {
+        if let Some(ref needed) = self.station {
+            if station != Some(needed) {
+                return Err(CraftError::NeedsStation(needed.clone()));
+            }
+        }
+        if !self.has_materials(inventory) {
+            return Err(CraftError::MissingMaterials);
+        }
+
+        // Craft into a copy, so nothing is lost if the outputs don't fit.
+        let mut crafted = inventory.clone();
+        for &(ref item_type, count) in &self.inputs {
+            crafted.remove(item_type, count);
+        }
+        for &(ref item_type, count) in &self.outputs {
+            let prototype = prototypes
+                .get(item_type.name())
+                .expect("recipe output has no item prototype");
+            for _ in 0..count {
+                if !crafted.add(prototype.clone()) {
+                    return Err(CraftError::InventoryFull);
+                }
+            }
+        }
+        *inventory = crafted;
+        Ok(())
+    }
+
+    /*
+        Describes the recipe for the crafting menu, e.g. "planks: 1 logs -> 4 planks".
+
+        @return String The description.
+    */
+    pub fn describe(&self) -> String {
+        let list = |items: &[(ItemType, u32)]| {
+            items
+                .iter()
+                .map(|&(ref item_type, count)| format!("{} {}", count, item_type.name()))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let mut description = format!(
+            "{}: {} -> {}",
+            self.name,
+            list(&self.inputs),
+            list(&self.outputs)
+        );
+        if let Some(ref station) = self.station {
+            description.push_str(&format!(" (at a {})", station_name(station)));
+        }
+        description
+    }
+}
+
+/*
+    Creates the registry of every recipe in the game.
+
+    @return Vec<Recipe> The recipes, in the order shown in the crafting menu.
+*/
+pub fn generate_recipes() -> Vec<Recipe> {
+    vec![
+        Recipe::new(
+            "planks",
+            vec![(ItemType::Resource(ResourceType::Logs), 1)],
+            vec![(ItemType::Resource(ResourceType::Planks), 4)],
+            Some(TileType::Workbench),
+        ),
+        Recipe::new(
+            "sword",
+            vec![
+                (ItemType::Resource(ResourceType::Logs), 2),
+                (ItemType::Resource(ResourceType::Grune), 1),
+            ],
+            vec![(ItemType::Interactable(InteractableType::Sword), 1)],
+            Some(TileType::Workbench),
+        ),
+    ]
+}
+
+/*
+    Generates the name of a crafting station.
+
+    @param station The station's tile type.
+    @return &str The name.
+*/
+pub fn station_name(station: &TileType) -> &'static str {
+    match *station {
+        TileType::Workbench => "workbench",
+        TileType::Wheel => "wheel",
+        TileType::Lookout => "lookout",
+        TileType::Cannon => "cannon",
+        _ => "station",
+    }
+}
@@ -1,17 +1,24 @@
-//! The Creature object is the template for any NPC in AOE. Currently this is used for
-//! the Player, but default functionality will eventually apply for Monsters and Crew.
+//! The Creature object is the template for any NPC in AOE. It is used for the Player
+//! and for hostile creatures, which are given an AI, and will eventually apply for Crew.
 //!
 //! The object handles Creature generation, position and movement, item interaction,
 //! and graphics rendering.
 
+use ai::Ai;
 use constants::*;
+use input::Action;
+use inventory::*;
 use item::*;
 use misc::*;
 use piston_window::*;
 use std::collections::HashMap;
-use std::mem::swap;
 
-#[derive(Debug, PartialEq)]
+const ATTACK_COOLDOWN: f64 = 0.5;
+const SWING_TIME: f64 = 0.2;
+const KNOCKBACK_SPEED: f64 = 360.0;
+const KNOCKBACK_DRAG: f64 = 1800.0;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum CreatureState {
     Normal,
     ControllingShip,
@@ -26,16 +33,26 @@ pub enum CreatureState {
     @field self_vel_x Creature's horizontal velocity.
     @field self_vel_y Creature's vertical velocity.
     @field directions Vector recording directions Creature is moving.
+    @field analog How far a stick moves the Creature on each axis, overriding directions.
     @field other_vel_x Horizontal velocity of other object(s) affecting Creature.
     @field other_vel_y Horizontal velocity of other object(s) affecting Creature.
-    @field speed Creature's maximum speed when moving.
+    @field speed Creature's maximum speed when moving, in pixels per second.
+    @field last_x Creature's horizontal position before the latest update, for interpolation.
+    @field last_y Creature's vertical position before the latest update, for interpolation.
     @field health Creature's health.
-    @field inventory Creature's item inventory.
+    @field inventory Creature's item inventory, with one slot held in hand.
+    @field attack_cooldown Seconds until the Creature can attack again.
+    @field swing_timer Seconds left of the current attack's swing animation.
+    @field knockback_x Horizontal velocity from being hit, fading over time.
+    @field knockback_y Vertical velocity from being hit, fading over time.
+    @field ai Behaviour of creatures not controlled by the player, None for the player.
+    @field color Tint the Creature's sprite is drawn with.
     @field dir Creature's direction for drawing grapics.
     @field sprite_index Used for animating the Creature's sprite.
     @field frames_since_last_draw Used for updating Creature animation.
     @field animation_rate Throttles how often the sprites update.
 */
+#[derive(Clone)]
 pub struct Creature {
     pub creature_state: CreatureState,
     pub x: f64,
@@ -43,11 +60,20 @@ pub struct Creature {
     pub self_vel_x: f64,
     pub self_vel_y: f64,
     pub directions: Vec<Direction>,
+    pub analog: Option<(f64, f64)>,
     pub other_vel_x: f64,
     pub other_vel_y: f64,
     pub speed: f64,
+    pub last_x: f64,
+    pub last_y: f64,
     pub health: i32,
-    pub inventory: Option<Item>,
+    pub inventory: Inventory,
+    pub attack_cooldown: f64,
+    pub swing_timer: f64,
+    pub knockback_x: f64,
+    pub knockback_y: f64,
+    pub ai: Option<Ai>,
+    pub color: [f32; 4],
     dir: Direction,
     sprite_index: i32,
     frames_since_last_draw: i32,
@@ -66,11 +92,20 @@ impl Creature {
             self_vel_x: 0.0,
             self_vel_y: 0.0,
             directions: vec![],
+            analog: None,
             other_vel_x: 0.0,
             other_vel_y: 0.0,
-            speed: 2.0,
+            speed: 120.0,
+            last_x: 0.0,
+            last_y: 0.0,
             health: 3,
-            inventory: None,
+            inventory: Inventory::new(INVENTORY_SLOTS, MAX_CARRY_WEIGHT),
+            attack_cooldown: 0.0,
+            swing_timer: 0.0,
+            knockback_x: 0.0,
+            knockback_y: 0.0,
+            ai: None,
+            color: [1.0; 4],
             dir: Direction::S,
             sprite_index: 0,
             frames_since_last_draw: 0,
@@ -81,23 +116,38 @@ impl Creature {
     /* 
         Updates the position of the Creature based on other objects acting on it,
         e.g. the airship's speed.
+
+        @param dt The time step in seconds.
     */
-    pub fn update_position_other(&mut self) {
-        self.x += self.other_vel_x;
-        self.y += self.other_vel_y;
+    pub fn update_position_other(&mut self, dt: f64) {
+        self.x += self.other_vel_x * dt;
+        self.y += self.other_vel_y * dt;
     }
 
     /* 
         Updates the position of the Creature based on its own velocity,
         e.g. the player's controls.
+
+        @param dt The time step in seconds.
+    */
+    pub fn update_position_self(&mut self, dt: f64) {
+        self.x += (self.self_vel_x + self.knockback_x) * dt;
+        self.y += (self.self_vel_y + self.knockback_y) * dt;
+    }
+
+    /*
+        Counts down the attack cooldown and swing, and slows any knockback.
+
+        @param dt The time step in seconds.
     */
-    pub fn update_position_self(&mut self) {
-        self.x += self.self_vel_x;
-        self.y += self.self_vel_y;
-        if let Some(ref mut item) = self.inventory {
-            // Update inventory position as well.
-            item.x = self.x;
-            item.y = self.y;
+    pub fn update_timers(&mut self, dt: f64) {
+        self.attack_cooldown = (self.attack_cooldown - dt).max(0.0);
+        self.swing_timer = (self.swing_timer - dt).max(0.0);
+        let speed = self.knockback_x.hypot(self.knockback_y);
+        if speed > 0.0 {
+            let slowed = (speed - KNOCKBACK_DRAG * dt).max(0.0);
+            self.knockback_x *= slowed / speed;
+            self.knockback_y *= slowed / speed;
         }
     }
 
@@ -121,17 +171,65 @@ impl Creature {
         }
     }
 
+    /*
+        Turns the Creature to face along a heading, e.g. towards the cursor.
+
+        @param dx How far the heading goes horizontally.
+        @param dy How far the heading goes vertically.
+    */
+    pub fn face_towards(&mut self, dx: f64, dy: f64) {
+        self.dir = if dx.abs() > dy.abs() {
+            if dx > 0.0 {
+                Direction::E
+            } else {
+                Direction::W
+            }
+        } else if dy > 0.0 {
+            Direction::S
+        } else {
+            Direction::N
+        };
+    }
+
+    /*
+        Finds the direction the Creature is facing.
+
+        @return Direction The direction.
+    */
+    pub fn facing(&self) -> Direction {
+        self.dir
+    }
+
     /*
         Calculates the location where the Creature is approaching -
         used for collision detection.
 
+        @param dt The time step in seconds.
         @return f64 The future x and y positions of the Creature.
     */
-    pub fn x_to_be_location(&self) -> f64 {
-        self.x + self.self_vel_x
+    pub fn x_to_be_location(&self, dt: f64) -> f64 {
+        self.x + (self.self_vel_x + self.knockback_x) * dt
+    }
+    pub fn y_to_be_location(&self, dt: f64) -> f64 {
+        self.y + (self.self_vel_y + self.knockback_y) * dt
+    }
+
+    /*
+        Remembers the current position, so drawing can interpolate from it.
+    */
+    pub fn store_last_position(&mut self) {
+        self.last_x = self.x;
+        self.last_y = self.y;
     }
-    pub fn y_to_be_location(&self) -> f64 {
-        self.y + self.self_vel_y
+
+    /*
+        Calculates where to draw the Creature between its last and current position.
+
+        @param alpha How far between the last and current update, from 0 to 1.
+        @return (f64, f64) The x and y position to draw at.
+    */
+    pub fn render_position(&self, alpha: f64) -> (f64, f64) {
+        (lerp(self.last_x, self.x, alpha), lerp(self.last_y, self.y, alpha))
     }
 
     /*
@@ -140,16 +238,16 @@ impl Creature {
         @param textures The map of sprite / tile textures.
         @param context The context used for drawing, affected by scaling and translation.
         @param graphics Graphics engine.
-        @param w_width Width of the window.
-        @param w_height Height of the window.
+        @param screen_x Horizontal position on screen to draw at.
+        @param screen_y Vertical position on screen to draw at.
     */
     pub fn draw(
         &mut self,
         textures: &HashMap<String, G2dTexture>,
         context: &Context,
         graphics: &mut G2d,
-        w_width: f64,
-        w_height: f64,
+        screen_x: f64,
+        screen_y: f64,
     ) {
         // Determine the proper file extension based on Creature moving.
         let pic_index = self.sprite_index + 1;
@@ -168,11 +266,12 @@ impl Creature {
             pic_index.to_string()
         );
 
-        image(
+        Image::new_color(self.color).draw(
             textures.get(img).expect(&format!("Not found: {:?}", img)),
+            &context.draw_state,
             context
                 .transform
-                .trans(w_width / 2.0, w_height / 2.0) // Draw Player at center of screen.
+                .trans(screen_x, screen_y)
                 .scale(IMAGE_SCALE, IMAGE_SCALE),
             graphics,
         );
@@ -184,22 +283,38 @@ impl Creature {
         }
         self.frames_since_last_draw += 1;
 
-        // Display Creature's item(s).
-        if let Some(_) = self.inventory {
-            let item = self.inventory.clone().unwrap();
-            match item.item_type {
-                ItemType::Food(FoodType::Bisket) => {
-                    let img = IMG_ITEM_BISKET;
-                    image(
-                        textures.get(img).expect(&format!("Not found: {:?}", img)),
-                        context
-                            .transform
-                            .trans(w_width / 2.0, w_height / 2.0 - IMAGE_SIZE_SCALED * 0.7) // Draw above Creature.
-                            .scale(IMAGE_SCALE, IMAGE_SCALE),
-                        graphics,
-                    );
-                }
-                _ => {}
+        // Display the item in the Creature's hand, swinging it in front while attacking.
+        if let Some(img) = self.inventory.held().and_then(|item| item.item_type.image()) {
+            let texture = textures.get(img).expect(&format!("Not found: {:?}", img));
+            if self.swing_timer > 0.0 {
+                let facing_deg = match self.dir {
+                    Direction::N => 0.0,
+                    Direction::E => 90.0,
+                    Direction::S => 180.0,
+                    Direction::W => 270.0,
+                };
+                // Sweep from one side of the facing direction to the other.
+                let progress = 1.0 - self.swing_timer / SWING_TIME;
+                let half = IMAGE_SIZE_SCALED / 2.0;
+                image(
+                    texture,
+                    context
+                        .transform
+                        .trans(screen_x + half, screen_y + half)
+                        .rot_deg(facing_deg - 60.0 + 120.0 * progress)
+                        .trans(-half, -IMAGE_SIZE_SCALED - half)
+                        .scale(IMAGE_SCALE, IMAGE_SCALE),
+                    graphics,
+                );
+            } else {
+                image(
+                    texture,
+                    context
+                        .transform
+                        .trans(screen_x, screen_y - IMAGE_SIZE_SCALED * 0.7) // Draw above Creature.
+                        .scale(IMAGE_SCALE, IMAGE_SCALE),
+                    graphics,
+                );
             }
         }
     }
@@ -223,47 +338,113 @@ impl Creature {
     }
 
     /*
-        Removes item from Creature's inventory.
+        Finds the damage of the weapon in the Creature's hand.
+
+        @return Option<i32> The weapon's damage, or None if no weapon is held.
+    */
+    pub fn weapon_damage(&self) -> Option<i32> {
+        match self.inventory.held() {
+            Some(item) => match item.item_type {
+                ItemType::Interactable(InteractableType::Sword) => Some(item.damage),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    /*
+        Starts a melee attack, if the last one has cooled down.
+
+        @return bool Whether the attack started.
+    */
+    pub fn start_attack(&mut self) -> bool {
+        if self.attack_cooldown > 0.0 {
+            return false;
+        }
+        self.attack_cooldown = ATTACK_COOLDOWN;
+        self.swing_timer = SWING_TIME;
+        true
+    }
+
+    /*
+        Calculates the area a melee attack hits, one tile in front of the Creature.
+
+        @return [f64; 4] The area, as x, y, width and height.
+    */
+    pub fn attack_hitbox(&self) -> [f64; 4] {
+        let (x, y) = match self.dir {
+            Direction::N => (self.x, self.y - IMAGE_SIZE_SCALED),
+            Direction::S => (self.x, self.y + IMAGE_SIZE_SCALED),
+            Direction::W => (self.x - IMAGE_SIZE_SCALED, self.y),
+            Direction::E => (self.x + IMAGE_SIZE_SCALED, self.y),
+        };
+        [x, y, IMAGE_SIZE_SCALED, IMAGE_SIZE_SCALED]
+    }
+
+    /*
+        Calculates the area the Creature takes up, for being hit.
+
+        @return [f64; 4] The area, as x, y, width and height.
+    */
+    pub fn hitbox(&self) -> [f64; 4] {
+        [self.x, self.y, IMAGE_SIZE_SCALED, IMAGE_SIZE_SCALED]
+    }
+
+    /*
+        Pushes the Creature away, e.g. after being hit.
+
+        @param dir The direction to be pushed in.
+    */
+    pub fn knock_back(&mut self, dir: Direction) {
+        let (x, y) = match dir {
+            Direction::N => (0.0, -KNOCKBACK_SPEED),
+            Direction::S => (0.0, KNOCKBACK_SPEED),
+            Direction::W => (-KNOCKBACK_SPEED, 0.0),
+            Direction::E => (KNOCKBACK_SPEED, 0.0),
+        };
+        self.knockback_x = x;
+        self.knockback_y = y;
+    }
+
+    /*
+        Removes one item from the Creature's hand, placing it where the Creature stands.
 
         @return Option<Item> The item that is dropped.
     */
     pub fn drop_item(&mut self) -> Option<Item> {
-        let mut dropped_item = None;
-        swap(&mut self.inventory, &mut dropped_item);
-        dropped_item
+        let mut dropped_item = self.inventory.take_held()?;
+        dropped_item.x = self.x;
+        dropped_item.y = self.y;
+        Some(dropped_item)
     }
 
     /*
-        Adds item to Creature's inventory, if empty.
+        Adds item to Creature's inventory, if there's room and it isn't too heavy.
 
         @param item The item being picked up.
         @return bool Whether pickup was successful. 
     */
     pub fn pickup_item(&mut self, item: Item) -> bool {
-        if let None = self.inventory {
-            self.inventory = Some(item);
-            return true;
-        }
-        false
+        self.inventory.add(item)
     }
 
     /*
-        Handles results of Creature using an item.
+        Handles results of Creature using the item in its hand.
     */
     pub fn use_item(&mut self) {
-        let mut item_used = false;
-        if let Some(ref item) = self.inventory {
-            match item.item_type {
+        let item_used = match self.inventory.held() {
+            Some(item) => match item.item_type {
                 ItemType::Food(_) => {
                     // Heal Creature.
                     self.health += 1;
-                    item_used = true;
+                    true
                 }
-                _ => {}
-            }
-        }
+                _ => false,
+            },
+            None => false,
+        };
         if item_used {
-            self.inventory = None; // Empty inventory.
+            self.inventory.take_held();
         }
     }
 
@@ -289,6 +470,7 @@ impl Creature {
     */
     fn state_normal(&mut self) {
         self.directions = vec![];
+        self.analog = None;
         self.self_vel_x = 0.0;
         self.self_vel_y = 0.0;
         self.creature_state = CreatureState::ControllingShip;
@@ -300,73 +482,36 @@ impl Moveable for Creature {
     /*
         Handles input from user for moving the Player.
 
-        @param state The Button State of some button (pressed or released).
-        @param key The Key pressed by the user (e.g. W, A, S, or D).
+        @param action The action, of which only movement is used.
+        @param active Whether the action starts or stops.
     */
-    fn handle_input(&mut self, state: &ButtonState, key: &Option<Key>) {
-        match *key {
-            // Add directions for Player.
-            Some(Key::W) => {
-                let dir = Direction::N;
-                if let Some(index) = self.directions.iter().position(|&x| x == dir) {
-                    if *state == ButtonState::Release {
-                        self.directions.remove(index);
-                    }
-                } else {
-                    if *state == ButtonState::Press {
-                        self.directions.push(dir);
-                    }
-                }
-            }
-            Some(Key::A) => {
-                let dir = Direction::W;
-                if let Some(index) = self.directions.iter().position(|&x| x == dir) {
-                    if *state == ButtonState::Release {
-                        self.directions.remove(index);
-                    }
-                } else {
-                    if *state == ButtonState::Press {
-                        self.directions.push(dir);
-                    }
-                }
-            }
-            Some(Key::S) => {
-                let dir = Direction::S;
-                if let Some(index) = self.directions.iter().position(|&x| x == dir) {
-                    if *state == ButtonState::Release {
-                        self.directions.remove(index);
-                    }
-                } else {
-                    if *state == ButtonState::Press {
-                        self.directions.push(dir);
-                    }
-                }
-            }
-            Some(Key::D) => {
-                let dir = Direction::E;
-                if let Some(index) = self.directions.iter().position(|&x| x == dir) {
-                    if *state == ButtonState::Release {
-                        self.directions.remove(index);
-                    }
-                } else {
-                    if *state == ButtonState::Press {
-                        self.directions.push(dir);
-                    }
-                }
-            }
-            _ => {}
+    fn handle_input(&mut self, action: Action, active: bool) {
+        if let Some(dir) = action.direction() {
+            update_directions(&mut self.directions, dir, active);
         }
     }
 
+    /*
+        Handles a stick moving the Player, by how far it's pushed.
+
+        @param x How far to move horizontally, from -1 to 1.
+        @param y How far to move vertically, from -1 to 1.
+    */
+    fn handle_analog(&mut self, x: f64, y: f64) {
+        self.analog = if x == 0.0 && y == 0.0 { None } else { Some((x, y)) };
+    }
+
     /*
         Updates Creature position based on velocities.
         Override for Creature.
+
+        @param dt The time step in seconds.
     */
-    fn update_position(&mut self) {
-        self.x += self.other_vel_x;
-        self.y += self.other_vel_y;
-        self.x += self.self_vel_x;
-        self.y += self.self_vel_y;
+    fn update_position(&mut self, dt: f64) {
+        self.x += self.other_vel_x * dt;
+        self.y += self.other_vel_y * dt;
+        self.x += self.self_vel_x * dt;
+        self.y += self.self_vel_y * dt;
     }
 
     /*
@@ -377,6 +522,12 @@ impl Moveable for Creature {
         @param dy The difference in y velocity.
     */
     fn update_self_velocity(&mut self) {
+        if let Some((x, y)) = self.analog {
+            self.self_vel_x = x * self.speed;
+            self.self_vel_y = y * self.speed;
+            return;
+        }
+
         let mut dx = 0.0;
         let mut dy = 0.0;
 
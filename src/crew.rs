@@ -0,0 +1,169 @@
+//! Crew are friendly creatures found living on islands.
+//!
+//! Talking to an islander recruits them. Recruits follow the player around the island,
+//! board the ship through the portal with them, and live on the ship from then on.
+//!
+//! Aboard, crew can be stationed at the wheel, a lookout, a workbench or a cannon, where
+//! they steer towards a waypoint, spot islands, craft queued recipes or shoot at enemies.
+//! Crew whose role suits their station work faster.
+
+use constants::*;
+use creature::Creature;
+use tile::TileType;
+
+pub const RECRUIT_RANGE: f64 = 1.5 * IMAGE_SIZE_SCALED;
+pub const FOLLOW_DISTANCE: f64 = 1.5 * IMAGE_SIZE_SCALED;
+pub const BOARDING_RANGE: f64 = 6.0 * IMAGE_SIZE_SCALED;
+pub const SKILLED_WORK_RATE: f64 = 1.5;
+pub const ARRIVAL_DISTANCE: f64 = 4.0 * IMAGE_SIZE_SCALED;
+pub const LOOKOUT_INTERVAL: f64 = 2.0;
+pub const LOOKOUT_RANGE: f64 = 96.0;
+pub const CRAFT_TIME: f64 = 5.0;
+pub const CANNON_INTERVAL: f64 = 1.5;
+pub const CANNON_RANGE: f64 = 8.0 * IMAGE_SIZE_SCALED;
+pub const CANNON_DAMAGE: i32 = 5;
+pub const CREW_NAMES: &[&str] = &[
+    "Abel", "Brin", "Corra", "Dov", "Esk", "Fenn", "Gil", "Hask", "Ilse", "Jory", "Kell", "Lune",
+];
+
+/**
+    The jobs a crew member can do aboard the ship.
+
+    @variant Deckhand Keeps the ship running.
+    @variant Lookout Keeps watch from the deck.
+    @variant Carpenter Builds and repairs.
+    @variant Gunner Mans the cannons.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    Deckhand,
+    Lookout,
+    Carpenter,
+    Gunner,
+}
+
+impl Role {
+    /*
+        Lists every role, in the order they're chosen from.
+
+        @return [Role; 4] The roles.
+    */
+    pub fn all() -> [Role; 4] {
+        [Role::Deckhand, Role::Lookout, Role::Carpenter, Role::Gunner]
+    }
+
+    /*
+        Generates the name of the role, used in saves and on screen.
+
+        @return &str The name.
+    */
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Role::Deckhand => "deckhand",
+            Role::Lookout => "lookout",
+            Role::Carpenter => "carpenter",
+            Role::Gunner => "gunner",
+        }
+    }
+
+    /*
+        Finds the role for its name.
+
+        @param name The role's name.
+        @return Option<Role> The role, if the name is known.
+    */
+    pub fn from_name(name: &str) -> Option<Role> {
+        Role::all().iter().find(|role| role.name() == name).cloned()
+    }
+
+    /*
+        Determines the station the role is best at.
+
+        @return TileType The station's tile type.
+    */
+    pub fn station(&self) -> TileType {
+        match *self {
+            Role::Deckhand => TileType::Wheel,
+            Role::Lookout => TileType::Lookout,
+            Role::Carpenter => TileType::Workbench,
+            Role::Gunner => TileType::Cannon,
+        }
+    }
+}
+
+/**
+    Implementation of the CrewMember object.
+
+    @field creature The crew member's body, positioned in world coordinates.
+    @field name The crew member's name.
+    @field role The crew member's job.
+    @field recruited Whether the player has recruited them.
+    @field on_ship Whether they're aboard the ship, moving with it.
+    @field station The ship tile they work at, as column and row, or None to stand idle.
+    @field task_timer Seconds of work done towards their station's next task.
+    @field home The id of the island they were found on, if known.
+*/
+#[derive(Clone)]
+pub struct CrewMember {
+    pub creature: Creature,
+    pub name: String,
+    pub role: Role,
+    pub recruited: bool,
+    pub on_ship: bool,
+    pub station: Option<(usize, usize)>,
+    pub task_timer: f64,
+    pub home: Option<u64>,
+}
+
+impl CrewMember {
+    /*
+        CrewMember constructor. New crew members are islanders waiting to be recruited.
+
+        @param name The crew member's name.
+        @param role The crew member's job.
+        @param x The x position in the world.
+        @param y The y position in the world.
+        @return CrewMember Returns self.
+    */
+    pub fn new(name: &str, role: Role, x: f64, y: f64) -> Self {
+        let mut creature = Creature::new();
+        creature.x = x;
+        creature.y = y;
+        creature.health = 10;
+        creature.color = [0.7, 1.0, 0.7, 1.0];
+        creature.store_last_position();
+        CrewMember {
+            creature: creature,
+            name: name.to_string(),
+            role: role,
+            recruited: false,
+            on_ship: false,
+            station: None,
+            task_timer: 0.0,
+            home: None,
+        }
+    }
+
+    /*
+        Determines how fast the crew member works at a station.
+
+        @param station The station's tile type.
+        @return f64 How many seconds of work they do each second.
+    */
+    pub fn work_rate(&self, station: &TileType) -> f64 {
+        if self.role.station() == *station {
+            SKILLED_WORK_RATE
+        } else {
+            1.0
+        }
+    }
+
+    /*
+        Describes the crew member, e.g. "Abel the lookout".
+
+        @return String The description.
+    */
+    pub fn describe(&self) -> String {
+        format!("{} the {}", self.name, self.role.name())
+    }
+}
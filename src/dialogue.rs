@@ -0,0 +1,444 @@
+//! Dialogue lets the player talk with NPCs, following conversation trees from data files.
+//!
+//! A dialogue is made of named nodes. Each node has a speaker, a few lines, and either
+//! choices leading to other nodes or a `next` node to go on to. Choices can depend on
+//! conditions, and entering a node can have effects, like giving the player an item:
+//!
+//! ```text
+//! ; Comments start with a semicolon.
+//! start = greeting
+//!
+//! node greeting
+//! speaker = $name
+//! line = Ahoy! A ship, out here?
+//! choice = [has bisket 1] [not flag fed] Have a bisket. -> thanks
+//! choice = Farewell. -> end
+//!
+//! node thanks
+//! speaker = $name
+//! line = Much obliged!
+//! effect = take bisket 1
+//! effect = set fed
+//! next = end
+//! ```
+//!
+//! `$name` is replaced with the name of whoever is being talked to, and `end` finishes
+//! the conversation.
+
+use find_folder::Search;
+use inventory::Inventory;
+use item::ItemType;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+
+pub const END_NODE: &str = "end";
+const DIALOGUE_EXTENSION: &str = "dlg";
+const NAME_PLACEHOLDER: &str = "$name";
+
+/**
+    Errors that can occur while reading dialogue.
+
+    @variant Io The file could not be read.
+    @variant Malformed The dialogue is invalid, with its name, the line and the reason.
+*/
+#[derive(Debug)]
+pub enum DialogueError {
+    Io(io::Error),
+    Malformed(String, usize, String),
+}
+
+impl From<io::Error> for DialogueError {
+    fn from(error: io::Error) -> Self {
+        DialogueError::Io(error)
+    }
+}
+
+impl fmt::Display for DialogueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DialogueError::Io(ref e) => write!(f, "{}", e),
+            DialogueError::Malformed(ref name, line, ref reason) => {
+                write!(f, "{}, line {}: {}", name, line, reason)
+            }
+        }
+    }
+}
+
+/**
+    Something that must be true for a choice to be offered.
+
+    @variant HasItem The player carries at least this many of an item.
+    @variant Flag The flag has been set.
+    @variant NotFlag The flag hasn't been set.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    HasItem(ItemType, u32),
+    Flag(String),
+    NotFlag(String),
+}
+
+impl Condition {
+    /*
+        Determines if the condition holds.
+
+        @param inventory The player's inventory.
+        @param flags The flags set so far.
+        @return bool Whether the condition is met.
+    */
+    pub fn is_met(&self, inventory: &Inventory, flags: &HashSet<String>) -> bool {
+        match *self {
+            Condition::HasItem(ref item_type, count) => inventory.count(item_type) >= count,
+            Condition::Flag(ref flag) => flags.contains(flag),
+            Condition::NotFlag(ref flag) => !flags.contains(flag),
+        }
+    }
+}
+
+/**
+    Something that happens when a node is reached.
+
+    @variant Give Gives the player some of an item.
+    @variant Take Takes some of an item from the player.
+    @variant SetFlag Sets a flag.
+    @variant ClearFlag Clears a flag.
+    @variant Recruit The NPC being talked to joins the crew.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum Effect {
+    Give(ItemType, u32),
+    Take(ItemType, u32),
+    SetFlag(String),
+    ClearFlag(String),
+    Recruit,
+}
+
+/**
+    Implementation of the Choice object, an answer the player can give.
+
+    @field text What the player says.
+    @field conditions What must hold for the choice to be offered.
+    @field next The node the choice leads to.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Choice {
+    pub text: String,
+    pub conditions: Vec<Condition>,
+    pub next: String,
+}
+
+/**
+    Implementation of the Node object, one step of a conversation.
+
+    @field speaker Who is talking.
+    @field lines What they say, shown one at a time.
+    @field choices Answers the player can give after the last line.
+    @field effects What happens when the node is reached.
+    @field next The node to go on to when there are no choices, or None to finish.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub speaker: String,
+    pub lines: Vec<String>,
+    pub choices: Vec<Choice>,
+    pub effects: Vec<Effect>,
+    pub next: Option<String>,
+}
+
+/**
+    Implementation of the Dialogue object.
+
+    @field name The dialogue's name, its file name without extension.
+    @field start The node conversations begin at.
+    @field nodes The nodes, keyed by name.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dialogue {
+    pub name: String,
+    pub start: String,
+    pub nodes: HashMap<String, Node>,
+}
+
+impl Dialogue {
+    /*
+        Reads every dialogue in the dialogue folder in the assets.
+
+        @return Result<HashMap<String, Dialogue>, DialogueError> The dialogues, keyed by name.
+    */
+    pub fn load_all() -> Result<HashMap<String, Dialogue>, DialogueError> {
+        let folder = Search::ParentsThenKids(3, 3)
+            .for_folder("dialogue")
+            .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "dialogue folder not found"))?;
+        let mut dialogues = HashMap::new();
+        for entry in fs::read_dir(folder)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(DIALOGUE_EXTENSION) {
+                continue;
+            }
+            let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            let dialogue = Dialogue::parse(&name, &fs::read_to_string(&path)?)?;
+            dialogues.insert(name, dialogue);
+        }
+        Ok(dialogues)
+    }
+
+    /*
+        Reads a dialogue from text.
+
+        @param name The dialogue's name.
+        @param text The dialogue text.
+        @return Result<Dialogue, DialogueError> The dialogue.
+    */
+    pub fn parse(name: &str, text: &str) -> Result<Dialogue, DialogueError> {
+        let mut start = None;
+        let mut nodes: HashMap<String, Node> = HashMap::new();
+        let mut node_lines: HashMap<String, usize> = HashMap::new();
+        let mut targets: Vec<(usize, String)> = vec![];
+        let mut current: Option<String> = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let error = |reason: &str| {
+                Err(DialogueError::Malformed(
+                    name.to_string(),
+                    line_number,
+                    reason.to_string(),
+                ))
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            if let Some(node_name) = line.strip_prefix("node ") {
+                let node_name = node_name.trim().to_string();
+                if node_name == END_NODE || nodes.contains_key(&node_name) {
+                    return error(&format!("node {:?} is already defined", node_name));
+                }
+                nodes.insert(
+                    node_name.clone(),
+                    Node {
+                        speaker: String::new(),
+                        lines: vec![],
+                        choices: vec![],
+                        effects: vec![],
+                        next: None,
+                    },
+                );
+                node_lines.insert(node_name.clone(), line_number);
+                current = Some(node_name);
+                continue;
+            }
+
+            // Everything else is "key = value".
+            let equals = match line.find('=') {
+                Some(equals) => equals,
+                None => return error("expected \"key = value\""),
+            };
+            let key = line[..equals].trim();
+            let value = line[equals + 1..].trim();
+
+            let node = match current {
+                Some(ref node_name) => nodes.get_mut(node_name).unwrap(),
+                None if key == "start" => {
+                    start = Some((line_number, value.to_string()));
+                    continue;
+                }
+                None => return error(&format!("unknown setting {:?}", key)),
+            };
+            match key {
+                "speaker" => node.speaker = value.to_string(),
+                "line" => node.lines.push(value.to_string()),
+                "next" => {
+                    targets.push((line_number, value.to_string()));
+                    node.next = Some(value.to_string());
+                }
+                "effect" => match parse_effect(value) {
+                    Some(effect) => node.effects.push(effect),
+                    None => return error(&format!("unknown effect {:?}", value)),
+                },
+                "choice" => match parse_choice(value) {
+                    Ok(choice) => {
+                        targets.push((line_number, choice.next.clone()));
+                        node.choices.push(choice);
+                    }
+                    Err(reason) => return error(&reason),
+                },
+                _ => return error(&format!("unknown setting {:?}", key)),
+            }
+        }
+
+        // Validate the dialogue as a whole.
+        let malformed =
+            |line: usize, reason: String| Err(DialogueError::Malformed(name.to_string(), line, reason));
+        let (start_line, start) = match start {
+            Some(start) => start,
+            None => return malformed(1, "missing start".to_string()),
+        };
+        targets.push((start_line, start.clone()));
+        for (line, target) in targets {
+            if target != END_NODE && !nodes.contains_key(&target) {
+                return malformed(line, format!("no node called {:?}", target));
+            }
+        }
+        for (node_name, node) in &nodes {
+            if node.lines.is_empty() {
+                return malformed(node_lines[node_name], format!("node {:?} has no lines", node_name));
+            }
+            if !node.choices.is_empty() && node.next.is_some() {
+                return malformed(
+                    node_lines[node_name],
+                    format!("node {:?} has both choices and next", node_name),
+                );
+            }
+        }
+
+        let dialogue = Dialogue {
+            name: name.to_string(),
+            start: start,
+            nodes: nodes,
+        };
+        if let Some(node_name) = dialogue.unreachable_nodes().first() {
+            return malformed(node_lines[node_name], format!("node {:?} can't be reached", node_name));
+        }
+        Ok(dialogue)
+    }
+
+    /*
+        Finds the nodes no conversation can reach, which are probably mistakes.
+
+        @return Vec<String> The unreachable nodes' names, sorted.
+    */
+    fn unreachable_nodes(&self) -> Vec<String> {
+        let mut reached: HashSet<&str> = HashSet::new();
+        let mut to_visit = vec![self.start.as_str()];
+        while let Some(node_name) = to_visit.pop() {
+            if !reached.insert(node_name) {
+                continue;
+            }
+            if let Some(node) = self.nodes.get(node_name) {
+                to_visit.extend(node.choices.iter().map(|choice| choice.next.as_str()));
+                to_visit.extend(node.next.as_deref());
+            }
+        }
+        let mut unreachable: Vec<String> = self.nodes
+            .keys()
+            .filter(|node_name| !reached.contains(node_name.as_str()))
+            .cloned()
+            .collect();
+        unreachable.sort();
+        unreachable
+    }
+}
+
+/**
+    Implementation of the Conversation object, a dialogue being had.
+
+    @field dialogue The dialogue's name.
+    @field node The current node.
+    @field line The line of the node being shown.
+    @field selection The choice picked among those offered.
+    @field speaker_name The name of whoever is being talked to.
+    @field speaker_index The index in crew of whoever is being talked to, if they're crew.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conversation {
+    pub dialogue: String,
+    pub node: String,
+    pub line: usize,
+    pub selection: usize,
+    pub speaker_name: String,
+    pub speaker_index: Option<usize>,
+}
+
+impl Conversation {
+    /*
+        Fills in the name of whoever is being talked to.
+
+        @param text A speaker or line from the dialogue.
+        @return String The text to show.
+    */
+    pub fn fill_in(&self, text: &str) -> String {
+        text.replace(NAME_PLACEHOLDER, &self.speaker_name)
+    }
+}
+
+/*
+    Reads a choice, e.g. "[has bisket 1] Have a bisket. -> thanks".
+
+    @param value The choice text.
+    @return Result<Choice, String> The choice, or why it's invalid.
+*/
+fn parse_choice(value: &str) -> Result<Choice, String> {
+    let arrow = match value.rfind("->") {
+        Some(arrow) => arrow,
+        None => return Err("choices need \"-> node\"".to_string()),
+    };
+    let next = value[arrow + 2..].trim().to_string();
+    let mut text = value[..arrow].trim();
+    let mut conditions = vec![];
+    while text.starts_with('[') {
+        let close = match text.find(']') {
+            Some(close) => close,
+            None => return Err("unclosed condition".to_string()),
+        };
+        let condition = &text[1..close];
+        match parse_condition(condition) {
+            Some(condition) => conditions.push(condition),
+            None => return Err(format!("unknown condition {:?}", condition)),
+        }
+        text = text[close + 1..].trim();
+    }
+    Ok(Choice {
+        text: text.to_string(),
+        conditions: conditions,
+        next: next,
+    })
+}
+
+/*
+    Reads a condition, e.g. "has bisket 1", "flag met" or "not flag met".
+
+    @param value The condition text.
+    @return Option<Condition> The condition, if it's valid.
+*/
+fn parse_condition(value: &str) -> Option<Condition> {
+    let words: Vec<&str> = value.split_whitespace().collect();
+    match words.as_slice() {
+        ["has", item, count] => Some(Condition::HasItem(
+            ItemType::from_name(item)?,
+            count.parse().ok()?,
+        )),
+        ["flag", flag] => Some(Condition::Flag(flag.to_string())),
+        ["not", "flag", flag] => Some(Condition::NotFlag(flag.to_string())),
+        _ => None,
+    }
+}
+
+/*
+    Reads an effect, e.g. "give grune 1", "set met" or "recruit".
+
+    @param value The effect text.
+    @return Option<Effect> The effect, if it's valid.
+*/
+fn parse_effect(value: &str) -> Option<Effect> {
+    let words: Vec<&str> = value.split_whitespace().collect();
+    match words.as_slice() {
+        ["give", item, count] => Some(Effect::Give(
+            ItemType::from_name(item)?,
+            count.parse().ok()?,
+        )),
+        ["take", item, count] => Some(Effect::Take(
+            ItemType::from_name(item)?,
+            count.parse().ok()?,
+        )),
+        ["set", flag] => Some(Effect::SetFlag(flag.to_string())),
+        ["clear", flag] => Some(Effect::ClearFlag(flag.to_string())),
+        ["recruit"] => Some(Effect::Recruit),
+        _ => None,
+    }
+}
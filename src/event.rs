@@ -0,0 +1,87 @@
+//! Events are things that happen in the game which other systems react to, like quests
+//! and achievements. This is the Observer pattern: the World publishes events to an
+//! EventBus as they happen, and at the end of each update the bus hands them out to every
+//! observer, in the order they were published.
+
+use item::ItemType;
+use ship::BuildAction;
+use tile::TileType;
+
+/**
+    Things that happen in the game.
+
+    @variant Collected The player picked up or harvested an item they hadn't had before.
+    @variant TileEntered The player stepped onto a tile, on the ship or in the world.
+    @variant DamageTaken The player was hurt, by some amount of health.
+    @variant IslandDiscovered An island was discovered, by its id.
+    @variant IslandReached The player set foot on an island for the first time, by its id.
+    @variant ShipBuilt The ship was changed in build mode.
+    @variant Recruited An islander joined the crew, by their name.
+    @variant EnemyDefeated An enemy was killed, by the player or the crew.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    Collected(ItemType),
+    TileEntered(TileType),
+    DamageTaken(i32),
+    IslandDiscovered(u64),
+    IslandReached(u64),
+    ShipBuilt(BuildAction),
+    Recruited(String),
+    EnemyDefeated,
+}
+
+/*
+    Something that reacts to game events.
+*/
+pub trait Observer {
+    /*
+        Reacts to an event.
+
+        @param event The event.
+    */
+    fn notify(&mut self, event: &GameEvent);
+}
+
+/**
+    Implementation of the EventBus object.
+
+    @field pending Events published since they were last dispatched, oldest first.
+*/
+#[derive(Default)]
+pub struct EventBus {
+    pub pending: Vec<GameEvent>,
+}
+
+impl EventBus {
+    /*
+        EventBus constructor.
+
+        @return EventBus Returns self.
+    */
+    pub fn new() -> Self {
+        EventBus { pending: vec![] }
+    }
+
+    /*
+        Records that something happened, to be dispatched later.
+
+        @param event The event.
+    */
+    pub fn publish(&mut self, event: GameEvent) {
+        self.pending.push(event);
+    }
+
+    /*
+        Hands every pending event to each observer, then forgets them.
+
+        @param observers The observers, notified in order for each event.
+    */
+    pub fn dispatch(&mut self, observers: &mut [&mut dyn Observer]) {
+        for event in self.pending.drain(..) {
+            for observer in observers.iter_mut() {
+                observer.notify(&event);
+            }
+        }
+    }
+}
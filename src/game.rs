@@ -3,61 +3,96 @@
 
 use piston_window::*;
 use find_folder::Search;
-use creature::{Creature, CreatureState};
 use texture::TextureSettings;
 use std::collections::HashMap;
-use ship::Ship;
 use misc::*;
-use item::*;
 use map::Map;
+use map_view::{draw_minimap, focus, MapView, MINIMAP_SIZE};
 use constants::*;
-use tile::*;
-use rand::*;
-use rand::distributions::Sample;
+use save;
+use ship::BuildAction;
+use blueprint::EXPORTED_SHIP;
+use crafting::station_name;
+use input::{gamepad_action, key_name, Action, Bindings, Gamepad, ACTIONS};
+use world::{Command, PlayerLocation, World};
+use timestep::*;
+use std::time::Instant;
+
+const TOAST_TIME: f64 = 4.0;
+const MENU_SCREENS: [&str; 7] = [
+    "Inventory",
+    "Crafting",
+    "Crew",
+    "Journal",
+    "Achievements",
+    "Controls",
+    "Map",
+];
 
 #[derive(Debug, PartialEq)]
 pub enum GameState {
     Title,
     InGame,
     InMenu,
+    Inventory,
+    Crafting,
+    Crew,
+    Journal,
+    Achievements,
+    Controls,
+    WorldMap,
     GameOver,
 }
 
-#[derive(Clone)]
-enum PlayerLocation {
-    OnShip,
-    InWorld,
-}
-
 /**
     Implementation of the Game object.
 
-    @field player The main player.
-    @field ship The player's airship.
+    @field world The simulated game world.
     @field game_state The Game State (see above). 
-    @field player_location Player's worldly position (see above).
-    @field item_prototypes Prototyping pattern for cloning items.
-    @field items_in_game Set of all items in the game.
-    @field map The world map.
     @field glyphs Glyphs library for graphics.
     @field textures HashMap of sprite / tile textures.
-    @field updates_since_last_gen Used for generating new items.
-    @field w_width The window width.
-    @field w_height The window height.
+    @field seed_input Seed typed on the title screen, empty to keep the current map.
+    @field save_status Result of the last save or load, shown in menus.
+    @field latest_slot The most recently written save slot, offered to "Continue" on the title screen.
+    @field recipe_selection The recipe chosen in the crafting menu.
+    @field craft_status Result of the last crafting attempt, shown in the crafting menu.
+    @field crew_selection The recruited crew member chosen on the crew screen.
+    @field toasts Titles of achievements just unlocked, with the seconds left to show each.
+    @field bindings The keys bound to each action.
+    @field controls_selection The action chosen on the controls screen.
+    @field rebinding Whether the controls screen is waiting for a key for the chosen action.
+    @field controls_status Result of the last change on the controls screen.
+    @field gamepad The gamepad's left stick.
+    @field stick_unsent Whether the stick moved while off the game screen, so the world hasn't followed it.
+    @field menu_selection The screen chosen in the menu, for opening without shortcut keys.
+    @field cursor Where the mouse cursor is, in window coordinates.
+    @field map_view The part of the world shown on the world map.
+    @field timestep Fixed timestep for updating the world.
+    @field last_frame When the last frame was drawn, for measuring frame time.
 */
 pub struct Game {
-    player: Creature,
-    ship: Ship,
+    world: World,
     game_state: GameState,
-    player_location: PlayerLocation,
-    item_prototypes: HashMap<String, Item>,
-    items_in_game: Vec<Item>,
-    map: Map,
     glyphs: Glyphs,
     textures: HashMap<String, G2dTexture>,
-    updates_since_last_gen: i32,
-    w_width: f64,
-    w_height: f64,
+    seed_input: String,
+    save_status: String,
+    latest_slot: Option<usize>,
+    recipe_selection: usize,
+    craft_status: String,
+    crew_selection: usize,
+    toasts: Vec<(String, f64)>,
+    bindings: Bindings,
+    controls_selection: usize,
+    rebinding: bool,
+    controls_status: String,
+    gamepad: Gamepad,
+    stick_unsent: bool,
+    menu_selection: usize,
+    cursor: (f64, f64),
+    map_view: MapView,
+    timestep: Timestep,
+    last_frame: Instant,
 }
 
 impl Game {
@@ -65,35 +100,43 @@ impl Game {
         Game constructor.
     */
     pub fn new(window: &mut PistonWindow) -> Self {
-        let ship_tiles: Vec<Vec<i32>> = vec![
-            // Default ship.
-            vec![0, 0, 1, 3, 1, 0, 0],
-            vec![0, 1, 1, 1, 1, 1, 0],
-            vec![0, 1, 1, 2, 1, 1, 0],
-            vec![1, 1, 1, 1, 1, 1, 1],
-            vec![1, 1, 1, 1, 1, 1, 1],
-            vec![1, 1, 1, 1, 1, 1, 1],
-            vec![1, 1, 1, 1, 1, 1, 1],
-            vec![1, 1, 1, 1, 1, 1, 1],
-        ];
-
-        let item_prototypes = generate_item_prototypes();
         let glyphs = generate_glyphs(window);
         let textures = generate_textures(window);
+        let mut world = World::new(Map::new());
+        let mut problems = world.load_errors.clone();
+        match save::load_achievements() {
+            Ok(counts) => world.achievements.counts = counts,
+            Err(e) => problems.push(format!("Could not load achievements: {}", e)),
+        }
+        let save_status = problems.join(". ");
+        let (bindings, controls_status) = match Bindings::load() {
+            Ok(bindings) => (bindings, String::new()),
+            Err(e) => (Bindings::new(), format!("Could not load controls: {}", e)),
+        };
 
         Game {
-            player: Creature::new(),
-            ship: Ship::new(ship_tiles),
+            world: world,
             game_state: GameState::Title,
-            player_location: PlayerLocation::OnShip,
-            item_prototypes: item_prototypes,
-            items_in_game: vec![],
-            map: Map::new(MAP_WIDTH, MAP_HEIGHT),
             glyphs: glyphs,
             textures: textures,
-            updates_since_last_gen: 0,
-            w_width: 800.0,
-            w_height: 640.0,
+            seed_input: String::new(),
+            save_status: save_status,
+            latest_slot: save::latest_slot(),
+            recipe_selection: 0,
+            craft_status: String::new(),
+            crew_selection: 0,
+            toasts: vec![],
+            bindings: bindings,
+            controls_selection: 0,
+            rebinding: false,
+            controls_status: controls_status,
+            gamepad: Gamepad::new(),
+            stick_unsent: false,
+            menu_selection: 0,
+            cursor: (0.0, 0.0),
+            map_view: MapView::new(),
+            timestep: Timestep::new(FIXED_DT, MAX_STEPS_PER_FRAME),
+            last_frame: Instant::now(),
         }
     }
 
@@ -109,14 +152,18 @@ impl Game {
         window.draw_2d(e, |context, mut graphics| {
             let w_width = window_size.width as f64;
             let w_height = window_size.height as f64;
-            self.w_width = w_width;
-            self.w_height = w_height;
+            self.world.view_width = w_width;
+            self.world.view_height = w_height;
             clear([0.0, 0.0, 0.0, 1.0], graphics); // Clears screen for new draw.
             match self.game_state {
                 GameState::InGame => {
+                    // Interpolate between updates for smooth drawing.
+                    let alpha = self.timestep.alpha();
+                    let (player_x, player_y) = self.world.player.render_position(alpha);
+                    let (ship_x, ship_y) = self.world.ship.render_position(alpha);
+
                     // Translations for objects around the player.
-                    let trans_x = w_width / 2.0 - self.player.x;
-                    let trans_y = w_height / 2.0 - self.player.y;
+                    let (trans_x, trans_y) = self.view_translation();
 
                     let sky_img_string = "sky";
                     // Sky background.
@@ -128,27 +175,26 @@ impl Game {
                         graphics,
                     );
 
-                    self.map.draw(
+                    self.world.map.draw(
                         &self.textures,
                         &context,
                         &mut graphics,
                         w_width,
                         w_height,
-                        self.player.x,
-                        self.player.y,
+                        player_x,
+                        player_y,
                         trans_x,
                         trans_y,
                     );
 
                     // Draw items.
-                    for i in 0..self.items_in_game.len() {
-                        if self.items_in_game[i].x - self.player.x
-                            > -w_width / 2.0 - IMAGE_SIZE_SCALED
-                            && self.items_in_game[i].x - self.player.x < w_width / 2.0
-                            && self.items_in_game[i].y - self.player.y > -w_width / 2.0
-                            && self.items_in_game[i].y - self.player.y < w_width / 2.0
+                    for i in 0..self.world.items_in_game.len() {
+                        if self.world.items_in_game[i].x - player_x > -w_width / 2.0 - IMAGE_SIZE_SCALED
+                            && self.world.items_in_game[i].x - player_x < w_width / 2.0
+                            && self.world.items_in_game[i].y - player_y > -w_width / 2.0
+                            && self.world.items_in_game[i].y - player_y < w_width / 2.0
                         {
-                            self.items_in_game[i].draw(
+                            self.world.items_in_game[i].draw(
                                 &self.textures,
                                 &context,
                                 &mut graphics,
@@ -158,23 +204,118 @@ impl Game {
                         }
                     }
 
-                    match self.player_location {
-                        PlayerLocation::OnShip => self.ship.draw(
+                    match self.world.player_location {
+                        PlayerLocation::OnShip => self.world.ship.draw(
                             // Don't draw ship if player isn't on board.
                             &self.textures,
                             &context,
                             &mut graphics,
-                            trans_x,
-                            trans_y,
+                            trans_x + ship_x - self.world.ship.x,
+                            trans_y + ship_y - self.world.ship.y,
                         ),
                         PlayerLocation::InWorld => {}
                     }
 
-                    self.player
-                        .draw(&self.textures, &context, &mut graphics, w_width, w_height);
+                    // Draw other creatures, when off the ship with them.
+                    if self.world.player_location == PlayerLocation::InWorld {
+                        for npc in self.world.npcs.iter_mut() {
+                            let (npc_x, npc_y) = npc.render_position(alpha);
+                            npc.draw(
+                                &self.textures,
+                                &context,
+                                &mut graphics,
+                                npc_x + trans_x,
+                                npc_y + trans_y,
+                            );
+                        }
+                    }
+
+                    // Draw the crew on the same side of the portal as the player.
+                    let on_ship = self.world.player_location == PlayerLocation::OnShip;
+                    for member in self.world.crew.iter_mut() {
+                        if member.on_ship != on_ship {
+                            continue;
+                        }
+                        let (member_x, member_y) = member.creature.render_position(alpha);
+                        member.creature.draw(
+                            &self.textures,
+                            &context,
+                            &mut graphics,
+                            member_x + trans_x,
+                            member_y + trans_y,
+                        );
+                    }
+
+                    // Draw Player at center of screen.
+                    self.world.player.draw(
+                        &self.textures,
+                        &context,
+                        &mut graphics,
+                        w_width / 2.0,
+                        w_height / 2.0,
+                    );
+
+                    // Outline the tile under the mouse cursor, brighter when it's in reach.
+                    let (cursor_x, cursor_y) = self.cursor_in_world();
+                    if let Some((tile_x, tile_y)) = self.world.tile_at(cursor_x, cursor_y) {
+                        let (origin_x, origin_y) = match self.world.player_location {
+                            PlayerLocation::OnShip => (ship_x, ship_y),
+                            PlayerLocation::InWorld => (0.0, 0.0),
+                        };
+                        let color = if self.world.in_reach(cursor_x, cursor_y) {
+                            [1.0, 1.0, 1.0, 0.8]
+                        } else {
+                            [1.0, 1.0, 1.0, 0.25]
+                        };
+                        Rectangle::new_border(color, 1.0).draw(
+                            [
+                                origin_x + tile_x as f64 * IMAGE_SIZE_SCALED,
+                                origin_y + tile_y as f64 * IMAGE_SIZE_SCALED,
+                                IMAGE_SIZE_SCALED,
+                                IMAGE_SIZE_SCALED,
+                            ],
+                            &context.draw_state,
+                            context.transform.trans(trans_x, trans_y),
+                            graphics,
+                        );
+                    }
+
+                    // Outline the ship tile being edited in build mode.
+                    if let Some((cursor_x, cursor_y)) = self.world.build_cursor {
+                        let color = match self.world.build_error {
+                            Some(_) => [1.0, 0.3, 0.3, 1.0],
+                            None => [1.0, 1.0, 1.0, 1.0],
+                        };
+                        Rectangle::new_border(color, 1.0).draw(
+                            [
+                                ship_x + cursor_x as f64 * IMAGE_SIZE_SCALED,
+                                ship_y + cursor_y as f64 * IMAGE_SIZE_SCALED,
+                                IMAGE_SIZE_SCALED,
+                                IMAGE_SIZE_SCALED,
+                            ],
+                            &context.draw_state,
+                            context.transform.trans(trans_x, trans_y),
+                            graphics,
+                        );
+
+                        let draw_text = match self.world.build_error {
+                            Some(ref error) => format!("Build mode: {}", error),
+                            None => "Build mode: 1 Floor, 2 Remove, 3 Wheel, 4 Portal, \
+                                     5 Workbench, 6 Lookout, 7 Cannon"
+                                .to_string(),
+                        };
+                        text(
+                            [1.0; 4],
+                            24,
+                            &draw_text,
+                            &mut self.glyphs,
+                            context.transform.trans(25.0, w_height - 25.0),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", draw_text));
+                    }
 
                     // Draw health at top of screen.
-                    for i in 0..self.player.health {
+                    for i in 0..self.world.player.health {
                         image(
                             self.textures
                                 .get(IMG_HEART)
@@ -187,6 +328,146 @@ impl Game {
                         );
                     }
 
+                    // Name the island below.
+                    if let Some(island) = self.world.island_under_player() {
+                        let draw_text = format!("Island {}", island.id);
+                        text(
+                            [1.0; 4],
+                            24,
+                            &draw_text,
+                            &mut self.glyphs,
+                            context.transform.trans(25.0, 25.0 + IMAGE_SIZE_SCALED + 24.0),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", draw_text));
+                    }
+
+                    // Track the first active quest under the island's name.
+                    if let Some(quest) = self.world.quests.active().first() {
+                        let mut draw_text =
+                            format!("{}: {}", quest.title, self.world.quests.describe(quest));
+                        if let Some(bearing) = self.quest_target_bearing(&quest.name) {
+                            draw_text.push_str(&format!(" - {}", bearing));
+                        }
+                        text(
+                            [1.0, 0.9, 0.5, 1.0],
+                            24,
+                            &draw_text,
+                            &mut self.glyphs,
+                            context.transform.trans(25.0, 25.0 + IMAGE_SIZE_SCALED + 48.0),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", draw_text));
+                    }
+
+                    // Announce achievements just unlocked in the top right.
+                    for (i, (title, _)) in self.toasts.iter().enumerate() {
+                        let draw_text = format!("Achievement unlocked: {}", title);
+                        let bounds = [w_width - 420.0, 20.0 + i as f64 * 40.0, 400.0, 34.0];
+                        Rectangle::new([0.0, 0.0, 0.0, 0.8]).draw(
+                            bounds,
+                            &context.draw_state,
+                            context.transform,
+                            graphics,
+                        );
+                        Rectangle::new_border([1.0, 0.9, 0.5, 1.0], 1.0).draw(
+                            bounds,
+                            &context.draw_state,
+                            context.transform,
+                            graphics,
+                        );
+                        text(
+                            [1.0, 0.9, 0.5, 1.0],
+                            24,
+                            &draw_text,
+                            &mut self.glyphs,
+                            context.transform.trans(bounds[0] + 10.0, bounds[1] + 24.0),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", draw_text));
+                    }
+
+                    // Offer to talk to an islander in reach.
+                    if let (Some(i), None) =
+                        (self.world.islander_in_reach(), self.world.conversation.as_ref())
+                    {
+                        let draw_text = format!(
+                            "{}: Talk to {}",
+                            self.bindings.describe(Action::Interact),
+                            self.world.crew[i].describe()
+                        );
+                        text(
+                            [1.0; 4],
+                            24,
+                            &draw_text,
+                            &mut self.glyphs,
+                            context.transform.trans(25.0, w_height - 60.0),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", draw_text));
+                    }
+
+                    // Summarize the terrain nearby in the bottom right.
+                    draw_minimap(
+                        &self.world,
+                        &context,
+                        &mut graphics,
+                        w_width - MINIMAP_SIZE - 20.0,
+                        w_height - MINIMAP_SIZE - 20.0,
+                    );
+
+                    // Draw the conversation in a text box along the bottom.
+                    if let (Some(conversation), Some(node)) =
+                        (self.world.conversation.as_ref(), self.world.conversation_node())
+                    {
+                        let font = 24;
+                        let mut draw_text = vec![
+                            format!("{}:", conversation.fill_in(&node.speaker)),
+                            conversation.fill_in(&node.lines[conversation.line]),
+                        ];
+                        let choices = self.world.conversation_choices();
+                        if conversation.line + 1 == node.lines.len() && !choices.is_empty() {
+                            for (i, choice) in choices.iter().enumerate() {
+                                let selected = if i == conversation.selection { ">" } else { " " };
+                                draw_text.push(format!("{} {}", selected, choice.text));
+                            }
+                        } else {
+                            draw_text.push(format!(
+                                "{}: Continue",
+                                self.bindings.describe(Action::Interact)
+                            ));
+                        }
+
+                        let box_height = (draw_text.len() + 1) as f64 * font as f64;
+                        let bounds = [
+                            20.0,
+                            w_height - box_height - 20.0,
+                            w_width - 40.0,
+                            box_height,
+                        ];
+                        Rectangle::new([0.0, 0.0, 0.0, 0.8]).draw(
+                            bounds,
+                            &context.draw_state,
+                            context.transform,
+                            graphics,
+                        );
+                        Rectangle::new_border([1.0; 4], 1.0).draw(
+                            bounds,
+                            &context.draw_state,
+                            context.transform,
+                            graphics,
+                        );
+                        for (i, line) in draw_text.iter().enumerate() {
+                            text(
+                                [1.0; 4],
+                                font,
+                                line,
+                                &mut self.glyphs,
+                                context.transform.trans(
+                                    bounds[0] + 15.0,
+                                    bounds[1] + (i + 1) as f64 * font as f64,
+                                ),
+                                graphics,
+                            ).expect(&format!("Error drawing {}", line));
+                        }
+                    }
+
                     // End in-game graphics.
                 }
 
@@ -230,25 +511,117 @@ impl Game {
                             .scale(scale, scale),
                         graphics,
                     );
+
+                    // Show the world seed, or the one being typed.
+                    let draw_text = if self.seed_input.is_empty() {
+                        format!("Seed: {}", self.world.map.seed)
+                    } else {
+                        format!("Seed: {}_", self.seed_input)
+                    };
+                    let font = 24;
+                    text(
+                        [1.0; 4],
+                        font,
+                        &draw_text,
+                        &mut self.glyphs,
+                        context.transform.trans(25.0, w_height - 50.0),
+                        graphics,
+                    ).expect(&format!("Error drawing {}", draw_text));
+                    let draw_text = "Type digits to enter a seed, Enter to start";
+                    text(
+                        [1.0; 4],
+                        font,
+                        draw_text,
+                        &mut self.glyphs,
+                        context.transform.trans(25.0, w_height - 25.0),
+                        graphics,
+                    ).expect(&format!("Error drawing {}", draw_text));
+
+                    // Offer to continue from the latest save.
+                    if let Some(slot) = self.latest_slot {
+                        let draw_text = format!("C: Continue (slot {}), F1-F3: Load slot", slot);
+                        text(
+                            [1.0; 4],
+                            font,
+                            &draw_text,
+                            &mut self.glyphs,
+                            context.transform.trans(25.0, 50.0),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", draw_text));
+                    }
+                    let draw_text = &self.save_status;
+                    text(
+                        [1.0; 4],
+                        font,
+                        draw_text,
+                        &mut self.glyphs,
+                        context.transform.trans(25.0, 75.0),
+                        graphics,
+                    ).expect(&format!("Error drawing {}", draw_text));
                 }
 
                 GameState::InMenu => {
                     // Display control options.
                     let transform_y = 100.0;
+                    let bindings = &self.bindings;
+                    let keys = |action| bindings.describe(action);
                     let draw_text = [
-                        "Controls:",
-                        "W/A/S/D: Movement",
-                        "Tab: Enter/Exit this menu",
-                        "E: Use item/Interact",
-                        "Space: Pickup/Drop item",
-                        "L: Owie :("
+                        "Controls:".to_string(),
+                        format!(
+                            "{}/{}/{}/{}: Movement",
+                            keys(Action::MoveNorth),
+                            keys(Action::MoveWest),
+                            keys(Action::MoveSouth),
+                            keys(Action::MoveEast)
+                        ),
+                        format!(
+                            "{}: Enter/Exit this menu, {}: World map",
+                            keys(Action::OpenMenu),
+                            keys(Action::OpenMap)
+                        ),
+                        format!(
+                            "{}: Use item/Interact, talk, swing a sword, chop trees, pick grune",
+                            keys(Action::Interact)
+                        ),
+                        format!(
+                            "{}/{} then {}: Answer while talking",
+                            keys(Action::MoveNorth),
+                            keys(Action::MoveSouth),
+                            keys(Action::Interact)
+                        ),
+                        format!(
+                            "{}: Pickup/Drop item, {}: Drop held item, {}: Throw held item",
+                            keys(Action::ToggleHands),
+                            keys(Action::DropItem),
+                            keys(Action::Throw)
+                        ),
+                        format!(
+                            "{}/{}: Change held slot",
+                            keys(Action::PreviousSlot),
+                            keys(Action::NextSlot)
+                        ),
+                        "I: Inventory (from this menu)".to_string(),
+                        "K: Crafting (from this menu)".to_string(),
+                        "R: Crew (from this menu)".to_string(),
+                        "J: Journal (from this menu)".to_string(),
+                        "H: Achievements (from this menu)".to_string(),
+                        "O: Change controls (from this menu)".to_string(),
+                        format!("{}: Owie :(", keys(Action::TakeDamage)),
+                        format!(
+                            "{}: Build mode, at the wheel or a workbench",
+                            keys(Action::ToggleBuildMode)
+                        ),
+                        "1-7: Add floor/Remove floor/Move wheel/Move portal/Workbench/Lookout/Cannon"
+                            .to_string(),
+                        "F1/F2/F3: Save to slot 1/2/3".to_string(),
+                        "F5: Export ship blueprint".to_string(),
                     ];
                     let font = 24;
                     for i in 0..draw_text.len() {
                         text(
                             [1.0; 4],
                             font,
-                            draw_text[i],
+                            &draw_text[i],
                             &mut self.glyphs,
                             context
                                 .transform
@@ -256,6 +629,356 @@ impl Game {
                             graphics,
                         ).expect(&format!("Error drawing {}", draw_text[i]));
                     }
+                    let status_y = transform_y + (draw_text.len() + 1) as f64 * font as f64;
+                    let draw_text = &self.save_status;
+                    text(
+                        [1.0; 4],
+                        font,
+                        draw_text,
+                        &mut self.glyphs,
+                        context.transform.trans(100.0, status_y),
+                        graphics,
+                    ).expect(&format!("Error drawing {}", draw_text));
+
+                    // The screen to open without a shortcut, e.g. from a gamepad.
+                    let draw_text = format!(
+                        "< {} >  {}/{} or d-pad: Choose, {} or A: Open",
+                        MENU_SCREENS[self.menu_selection],
+                        self.bindings.describe(Action::MoveNorth),
+                        self.bindings.describe(Action::MoveSouth),
+                        self.bindings.describe(Action::Interact)
+                    );
+                    text(
+                        [1.0, 0.9, 0.5, 1.0],
+                        font,
+                        &draw_text,
+                        &mut self.glyphs,
+                        context.transform.trans(100.0, transform_y - 40.0),
+                        graphics,
+                    ).expect(&format!("Error drawing {}", draw_text));
+                }
+
+                GameState::Inventory => {
+                    // List the slots, marking the one in hand.
+                    let inventory = &self.world.player.inventory;
+                    let transform_y = 100.0;
+                    let font = 24;
+                    let mut draw_text = vec![format!(
+                        "Inventory ({:.1} / {:.1} weight)",
+                        inventory.weight(),
+                        inventory.max_weight
+                    )];
+                    for (i, slot) in inventory.slots.iter().enumerate() {
+                        let hand = if i == inventory.hand { ">" } else { " " };
+                        draw_text.push(match *slot {
+                            Some(ref stack) => format!(
+                                "{} {}: {} x{}",
+                                hand,
+                                i + 1,
+                                stack.item.item_type.name(),
+                                stack.count
+                            ),
+                            None => format!("{} {}: -", hand, i + 1),
+                        });
+                    }
+                    let keys = |action| self.bindings.describe(action);
+                    draw_text.push(format!(
+                        "{}/{}: Choose held slot, {}: Drop one, {}: Back",
+                        keys(Action::MoveNorth),
+                        keys(Action::MoveSouth),
+                        keys(Action::DropItem),
+                        keys(Action::OpenMenu)
+                    ));
+                    for (i, line) in draw_text.iter().enumerate() {
+                        text(
+                            [1.0; 4],
+                            font,
+                            line,
+                            &mut self.glyphs,
+                            context
+                                .transform
+                                .trans(100.0, transform_y + i as f64 * font as f64),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", line));
+                    }
+                }
+
+                GameState::Crafting => {
+                    // List the recipes, marking the chosen one and those with enough materials.
+                    let transform_y = 100.0;
+                    let font = 24;
+                    let mut draw_text = vec!["Crafting".to_string()];
+                    for (i, recipe) in self.world.recipes.iter().enumerate() {
+                        let selected = if i == self.recipe_selection { ">" } else { " " };
+                        let ready = if recipe.has_materials(&self.world.player.inventory) {
+                            ""
+                        } else {
+                            " - missing materials"
+                        };
+                        draw_text.push(format!("{} {}{}", selected, recipe.describe(), ready));
+                    }
+                    let keys = |action| self.bindings.describe(action);
+                    draw_text.push(format!(
+                        "{}/{}: Choose recipe, {}: Craft, {}: Queue for the crew, {}: Back",
+                        keys(Action::MoveNorth),
+                        keys(Action::MoveSouth),
+                        keys(Action::Interact),
+                        keys(Action::DropItem),
+                        keys(Action::OpenMenu)
+                    ));
+                    draw_text.push(self.craft_status.clone());
+                    for (i, line) in draw_text.iter().enumerate() {
+                        text(
+                            [1.0; 4],
+                            font,
+                            line,
+                            &mut self.glyphs,
+                            context
+                                .transform
+                                .trans(100.0, transform_y + i as f64 * font as f64),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", line));
+                    }
+                }
+
+                GameState::Crew => {
+                    // List the crew and where they work, then the ship's orders.
+                    let transform_y = 100.0;
+                    let font = 24;
+                    let world = &self.world;
+                    let mut draw_text = vec!["Crew".to_string()];
+                    let recruited = world.recruited_crew();
+                    if recruited.is_empty() {
+                        draw_text.push("  Nobody yet, recruit islanders with E".to_string());
+                    }
+                    for (i, &member) in recruited.iter().enumerate() {
+                        let member = &world.crew[member];
+                        let selected = if i == self.crew_selection { ">" } else { " " };
+                        let place = match member.station {
+                            _ if !member.on_ship => "ashore".to_string(),
+                            Some((x, y)) => format!(
+                                "at the {} ({}, {})",
+                                station_name(&world.ship.tile_type_at(x as i32, y as i32)),
+                                x,
+                                y
+                            ),
+                            None => "on deck".to_string(),
+                        };
+                        draw_text.push(format!("{} {}: {}", selected, member.describe(), place));
+                    }
+                    draw_text.push(match world.waypoint {
+                        Some((x, y)) => format!(
+                            "Waypoint: {:.0}, {:.0}",
+                            x / IMAGE_SIZE_SCALED,
+                            y / IMAGE_SIZE_SCALED
+                        ),
+                        None => "Waypoint: none".to_string(),
+                    });
+                    let queue: Vec<&str> = world
+                        .craft_queue
+                        .iter()
+                        .map(|&recipe| world.recipes[recipe].name.as_str())
+                        .collect();
+                    draw_text.push(format!("Craft queue: {}", queue.join(", ")));
+                    let keys = |action| self.bindings.describe(action);
+                    draw_text.push(format!(
+                        "{}/{}: Choose, {}: Change station, {}: Plot course to nearest island, \
+                         {}: Back",
+                        keys(Action::MoveNorth),
+                        keys(Action::MoveSouth),
+                        keys(Action::Interact),
+                        keys(Action::PlotCourse),
+                        keys(Action::OpenMenu)
+                    ));
+                    for report in &world.crew_reports {
+                        draw_text.push(report.clone());
+                    }
+                    for (i, line) in draw_text.iter().enumerate() {
+                        text(
+                            [1.0; 4],
+                            font,
+                            line,
+                            &mut self.glyphs,
+                            context
+                                .transform
+                                .trans(100.0, transform_y + i as f64 * font as f64),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", line));
+                    }
+                }
+
+                GameState::Journal => {
+                    // List the active quests' stages so far, then the finished quests.
+                    let transform_y = 100.0;
+                    let font = 24;
+                    let quests = &self.world.quests;
+                    let mut draw_text = vec!["Journal".to_string()];
+                    for quest in quests.active() {
+                        draw_text.push(quest.title.clone());
+                        let stage = quests.progress[&quest.name].stage;
+                        for done in &quest.stages[..stage] {
+                            draw_text.push(format!("  [x] {}", done.description));
+                        }
+                        draw_text.push(format!("  [ ] {}", quests.describe(quest)));
+                        let reward: Vec<String> = quest
+                            .reward
+                            .iter()
+                            .map(|&(ref item_type, count)| format!("{} {}", count, item_type.name()))
+                            .collect();
+                        draw_text.push(format!("  Reward: {}", reward.join(", ")));
+                    }
+                    let finished: Vec<&str> = quests
+                        .finished()
+                        .iter()
+                        .map(|quest| quest.title.as_str())
+                        .collect();
+                    if !finished.is_empty() {
+                        draw_text.push(format!("Finished: {}", finished.join(", ")));
+                    }
+                    draw_text.push(format!("{}: Back", self.bindings.describe(Action::OpenMenu)));
+                    for (i, line) in draw_text.iter().enumerate() {
+                        text(
+                            [1.0; 4],
+                            font,
+                            line,
+                            &mut self.glyphs,
+                            context
+                                .transform
+                                .trans(100.0, transform_y + i as f64 * font as f64),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", line));
+                    }
+                }
+
+                GameState::Achievements => {
+                    // List every achievement, unlocked or with the progress towards it.
+                    let transform_y = 100.0;
+                    let font = 24;
+                    let achievements = &self.world.achievements;
+                    let mut draw_text = vec!["Achievements".to_string()];
+                    for achievement in &achievements.registry {
+                        draw_text.push(if achievements.is_unlocked(achievement) {
+                            format!("[x] {} - {}", achievement.title, achievement.description)
+                        } else {
+                            format!(
+                                "[ ] {} - {} ({}/{})",
+                                achievement.title,
+                                achievement.description,
+                                achievements.count(achievement),
+                                achievement.goal
+                            )
+                        });
+                    }
+                    draw_text.push(format!("{}: Back", self.bindings.describe(Action::OpenMenu)));
+                    for (i, line) in draw_text.iter().enumerate() {
+                        text(
+                            [1.0; 4],
+                            font,
+                            line,
+                            &mut self.glyphs,
+                            context
+                                .transform
+                                .trans(100.0, transform_y + i as f64 * font as f64),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", line));
+                    }
+                }
+
+                GameState::Controls => {
+                    // List every action with its keys, marking the chosen one.
+                    let transform_y = 100.0;
+                    let font = 24;
+                    let mut draw_text = vec!["Controls".to_string()];
+                    for (i, action) in ACTIONS.iter().enumerate() {
+                        let selected = if i == self.controls_selection { ">" } else { " " };
+                        let keys = if i == self.controls_selection && self.rebinding {
+                            "Press a key...".to_string()
+                        } else {
+                            self.bindings.describe(*action)
+                        };
+                        draw_text.push(format!("{} {}: {}", selected, action, keys));
+                    }
+                    draw_text.push(
+                        "Up/Down: Choose, Return: Rebind, Backspace: Restore defaults, Tab: Back"
+                            .to_string(),
+                    );
+                    draw_text.push(self.controls_status.clone());
+                    for (i, line) in draw_text.iter().enumerate() {
+                        text(
+                            [1.0; 4],
+                            font,
+                            line,
+                            &mut self.glyphs,
+                            context
+                                .transform
+                                .trans(100.0, transform_y + i as f64 * font as f64),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", line));
+                    }
+                }
+
+                GameState::WorldMap => {
+                    let font = 24;
+                    self.map_view.draw(
+                        &self.world,
+                        &mut self.glyphs,
+                        &context,
+                        &mut graphics,
+                        w_width,
+                        w_height,
+                    );
+                    let (x, y) = (self.map_view.center_x, self.map_view.center_y);
+                    let bindings = &self.bindings;
+                    let keys = |action| bindings.describe(action);
+                    let draw_text = [
+                        format!(
+                            "World map - {:.0}, {:.0} at {}x zoom",
+                            x.floor(),
+                            y.floor(),
+                            self.map_view.scale()
+                        ),
+                        format!(
+                            "{}/{}/{}/{}: Pan, {}/{} or +/- or scroll: Zoom",
+                            keys(Action::MoveNorth),
+                            keys(Action::MoveWest),
+                            keys(Action::MoveSouth),
+                            keys(Action::MoveEast),
+                            keys(Action::PreviousSlot),
+                            keys(Action::NextSlot)
+                        ),
+                        format!(
+                            "{} or left click: Place marker",
+                            keys(Action::Interact)
+                        ),
+                        format!(
+                            "{} or right click: Remove marker",
+                            keys(Action::ToggleHands)
+                        ),
+                        "Backspace: Back to the ship".to_string(),
+                        format!(
+                            "{}: Close the map, {}: Menu",
+                            keys(Action::OpenMap),
+                            keys(Action::OpenMenu)
+                        ),
+                    ];
+                    Rectangle::new([0.0, 0.0, 0.0, 0.6]).draw(
+                        [15.0, 5.0, 560.0, draw_text.len() as f64 * font as f64 + 10.0],
+                        &context.draw_state,
+                        context.transform,
+                        graphics,
+                    );
+                    for (i, line) in draw_text.iter().enumerate() {
+                        text(
+                            [1.0; 4],
+                            font,
+                            line,
+                            &mut self.glyphs,
+                            context
+                                .transform
+                                .trans(25.0, 25.0 + i as f64 * font as f64),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", line));
+                    }
                 }
 
                 GameState::GameOver => {
@@ -283,31 +1006,34 @@ impl Game {
         @param window The PistonWindow that is drawn to.
     */
     pub fn run(&mut self, window: &mut PistonWindow) {
-        self.ship.x = MAP_WIDTH as f64 * IMAGE_SIZE_SCALED / 2.0; // Initial ship position.
-        self.ship.y = MAP_HEIGHT as f64 * IMAGE_SIZE_SCALED / 2.0;
-        self.player.x = self.ship.x + ((self.ship.width / 2.0) * IMAGE_SIZE_SCALED);
-        self.player.y = self.ship.y + ((self.ship.height / 2.0) * IMAGE_SIZE_SCALED);
-
-        // Temporary item generation.
-        self.items_in_game.push(
-            self.item_prototypes
-                .get("bisket")
-                .unwrap()
-                .generate_clone(self.ship.x + 128.0, self.ship.y + 128.0),
-        );
-
         while let Some(e) = window.next() {
             match e {
                 Event::Input(Input::Button(args)) => {
                     self.handle_input(&args.state, &args.button);
                 }
 
-                // TODO Add lag handler here
-                Event::Loop(Loop::Update(_args)) => {
-                    self.update();
+                Event::Input(Input::Move(Motion::ControllerAxis(args))) => {
+                    self.handle_axis(args);
+                }
+
+                Event::Input(Input::Move(Motion::MouseCursor(x, y))) => {
+                    self.cursor = (x, y);
+                }
+
+                Event::Input(Input::Move(Motion::MouseScroll(_, y))) => {
+                    self.handle_scroll(y);
                 }
 
                 Event::Loop(Loop::Render(_args)) => {
+                    // Run as many fixed updates as the time since the last frame allows.
+                    let now = Instant::now();
+                    let elapsed = now.duration_since(self.last_frame);
+                    self.last_frame = now;
+                    let frame_time =
+                        elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9;
+                    for _ in 0..self.timestep.advance(frame_time) {
+                        self.update();
+                    }
                     self.display(&e, window);
                 }
                 _ => {}
@@ -316,377 +1042,871 @@ impl Game {
     }
 
     /*
-        Handles updating of the Game, mosti mportantly player position.
+        Handles updating of the Game, stepping the world while in game.
     */
     fn update(&mut self) {
-        if self.updates_since_last_gen > 1000 {
-            self.updates_since_last_gen = 0;
-
-            let mut rng = thread_rng();
-            let mut range_x = distributions::Range::new(-1.0 * self.w_width/2.0, self.w_width/2.0);
-            let mut range_y = distributions::Range::new(-1.0 * self.w_height/2.0, self.w_height/2.0);
-
-            let item_x = self.player.x + range_x.sample(&mut rng);
-            let item_y = self.player.y + range_y.sample(&mut rng);
-
-            self.items_in_game.push(
-                self.item_prototypes
-                    .get("bisket")
-                    .unwrap()
-                    .generate_clone(item_x, item_y),
-            );
-        }
-        self.updates_since_last_gen+=1;
-
-        if self.player.is_dead() {
+        if self.world.player.is_dead() {
             self.game_state = GameState::GameOver;
         }
         if self.game_state == GameState::InGame {
-            match self.player_location {
-                PlayerLocation::OnShip => {
-                    self.player.other_vel_x = self.ship.self_vel_x;
-                    self.player.other_vel_y = self.ship.self_vel_y;
-                    self.player.update_position_other();
-                    let x = self.player.x_to_be_location();
-                    let y = self.player.y_to_be_location();
-                    if self.is_on_ship(x, y) {
-                        self.player.update_position_self();
-                        self.player.update_direction();
-                    }
-                    self.ship.update_position();
-                }
-                PlayerLocation::InWorld => {
-                    let x = self.player.x_to_be_location();
-                    let y = self.player.y_to_be_location();
-                    if self.can_go_to(x, y) {
-                        self.player.update_position_self();
-                        self.player.update_direction();
-                    }
-                }
+            // Catch up with the stick, e.g. if it was let go of in a menu.
+            if self.stick_unsent {
+                let (x, y) = self.gamepad.stick;
+                self.world.execute(Command::MoveAnalog(x, y));
+                self.stick_unsent = false;
             }
+            self.world.update(self.timestep.dt);
+            self.world.run_crew_tasks(self.timestep.dt);
+        }
+
+        // Show newly unlocked achievements for a while, and keep them unlocked for good.
+        for toast in self.toasts.iter_mut() {
+            toast.1 -= self.timestep.dt;
+        }
+        self.toasts.retain(|toast| toast.1 > 0.0);
+        let unlocked = self.world.achievements.take_unlocked();
+        if !unlocked.is_empty() {
+            for title in unlocked {
+                self.toasts.push((title, TOAST_TIME));
+            }
+            self.save_achievements();
         }
     }
 
     /*
-        Determines the tile the player is standing on.
+        Input Handling from user.
 
-        @return Option<TileType> Some tile.
+        @param state The ButtonState.
+        @param button The input button arguments.
     */
-    fn tile_under_player(&self, location: PlayerLocation) -> Option<Tile> {
-        let x = self.player.x + IMAGE_SIZE_SCALED as f64 / 2.0;
-        let y = self.player.y + IMAGE_SIZE_SCALED as f64 / 2.0;
-        let iss = IMAGE_SIZE_SCALED as f64;
-        match location {
-            PlayerLocation::OnShip => {
-                let is_in_x = x >= self.ship.x && x + iss <= self.ship.x + self.ship.width * iss;
-                let is_in_y = y >= self.ship.y && y + iss <= self.ship.y + self.ship.height * iss;
-                if is_in_x && is_in_y {
-                    return Some(
-                        self.ship.tiles[((x - self.ship.x) / IMAGE_SIZE_SCALED).floor() as usize]
-                            [((y - self.ship.y) / IMAGE_SIZE_SCALED).floor() as usize]
-                            .clone(),
-                    );
+    fn handle_input(&mut self, state: &ButtonState, button: &Button) {
+        use self::Key::*;
+        if let Button::Mouse(mouse_button) = *button {
+            self.execute_click(state, mouse_button);
+            return;
+        }
+        // Keys and gamepad buttons both come through as actions.
+        let key = match *button {
+            Button::Keyboard(key) => Some(key),
+            _ => None,
+        };
+        let action = match *button {
+            Button::Keyboard(key) => self.bindings.action_for(key),
+            Button::Controller(button) => gamepad_action(button.button),
+            Button::Mouse(_) => None,
+        };
+        if self.game_state == GameState::Title {
+            if let Some(key) = key {
+                if self.execute_seed_input(state, key) {
+                    return;
                 }
             }
-            PlayerLocation::InWorld => {
-                let is_in_x = x >= 0.0 && x + iss <= MAP_WIDTH as f64 * iss;
-                let is_in_y = y >= 0.0 && y + iss <= MAP_HEIGHT as f64 * iss;
-                if is_in_x && is_in_y {
-                    return Some(
-                        self.map.tiles[(x / IMAGE_SIZE_SCALED).floor() as usize]
-                            [(y / IMAGE_SIZE_SCALED).floor() as usize]
-                            .clone(),
-                    );
-                }
+        }
+        if self.game_state == GameState::Inventory
+            && self.execute_inventory_input(state, key, action)
+        {
+            return;
+        }
+        if self.game_state == GameState::Crafting && self.execute_crafting_input(state, key, action)
+        {
+            return;
+        }
+        if self.game_state == GameState::Crew && self.execute_crew_input(state, key, action) {
+            return;
+        }
+        if self.game_state == GameState::Controls {
+            // Only the gamepad's actions are used here, the keyboard's keys are fixed.
+            let pad_action = if key.is_none() { action } else { None };
+            self.execute_controls_input(state, key, pad_action);
+            return;
+        }
+        if self.game_state == GameState::WorldMap {
+            self.execute_map_input(state, key, action);
+            return;
+        }
+        if self.game_state == GameState::Journal || self.game_state == GameState::Achievements {
+            let back = key == Some(Return) || action == Some(Action::OpenMenu);
+            if back && *state == ButtonState::Press {
+                self.game_state = GameState::InMenu;
             }
+            return;
+        }
+
+        // Shortcuts come before the bindings, so actions used in game can share their keys.
+        let shortcuts = self.game_state == GameState::Title || self.game_state == GameState::InMenu;
+        if let Some(key) = key {
+            if shortcuts && self.execute_shortcut(state, key) {
+                return;
+            }
+        }
+        if self.game_state == GameState::InMenu && self.execute_menu_input(state, action) {
+            return;
+        }
+        if let Some(action) = action {
+            self.execute_action(state, action);
+            return;
+        }
+        match key {
+            Some(D1) => self.execute_press(state, Command::Build(BuildAction::AddFloor)),
+            Some(D2) => self.execute_press(state, Command::Build(BuildAction::RemoveFloor)),
+            Some(D3) => self.execute_press(state, Command::Build(BuildAction::MoveWheel)),
+            Some(D4) => self.execute_press(state, Command::Build(BuildAction::MovePortal)),
+            Some(D5) => self.execute_press(state, Command::Build(BuildAction::PlaceWorkbench)),
+            Some(D6) => self.execute_press(state, Command::Build(BuildAction::PlaceLookout)),
+            Some(D7) => self.execute_press(state, Command::Build(BuildAction::PlaceCannon)),
+            _ => {}
         }
-        None
     }
 
     /*
-        Determines whether some x and y can be stepped on.
+        Finds how far the world is shifted when drawn, so the player is in the middle of
+        the window. The same shift turns window coordinates back into world coordinates.
 
-        @param x Some x coordinate.
-        @param y Some y coordinate.
-        @return bool Whether the respective tile is passable.
+        @return (f64, f64) The horizontal and vertical translation.
     */
-    fn can_go_to(&self, x: f64, y: f64) -> bool {
-        let iss = IMAGE_SIZE_SCALED as f64;
-        let is_in_x = x >= 0.0 && x + iss <= MAP_WIDTH as f64 * iss;
-        let is_in_y = y >= 0.0 && y + iss <= MAP_HEIGHT as f64 * iss;
-        if is_in_x && is_in_y {
-            let x = x / iss;
-            let y = y / iss;
-            if self.map.tiles[x.floor() as usize][y.floor() as usize].passable // Determines where x,y is on the map.
-                && self.map.tiles[x.floor() as usize][y.ceil() as usize].passable
-                && self.map.tiles[x.ceil() as usize][y.floor() as usize].passable
-                && self.map.tiles[x.ceil() as usize][y.ceil() as usize].passable
-            {
-                return true;
+    fn view_translation(&self) -> (f64, f64) {
+        let (player_x, player_y) = self.world.player.render_position(self.timestep.alpha());
+        (
+            self.world.view_width / 2.0 - player_x,
+            self.world.view_height / 2.0 - player_y,
+        )
+    }
+
+    /*
+        Finds where the mouse cursor points in the world.
+
+        @return (f64, f64) The x and y position, in world coordinates.
+    */
+    fn cursor_in_world(&self) -> (f64, f64) {
+        let (trans_x, trans_y) = self.view_translation();
+        screen_to_world(self.cursor.0, self.cursor.1, trans_x, trans_y)
+    }
+
+    /*
+        Handles mouse clicks in game. Left clicking interacts with what's under the cursor,
+        right clicking throws the held item towards it. On the world map, left clicking
+        places a marker and right clicking removes one.
+
+        @param state The Button State (e.g. pressed).
+        @param mouse_button The mouse button.
+    */
+    fn execute_click(&mut self, state: &ButtonState, mouse_button: MouseButton) {
+        if self.game_state == GameState::WorldMap {
+            let (x, y) = self.map_view.to_tiles(
+                self.cursor.0,
+                self.cursor.1,
+                self.world.view_width,
+                self.world.view_height,
+            );
+            match mouse_button {
+                MouseButton::Left => self.execute_marker(state, x, y, true),
+                MouseButton::Right => self.execute_marker(state, x, y, false),
+                _ => {}
+            }
+            return;
+        }
+        let (x, y) = self.cursor_in_world();
+        match mouse_button {
+            MouseButton::Left => self.execute_press(state, Command::InteractAt(x, y)),
+            MouseButton::Right => self.execute_press(state, Command::ThrowAt(x, y)),
+            _ => {}
+        }
+    }
+
+    /*
+        Zooms the world map with the mouse wheel.
+
+        @param y How far the wheel turned, positive away from the player.
+    */
+    fn handle_scroll(&mut self, y: f64) {
+        if self.game_state == GameState::WorldMap {
+            if y > 0.0 {
+                self.map_view.zoom_in();
+            } else if y < 0.0 {
+                self.map_view.zoom_out();
+            }
+        }
+    }
+
+    /*
+        Follows the gamepad's left stick, moving the player or ship by how far it's pushed.
+
+        @param args The axis event.
+    */
+    fn handle_axis(&mut self, args: ControllerAxisArgs) {
+        if let Some((x, y)) = self.gamepad.move_axis(args) {
+            if self.game_state == GameState::InGame {
+                self.world.execute(Command::MoveAnalog(x, y));
+            } else {
+                self.stick_unsent = true;
+            }
+        }
+    }
+
+    /*
+        Chooses and opens screens from the menu, for gamepads which lack the shortcut keys.
+
+        @param state The Button State (e.g. pressed).
+        @param action The action pressed, if any.
+        @return bool Whether the action was used by the menu.
+    */
+    fn execute_menu_input(&mut self, state: &ButtonState, action: Option<Action>) -> bool {
+        let count = MENU_SCREENS.len();
+        if *state != ButtonState::Press {
+            return false;
+        }
+        match action {
+            Some(Action::MoveNorth) => {
+                self.menu_selection = (self.menu_selection + count - 1) % count
+            }
+            Some(Action::MoveSouth) => self.menu_selection = (self.menu_selection + 1) % count,
+            Some(Action::Interact) => match self.menu_selection {
+                0 => self.execute_open_inventory(state),
+                1 => self.execute_open_crafting(state),
+                2 => self.execute_open_crew(state),
+                3 => self.execute_open_journal(state),
+                4 => self.execute_open_achievements(state),
+                5 => self.execute_open_controls(state),
+                _ => self.execute_open_map(state),
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    /*
+        Handles the shortcuts on the title screen and in the menu.
+
+        @param state The Button State (e.g. pressed).
+        @param key The key pressed.
+        @return bool Whether the key is a shortcut.
+    */
+    fn execute_shortcut(&mut self, state: &ButtonState, key: Key) -> bool {
+        use self::Key::*;
+        match key {
+            Return if self.game_state == GameState::Title => self.execute_open_menu(state),
+            I => self.execute_open_inventory(state),
+            K => self.execute_open_crafting(state),
+            R => self.execute_open_crew(state),
+            J => self.execute_open_journal(state),
+            H => self.execute_open_achievements(state),
+            O => self.execute_open_controls(state),
+            C => self.execute_continue(state),
+            F1 => self.execute_save_slot(state, 1),
+            F2 => self.execute_save_slot(state, 2),
+            F3 => self.execute_save_slot(state, 3),
+            F5 => self.execute_export_ship(state),
+            _ => return false,
+        }
+        true
+    }
+
+    /*
+        Carries out an action bound to a key.
+
+        @param state The Button State (e.g. pressed or released).
+        @param action The action.
+    */
+    fn execute_action(&mut self, state: &ButtonState, action: Action) {
+        if let Some(dir) = action.direction() {
+            self.execute_move(state, dir);
+            return;
+        }
+        match action {
+            Action::Interact => self.execute_press(state, Command::Interact),
+            Action::ToggleHands => self.execute_press(state, Command::ToggleHands),
+            Action::DropItem => self.execute_press(state, Command::DropItem),
+            Action::Throw => self.execute_press(state, Command::Throw),
+            Action::PreviousSlot => self.execute_cycle_hand(state, false),
+            Action::NextSlot => self.execute_cycle_hand(state, true),
+            Action::ToggleBuildMode => self.execute_press(state, Command::ToggleBuildMode),
+            Action::OpenMenu => self.execute_open_menu(state),
+            Action::OpenMap => self.execute_open_map(state),
+            Action::TakeDamage => self.execute_press(state, Command::TakeDamage),
+            _ => {}
+        }
+    }
+
+    /*
+        Sends movement to the world while in game.
+
+        @param state Button State (e.g. pressed or released).
+        @param dir The direction of the movement key.
+    */
+    fn execute_move(&mut self, state: &ButtonState, dir: Direction) {
+        if self.game_state == GameState::InGame {
+            match *state {
+                ButtonState::Press => self.world.execute(Command::StartMoving(dir)),
+                ButtonState::Release => self.world.execute(Command::StopMoving(dir)),
             }
         }
-        false
     }
 
     /*
-        Checks whether a specific x,y position is on the ship.
-        Used for collision detection.
+        Sends a command to the world when its key is pressed in game.
+
+        @param state The Button State (e.g. pressed).
+        @param command The command for the key.
+    */
+    fn execute_press(&mut self, state: &ButtonState, command: Command) {
+        if *state == ButtonState::Press && self.game_state == GameState::InGame {
+            self.world.execute(command);
+        }
+    }
+
+    /*
+        Changes the held slot to the next or previous one while in game.
+
+        @param state The Button State (e.g. pressed).
+        @param forward Whether to go to the next slot rather than the previous.
+    */
+    fn execute_cycle_hand(&mut self, state: &ButtonState, forward: bool) {
+        let inventory = &self.world.player.inventory;
+        let slot = if forward {
+            inventory.hand + 1
+        } else {
+            inventory.hand + inventory.slots.len() - 1
+        };
+        self.execute_press(state, Command::SelectSlot(slot));
+    }
+
+    /*
+        Opens the inventory screen from the menu.
 
-        @param x Some x coordinate.
-        @param y Some y coordinate.
-        @return bool Whether the x,y coordinate is within the ship.
+        @param state The Button State (e.g. pressed).
     */
-    fn is_on_ship(&self, x: f64, y: f64) -> bool {
-        let ship_x = self.ship.x_to_be_location(); // Extraneous for moving ship.
-        let ship_y = self.ship.y_to_be_location();
+    fn execute_open_inventory(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press && self.game_state == GameState::InMenu {
+            self.game_state = GameState::Inventory;
+        }
+    }
 
-        // Check edges.
-        let is_in_x =
-            x >= ship_x && x + IMAGE_SIZE_SCALED <= ship_x + self.ship.width * IMAGE_SIZE_SCALED;
-        let is_in_y =
-            y >= ship_y && y + IMAGE_SIZE_SCALED <= ship_y + self.ship.height * IMAGE_SIZE_SCALED;
-        if is_in_x && is_in_y {
-            // Check surrounding tiles.
-            let ship_tile_x = (x - ship_x) / IMAGE_SIZE_SCALED;
-            let ship_tile_y = (y - ship_y) / IMAGE_SIZE_SCALED;
-            if self.ship.tiles[ship_tile_x.floor() as usize][ship_tile_y.floor() as usize].passable
-                && self.ship.tiles[ship_tile_x.floor() as usize][ship_tile_y.ceil() as usize]
-                    .passable
-                && self.ship.tiles[ship_tile_x.ceil() as usize][ship_tile_y.floor() as usize]
-                    .passable
-                && self.ship.tiles[ship_tile_x.ceil() as usize][ship_tile_y.ceil() as usize]
-                    .passable
-            {
+    /*
+        Handles the inventory screen, choosing the held slot and dropping from it.
+
+        @param state The Button State (e.g. pressed).
+        @param key The key pressed, if it was a key.
+        @param action The action pressed, if any.
+        @return bool Whether the press was used by the inventory screen.
+    */
+    fn execute_inventory_input(
+        &mut self,
+        state: &ButtonState,
+        key: Option<Key>,
+        action: Option<Action>,
+    ) -> bool {
+        use self::Key::*;
+        if *state != ButtonState::Press {
+            return true;
+        }
+        let inventory = &self.world.player.inventory;
+        let command = match (key, action) {
+            (_, Some(Action::MoveNorth)) | (Some(Up), _) => {
+                Command::SelectSlot(inventory.hand + inventory.slots.len() - 1)
+            }
+            (_, Some(Action::MoveSouth)) | (Some(Down), _) => Command::SelectSlot(inventory.hand + 1),
+            (_, Some(Action::DropItem)) => Command::DropItem,
+            (_, Some(Action::OpenMenu)) | (Some(Return), _) => {
+                self.game_state = GameState::InMenu;
                 return true;
             }
+            _ => return true,
+        };
+        self.world.execute(command);
+        true
+    }
+
+    /*
+        Opens the crafting menu from the menu.
+
+        @param state The Button State (e.g. pressed).
+    */
+    fn execute_open_crafting(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press && self.game_state == GameState::InMenu {
+            self.craft_status.clear();
+            self.game_state = GameState::Crafting;
         }
-        false
     }
 
     /*
-        Input Handling from user.
+        Handles the crafting menu, choosing a recipe and crafting it.
 
-        @param state The ButtonState.
-        @param button The input button arguments.
+        @param state The Button State (e.g. pressed).
+        @param key The key pressed, if it was a key.
+        @param action The action pressed, if any.
+        @return bool Whether the press was used by the crafting menu.
     */
-    fn handle_input(&mut self, state: &ButtonState, button: &Button) {
+    fn execute_crafting_input(
+        &mut self,
+        state: &ButtonState,
+        key: Option<Key>,
+        action: Option<Action>,
+    ) -> bool {
         use self::Key::*;
-        match *button {
-            Button::Keyboard(key) => match key {
-                // Menu toggle.
-                Return | Tab => self.execute_open_menu(state),
-                // Moving.
-                W | A | S | D => self.execute_move(state, &Some(key)),
-                E => self.execute_action(state),
-                L => {
-                    if *state == ButtonState::Press {
-                        self.player.take_damage(1)
-                    }
-                }
-                Space => {
-                    self.execute_player_hands(state);
+        if *state != ButtonState::Press {
+            return true;
+        }
+        let recipe_count = self.world.recipes.len();
+        match (key, action) {
+            (_, Some(Action::MoveNorth)) | (Some(Up), _) => {
+                self.recipe_selection = (self.recipe_selection + recipe_count - 1) % recipe_count
+            }
+            (_, Some(Action::MoveSouth)) | (Some(Down), _) => {
+                self.recipe_selection = (self.recipe_selection + 1) % recipe_count
+            }
+            (_, Some(Action::Interact)) => {
+                self.world.execute(Command::Craft(self.recipe_selection));
+                self.craft_status = match self.world.craft_error {
+                    Some(ref error) => format!("{}", error),
+                    None => format!("Crafted {}", self.world.recipes[self.recipe_selection].name),
+                };
+            }
+            (_, Some(Action::DropItem)) => {
+                self.world.execute(Command::QueueCraft(self.recipe_selection));
+                self.craft_status = match self.world.craft_error {
+                    Some(ref error) => format!("{}", error),
+                    None => format!(
+                        "Queued {} for the crew",
+                        self.world.recipes[self.recipe_selection].name
+                    ),
+                };
+            }
+            (_, Some(Action::OpenMenu)) | (Some(Return), _) => self.game_state = GameState::InMenu,
+            _ => {}
+        }
+        true
+    }
+
+    /*
+        Opens the crew screen from the menu.
+
+        @param state The Button State (e.g. pressed).
+    */
+    fn execute_open_crew(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press && self.game_state == GameState::InMenu {
+            self.crew_selection = 0;
+            self.game_state = GameState::Crew;
+        }
+    }
+
+    /*
+        Handles the crew screen, choosing a crew member and giving orders.
+
+        @param state The Button State (e.g. pressed).
+        @param key The key pressed, if it was a key.
+        @param action The action pressed, if any.
+        @return bool Whether the press was used by the crew screen.
+    */
+    fn execute_crew_input(
+        &mut self,
+        state: &ButtonState,
+        key: Option<Key>,
+        action: Option<Action>,
+    ) -> bool {
+        use self::Key::*;
+        if *state != ButtonState::Press {
+            return true;
+        }
+        let recruited = self.world.recruited_crew();
+        let crew_count = recruited.len().max(1);
+        match (key, action) {
+            (_, Some(Action::MoveNorth)) | (Some(Up), _) => {
+                self.crew_selection = (self.crew_selection + crew_count - 1) % crew_count
+            }
+            (_, Some(Action::MoveSouth)) | (Some(Down), _) => {
+                self.crew_selection = (self.crew_selection + 1) % crew_count
+            }
+            (_, Some(Action::Interact)) => {
+                if let Some(&member) = recruited.get(self.crew_selection) {
+                    self.world.execute(Command::CycleStation(member));
                 }
-                _ => {}
-            },
+            }
+            (_, Some(Action::OpenMenu)) | (Some(Return), _) => self.game_state = GameState::InMenu,
+            (_, Some(Action::PlotCourse)) => self.world.execute(Command::PlotCourse),
+            _ => {}
+        }
+        true
+    }
+
+    /*
+        Opens the journal from the menu.
+
+        @param state The Button State (e.g. pressed).
+    */
+    fn execute_open_journal(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press && self.game_state == GameState::InMenu {
+            self.game_state = GameState::Journal;
+        }
+    }
+
+    /*
+        Opens the achievements page from the menu.
+
+        @param state The Button State (e.g. pressed).
+    */
+    fn execute_open_achievements(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press && self.game_state == GameState::InMenu {
+            self.game_state = GameState::Achievements;
+        }
+    }
+
+    /*
+        Opens the controls screen from the menu.
+
+        @param state The Button State (e.g. pressed).
+    */
+    fn execute_open_controls(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press && self.game_state == GameState::InMenu {
+            self.controls_selection = 0;
+            self.rebinding = false;
+            self.game_state = GameState::Controls;
+        }
+    }
+
+    /*
+        Opens the world map from the game or the menu, centred on the ship.
+
+        @param state The Button State (e.g. pressed).
+    */
+    fn execute_open_map(&mut self, state: &ButtonState) {
+        let from = self.game_state == GameState::InGame || self.game_state == GameState::InMenu;
+        if *state == ButtonState::Press && from {
+            let (x, y) = focus(&self.world);
+            self.map_view.center_on(x, y);
+            self.game_state = GameState::WorldMap;
+        }
+    }
+
+    /*
+        Handles the world map: panning, zooming, and placing markers under the crosshair.
+
+        @param state The Button State (e.g. pressed).
+        @param key The key pressed, if it was a key.
+        @param action The action pressed, if any.
+    */
+    fn execute_map_input(&mut self, state: &ButtonState, key: Option<Key>, action: Option<Action>) {
+        use self::Key::*;
+        if *state != ButtonState::Press {
+            return;
+        }
+        if let Some(dir) = action.and_then(|action| action.direction()) {
+            let (width, height) = (self.world.view_width, self.world.view_height);
+            self.map_view.pan(dir, width, height);
+            return;
+        }
+        let (x, y) = (self.map_view.center_x, self.map_view.center_y);
+        match (key, action) {
+            (Some(Equals), _) | (Some(NumPadPlus), _) | (_, Some(Action::NextSlot)) => {
+                self.map_view.zoom_in();
+            }
+            (Some(Minus), _) | (Some(NumPadMinus), _) | (_, Some(Action::PreviousSlot)) => {
+                self.map_view.zoom_out();
+            }
+            (Some(Backspace), _) => {
+                let (x, y) = focus(&self.world);
+                self.map_view.center_on(x, y);
+            }
+            (_, Some(Action::Interact)) => self.execute_marker(state, x, y, true),
+            (_, Some(Action::ToggleHands)) => self.execute_marker(state, x, y, false),
+            (_, Some(Action::OpenMap)) => self.game_state = GameState::InGame,
+            (_, Some(Action::OpenMenu)) => self.game_state = GameState::InMenu,
+            _ => {}
+        }
+    }
+
+    /*
+        Places a marker on the world map, or removes the one nearest a position.
+
+        @param state The Button State (e.g. pressed).
+        @param x The x position, in tiles.
+        @param y The y position, in tiles.
+        @param place Whether to place a marker, rather than remove one.
+    */
+    fn execute_marker(&mut self, state: &ButtonState, x: f64, y: f64, place: bool) {
+        if *state != ButtonState::Press {
+            return;
+        }
+        if place {
+            self.world.map.add_marker(x.floor() as i32, y.floor() as i32);
+        } else {
+            let reach = self.map_view.marker_reach();
+            self.world.map.remove_marker_near(x, y, reach);
+        }
+    }
+
+    /*
+        Handles the controls screen, choosing an action and binding a key to it.
+        Its own keys are fixed, so a bad binding can't lock the player out.
+
+        @param state The Button State (e.g. pressed).
+        @param key The key pressed, if it was a key.
+        @param pad_action The gamepad action pressed, if any.
+    */
+    fn execute_controls_input(
+        &mut self,
+        state: &ButtonState,
+        key: Option<Key>,
+        pad_action: Option<Action>,
+    ) {
+        use self::Key::*;
+        if *state != ButtonState::Press {
+            return;
+        }
+        let action = ACTIONS[self.controls_selection];
+        if self.rebinding {
+            // Only keys can be bound.
+            if let Some(key) = key {
+                self.rebinding = false;
+                self.controls_status = match self.bindings.rebind(action, key) {
+                    Ok(()) => self.save_bindings(format!("{} bound to {}", action, key_name(key))),
+                    Err(e) => format!("{}", e),
+                };
+            }
+            return;
+        }
+        match (key, pad_action) {
+            (Some(Up), _) | (_, Some(Action::MoveNorth)) => {
+                self.controls_selection = (self.controls_selection + ACTIONS.len() - 1)
+                    % ACTIONS.len()
+            }
+            (Some(Down), _) | (_, Some(Action::MoveSouth)) => {
+                self.controls_selection = (self.controls_selection + 1) % ACTIONS.len()
+            }
+            (Some(Return), _) => {
+                self.rebinding = true;
+                self.controls_status = format!("Press a key for {}", action);
+            }
+            (Some(Backspace), _) => {
+                self.bindings = Bindings::new();
+                self.controls_status = self.save_bindings("Restored the default controls".to_string());
+            }
+            (Some(Tab), _) | (_, Some(Action::OpenMenu)) => self.game_state = GameState::InMenu,
             _ => {}
         }
     }
 
     /*
-        Changes player position in the world.
+        Writes the bindings to their file.
+
+        @param done What to report if they were written.
+        @return String What to report on the controls screen.
+    */
+    fn save_bindings(&self, done: String) -> String {
+        match self.bindings.save() {
+            Ok(()) => done,
+            Err(e) => format!("Could not save controls: {}", e),
+        }
+    }
+
+    /*
+        Describes where the island a quest points the player to lies, e.g. "40 tiles NE".
 
-        @param state State of the toggled button (e.g. pressed).
+        @param name The quest's name.
+        @return Option<String> The bearing, if the quest points to an island that's loaded.
     */
-    fn change_player_location(&mut self) {
-        self.player_location = match self.player_location {
-            PlayerLocation::OnShip => PlayerLocation::InWorld,
-            PlayerLocation::InWorld => PlayerLocation::OnShip,
+    fn quest_target_bearing(&self, name: &str) -> Option<String> {
+        let target = self.world.quests.progress.get(name)?.target?;
+        let island = self.world
+            .map
+            .islands()
+            .into_iter()
+            .find(|island| island.id == target)?;
+        let (x, y) = self.world.player_tile();
+        let (center_x, center_y) = island.center();
+        let (diff_x, diff_y) = (center_x - x as f64, center_y - y as f64);
+        let vertical = if diff_y < -diff_x.abs() / 2.0 {
+            "N"
+        } else if diff_y > diff_x.abs() / 2.0 {
+            "S"
+        } else {
+            ""
         };
-        self.player.creature_state = CreatureState::Normal;
+        let horizontal = if diff_x > diff_y.abs() / 2.0 {
+            "E"
+        } else if diff_x < -diff_y.abs() / 2.0 {
+            "W"
+        } else {
+            ""
+        };
+        Some(format!(
+            "{:.0} tiles {}{}",
+            diff_x.hypot(diff_y),
+            vertical,
+            horizontal
+        ))
     }
 
     /*
-        Handles "pickup" and "drop" for the player.
+        Edits the seed typed on the title screen.
 
         @param state The Button State (e.g. pressed).
+        @param key The key pressed.
+        @return bool Whether the key was used for the seed.
     */
-    fn execute_player_hands(&mut self, state: &ButtonState) {
-        if *state == ButtonState::Press {
-            match self.player.inventory {
-                Some(_) => {
-                    // Dropping an item.
-                    let item = self.player.drop_item().expect("dropped empty inventory");
-                    self.items_in_game.push(item);
-                }
-                None => {
-                    let mut place = -1;
-                    for i in 0..self.items_in_game.len() {
-                        // Determines if player is in range of item.
-                        let diff_x = self.items_in_game[i].x - self.player.x;
-                        let diff_y = self.items_in_game[i].y - self.player.y;
-                        if diff_x < IMAGE_SIZE_SCALED && diff_x > -IMAGE_SIZE_SCALED
-                            && diff_y < IMAGE_SIZE_SCALED
-                            && diff_y > -IMAGE_SIZE_SCALED
-                        {
-                            place = i as i32;
-                            break;
-                        }
-                    }
-                    if place != -1 {
-                        // Add item to inventory.
-                        let item = self.items_in_game.remove(place as usize);
-                        self.player.pickup_item(item);
-                    }
+    fn execute_seed_input(&mut self, state: &ButtonState, key: Key) -> bool {
+        use self::Key::*;
+        let digit = match key {
+            D0 | NumPad0 => '0',
+            D1 | NumPad1 => '1',
+            D2 | NumPad2 => '2',
+            D3 | NumPad3 => '3',
+            D4 | NumPad4 => '4',
+            D5 | NumPad5 => '5',
+            D6 | NumPad6 => '6',
+            D7 | NumPad7 => '7',
+            D8 | NumPad8 => '8',
+            D9 | NumPad9 => '9',
+            Backspace => {
+                if *state == ButtonState::Press {
+                    self.seed_input.pop();
                 }
+                return true;
             }
+            _ => return false,
+        };
+        // Only accept digits that still fit in a seed.
+        let mut new_input = self.seed_input.clone();
+        new_input.push(digit);
+        if *state == ButtonState::Press && new_input.parse::<u64>().is_ok() {
+            self.seed_input = new_input;
         }
+        true
     }
 
     /*
-        Opens the menu and changes Game State respectively.
+        Regenerates the map if a different seed was entered on the title screen.
+    */
+    fn apply_seed_input(&mut self) {
+        if let Ok(seed) = self.seed_input.parse::<u64>() {
+            if seed != self.world.map.seed {
+                // Achievements carry over to the new world.
+                let counts = self.world.achievements.counts.clone();
+                self.world = World::new(Map::with_seed(seed));
+                self.world.achievements.counts = counts;
+                self.stick_unsent = true;
+            }
+        }
+        self.seed_input.clear();
+    }
+
+    /*
+        Writes the current game to a save slot.
+
+        @param slot The slot number, starting at 1.
+    */
+    fn save_game(&mut self, slot: usize) {
+        let text = save::write_save(&self.world);
+        self.save_status = match save::save_to_slot(slot, &text) {
+            Ok(()) => {
+                self.latest_slot = Some(slot);
+                format!("Saved to slot {}", slot)
+            }
+            Err(e) => format!("Could not save: {}", e),
+        };
+        self.save_achievements();
+    }
+
+    /*
+        Writes the player's achievement progress to its file.
+    */
+    fn save_achievements(&mut self) {
+        let text = save::write_achievements(&self.world.achievements.counts);
+        if let Err(e) = save::save_achievements(&text) {
+            self.save_status = format!("Could not save achievements: {}", e);
+        }
+    }
+
+    /*
+        Writes the current ship to a blueprint in the ships folder, from the menu.
 
         @param state The Button State (e.g. pressed).
     */
-    fn execute_open_menu(&mut self, state: &ButtonState) {
-        if *state == ButtonState::Press {
-            match self.game_state {
-                GameState::Title => {
-                    self.game_state = GameState::InMenu;
-                }
-                GameState::InGame => {
-                    self.game_state = GameState::InMenu;
-                }
-                GameState::InMenu => {
-                    self.game_state = GameState::InGame;
-                }
-                _ => {}
+    fn execute_export_ship(&mut self, state: &ButtonState) {
+        if *state != ButtonState::Press || self.game_state != GameState::InMenu {
+            return;
+        }
+        let blueprint = self.world.ship_blueprint(EXPORTED_SHIP);
+        self.save_status = match blueprint.save(EXPORTED_SHIP) {
+            Ok(()) => format!("Exported ship to {}.ship", EXPORTED_SHIP),
+            Err(e) => format!("Could not export ship: {}", e),
+        };
+    }
+
+    /*
+        Replaces the current game with one from a save slot.
+
+        @param slot The slot number, starting at 1.
+        @return bool Whether the load was successful.
+    */
+    fn load_game(&mut self, slot: usize) -> bool {
+        match save::load_from_slot(slot) {
+            Ok(data) => {
+                self.world.load(data);
+                self.stick_unsent = true;
+                self.save_status = format!("Loaded slot {}", slot);
+                true
+            }
+            Err(e) => {
+                self.save_status = format!("Could not load slot {}: {}", slot, e);
+                false
             }
         }
     }
 
     /*
-        Moves the player / ship, depending on player control state.
-        Calls respective ship / player functionality to handle.
+        Continues from the most recent save when on the title screen.
 
-        @param state Button State (e.g. pressed or released).
-        @param key Some key that has been pressed.
+        @param state The Button State (e.g. pressed).
     */
-    fn execute_move(&mut self, state: &ButtonState, key: &Option<Key>) {
-        if self.game_state == GameState::InGame {
-            match self.player.creature_state {
-                CreatureState::Normal => {
-                    self.player.handle_input(&state, &key);
-                    self.player.update_self_velocity();
-                }
-                CreatureState::ControllingShip => {
-                    self.ship.handle_input(&state, &key);
-                    self.ship.update_self_velocity();
+    fn execute_continue(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press && self.game_state == GameState::Title {
+            if let Some(slot) = self.latest_slot {
+                if self.load_game(slot) {
+                    self.game_state = GameState::InGame;
                 }
             }
         }
     }
 
     /*
-        Handles player general "action" button, with varying results (a sort of catch all).
+        Saves to a slot from the menu, or loads it from the title screen.
 
         @param state The Button State (e.g. pressed).
+        @param slot The slot number, starting at 1.
     */
-    fn execute_action(&mut self, state: &ButtonState) {
-        if self.game_state == GameState::InGame {
-            if *state == ButtonState::Press {
-                let current_location = self.player_location.clone();
-                match self.tile_under_player(current_location) {
-                    Some(t) => match t.tile_type {
-                        TileType::Portal => match self.player_location {
-                            PlayerLocation::OnShip => {
-                                if let Some(tile) = self.tile_under_player(PlayerLocation::InWorld)
-                                {
-                                    if tile.passable {
-                                        self.change_player_location();
-                                        let x = (self.player.x + IMAGE_SIZE_SCALED as f64 / 2.0)
-                                            / IMAGE_SIZE_SCALED;
-                                        let y = (self.player.y + IMAGE_SIZE_SCALED as f64 / 2.0)
-                                            / IMAGE_SIZE_SCALED;
-                                        self.player.x = x.floor() * IMAGE_SIZE_SCALED;
-                                        self.player.y = y.floor() * IMAGE_SIZE_SCALED;
-
-                                        let x = self.player.x + IMAGE_SIZE_SCALED as f64 / 2.0;
-                                        let y = self.player.y + IMAGE_SIZE_SCALED as f64 / 2.0;
-                                        let temp = self.map.tiles
-                                            [(x / IMAGE_SIZE_SCALED).floor() as usize]
-                                            [(y / IMAGE_SIZE_SCALED).floor() as usize]
-                                            .clone();
-
-                                        self.map.under_portal = temp;
-                                        self.map.tiles[(x / IMAGE_SIZE_SCALED).floor() as usize]
-                                            [(y / IMAGE_SIZE_SCALED).floor() as usize] =
-                                            Tile::new(TileType::Portal);
-                                    }
-                                }
-                            }
-                            PlayerLocation::InWorld => {
-                                if let Some(tile) = self.tile_under_player(PlayerLocation::OnShip) {
-                                    if tile.passable {
-                                        self.change_player_location();
-
-                                        let x = self.player.x + IMAGE_SIZE_SCALED as f64 / 2.0;
-                                        let y = self.player.y + IMAGE_SIZE_SCALED as f64 / 2.0;
-                                        self.player.x = self.ship.x + 3.0 * IMAGE_SIZE_SCALED;
-                                        self.player.y = self.ship.y;
-
-                                        self.map.tiles[(x / IMAGE_SIZE_SCALED).floor() as usize]
-                                            [(y / IMAGE_SIZE_SCALED).floor() as usize] =
-                                            self.map.under_portal.clone();
-                                        self.map.under_portal = Tile::new(TileType::Air);
-                                    }
-                                }
-                            }
-                        },
+    fn execute_save_slot(&mut self, state: &ButtonState, slot: usize) {
+        if *state == ButtonState::Press {
+            if self.game_state == GameState::InMenu {
+                self.save_game(slot);
+            } else if self.game_state == GameState::Title && self.load_game(slot) {
+                self.game_state = GameState::InGame;
+            }
+        }
+    }
 
-                        TileType::Wheel => {
-                            self.player.change_control_state();
-                            self.ship.reset_dir();
-                        }
-                        _ => self.player.use_item(),
-                    },
-                    _ => {
-                        self.player.use_item();
-                    }
+    /*
+        Opens the menu and changes Game State respectively.
+
+        @param state The Button State (e.g. pressed).
+    */
+    fn execute_open_menu(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press {
+            match self.game_state {
+                GameState::Title => {
+                    self.apply_seed_input();
+                    self.game_state = GameState::InMenu;
+                }
+                GameState::InGame => {
+                    self.game_state = GameState::InMenu;
+                }
+                GameState::InMenu => {
+                    self.game_state = GameState::InGame;
                 }
+                _ => {}
             }
         }
     }
-}
 
-/*
-    Creates prototypes of items for prototyping pattern.
-
-    @return HashMap<String, Item> A map of string to items.
-*/
-fn generate_item_prototypes() -> HashMap<String, Item> {
-    let mut prototypes: HashMap<String, Item> = HashMap::new();
-    prototypes.insert(
-        "bisket".to_string(),
-        Item::new(ItemType::Food(FoodType::Bisket), 1, true, 1.0),
-    );
-    prototypes.insert(
-        "sword".to_string(),
-        Item::new(
-            ItemType::Interactable(InteractableType::Sword),
-            10,
-            true,
-            5.0,
-        ),
-    );
-    prototypes.insert(
-        "grune".to_string(),
-        Item::new(ItemType::Resource(ResourceType::Grune), 1, true, 0.5),
-    );
-    prototypes.insert(
-        "logs".to_string(),
-        Item::new(ItemType::Resource(ResourceType::Logs), 5, true, 8.0),
-    );
-    prototypes
 }
 
 /* 
@@ -722,6 +1942,9 @@ fn generate_textures(window: &mut PistonWindow) -> HashMap<String, G2dTexture> {
         IMG_CLOUD_2,
         IMG_WOOD_FLOOR,
         IMG_WHEEL,
+        IMG_WORKBENCH,
+        IMG_LOOKOUT,
+        IMG_CANNON,
         IMG_TREE,
         IMG_GRUNE,
         IMG_PORTAL,
@@ -730,6 +1953,7 @@ fn generate_textures(window: &mut PistonWindow) -> HashMap<String, G2dTexture> {
         IMG_ITEM_SWORD,
         IMG_ITEM_GRUNE,
         IMG_ITEM_LOGS,
+        IMG_ITEM_PLANKS,
         IMG_STONE_WALL,
         IMG_STONE_WALL_TEXTURE,
         IMG_STONE_WALL_EDGE_1_SIDE,
@@ -0,0 +1,616 @@
+//! Input is read as actions, like moving north or interacting, rather than as raw keys,
+//! so players can choose their own keys. Bindings map each action to its keys, and are
+//! kept in a small config file, one action per line:
+//!
+//! ```text
+//! ; Comments start with a semicolon.
+//! move_north = W
+//! open_menu = Tab Return
+//! ```
+//!
+//! Actions missing from the file keep their default keys, if those are still free.
+//! Some keys stay with the game itself and can't be bound, like the number keys used
+//! for the seed and for building.
+//!
+//! Gamepads give the same actions from their buttons, with the d-pad for movement and
+//! menus. The left stick moves by how far it's pushed rather than as an action.
+
+use misc::Direction;
+use piston_window::{ControllerAxisArgs, Key};
+use std::fmt;
+use std::fs;
+use std::io;
+
+const BINDINGS_FILE: &str = "bindings.txt";
+
+// Gamepad buttons and axes, numbered as in SDL's game controller layout.
+const BUTTON_A: u8 = 0;
+const BUTTON_B: u8 = 1;
+const BUTTON_X: u8 = 2;
+const BUTTON_Y: u8 = 3;
+const BUTTON_BACK: u8 = 4;
+const BUTTON_START: u8 = 6;
+const BUTTON_LEFT_SHOULDER: u8 = 9;
+const BUTTON_RIGHT_SHOULDER: u8 = 10;
+const BUTTON_DPAD_UP: u8 = 11;
+const BUTTON_DPAD_DOWN: u8 = 12;
+const BUTTON_DPAD_LEFT: u8 = 13;
+const BUTTON_DPAD_RIGHT: u8 = 14;
+const AXIS_LEFT_X: u8 = 0;
+const AXIS_LEFT_Y: u8 = 1;
+
+// How far the stick must be pushed before it moves anything, as sticks rarely rest at 0.
+pub const STICK_DEAD_ZONE: f64 = 0.2;
+
+/*
+    Keys the game always handles itself: the seed and build keys, the save slots, and
+    Escape, which closes the window.
+*/
+pub const RESERVED_KEYS: [Key; 15] = [
+    Key::D0,
+    Key::D1,
+    Key::D2,
+    Key::D3,
+    Key::D4,
+    Key::D5,
+    Key::D6,
+    Key::D7,
+    Key::D8,
+    Key::D9,
+    Key::Backspace,
+    Key::Escape,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+];
+
+/*
+    Shortcuts on the title screen and in the menu. They're handled before the bindings
+    there, so only actions used in game can share them.
+*/
+pub const MENU_KEYS: [Key; 8] = [Key::C, Key::I, Key::K, Key::R, Key::J, Key::H, Key::O, Key::F5];
+
+/**
+    Things the player can do with a key.
+
+    @variant MoveNorth Move the player, or the ship when steering, up.
+    @variant MoveSouth Move down.
+    @variant MoveEast Move right.
+    @variant MoveWest Move left.
+    @variant Interact Use the held item, talk, or use what's in front of the player.
+    @variant ToggleHands Pick up or drop an item.
+    @variant DropItem Drop the held item.
+    @variant Throw Throw the held item.
+    @variant PreviousSlot Hold the previous inventory slot.
+    @variant NextSlot Hold the next inventory slot.
+    @variant ToggleBuildMode Start or stop building the ship.
+    @variant OpenMenu Open or close the menu.
+    @variant OpenMap Open or close the world map.
+    @variant PlotCourse Plot a course to the nearest island, from the crew screen.
+    @variant TakeDamage Hurt the player, for testing.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    MoveNorth,
+    MoveSouth,
+    MoveEast,
+    MoveWest,
+    Interact,
+    ToggleHands,
+    DropItem,
+    Throw,
+    PreviousSlot,
+    NextSlot,
+    ToggleBuildMode,
+    OpenMenu,
+    OpenMap,
+    PlotCourse,
+    TakeDamage,
+}
+
+/*
+    Every action, in the order they're listed on the controls screen.
+*/
+pub const ACTIONS: [Action; 15] = [
+    Action::MoveNorth,
+    Action::MoveSouth,
+    Action::MoveEast,
+    Action::MoveWest,
+    Action::Interact,
+    Action::ToggleHands,
+    Action::DropItem,
+    Action::Throw,
+    Action::PreviousSlot,
+    Action::NextSlot,
+    Action::ToggleBuildMode,
+    Action::OpenMenu,
+    Action::OpenMap,
+    Action::PlotCourse,
+    Action::TakeDamage,
+];
+
+impl Action {
+    /*
+        Finds the action for moving in a direction.
+
+        @param dir The direction.
+        @return Action The movement action.
+    */
+    pub fn moving(dir: Direction) -> Action {
+        match dir {
+            Direction::N => Action::MoveNorth,
+            Direction::S => Action::MoveSouth,
+            Direction::E => Action::MoveEast,
+            Direction::W => Action::MoveWest,
+        }
+    }
+
+    /*
+        Determines which way a movement action moves.
+
+        @return Option<Direction> The direction, or None if the action isn't movement.
+    */
+    pub fn direction(&self) -> Option<Direction> {
+        match *self {
+            Action::MoveNorth => Some(Direction::N),
+            Action::MoveSouth => Some(Direction::S),
+            Action::MoveEast => Some(Direction::E),
+            Action::MoveWest => Some(Direction::W),
+            _ => None,
+        }
+    }
+
+    /*
+        Generates the action's name, as used in the bindings file.
+
+        @return &str The name.
+    */
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Action::MoveNorth => "move_north",
+            Action::MoveSouth => "move_south",
+            Action::MoveEast => "move_east",
+            Action::MoveWest => "move_west",
+            Action::Interact => "interact",
+            Action::ToggleHands => "toggle_hands",
+            Action::DropItem => "drop_item",
+            Action::Throw => "throw",
+            Action::PreviousSlot => "previous_slot",
+            Action::NextSlot => "next_slot",
+            Action::ToggleBuildMode => "toggle_build_mode",
+            Action::OpenMenu => "open_menu",
+            Action::OpenMap => "open_map",
+            Action::PlotCourse => "plot_course",
+            Action::TakeDamage => "take_damage",
+        }
+    }
+
+    /*
+        Generates the action's label, as shown on the controls screen.
+
+        @return &str The label.
+    */
+    pub fn label(&self) -> &'static str {
+        match *self {
+            Action::MoveNorth => "Move north",
+            Action::MoveSouth => "Move south",
+            Action::MoveEast => "Move east",
+            Action::MoveWest => "Move west",
+            Action::Interact => "Use item/Interact",
+            Action::ToggleHands => "Pickup/Drop item",
+            Action::DropItem => "Drop held item",
+            Action::Throw => "Throw held item",
+            Action::PreviousSlot => "Previous held slot",
+            Action::NextSlot => "Next held slot",
+            Action::ToggleBuildMode => "Build mode",
+            Action::OpenMenu => "Enter/Exit the menu",
+            Action::OpenMap => "Open/Close the map",
+            Action::PlotCourse => "Plot course to nearest island",
+            Action::TakeDamage => "Owie :(",
+        }
+    }
+
+    /*
+        Finds an action by its name in the bindings file.
+
+        @param name The name.
+        @return Option<Action> The action, if there is one by that name.
+    */
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTIONS.iter().cloned().find(|action| action.name() == name)
+    }
+
+    /*
+        Generates the keys an action is bound to before the player changes anything.
+
+        @return Vec<Key> The keys.
+    */
+    pub fn default_keys(&self) -> Vec<Key> {
+        match *self {
+            Action::MoveNorth => vec![Key::W],
+            Action::MoveSouth => vec![Key::S],
+            Action::MoveEast => vec![Key::D],
+            Action::MoveWest => vec![Key::A],
+            Action::Interact => vec![Key::E],
+            Action::ToggleHands => vec![Key::Space],
+            Action::DropItem => vec![Key::Q],
+            Action::Throw => vec![Key::T],
+            Action::PreviousSlot => vec![Key::LeftBracket],
+            Action::NextSlot => vec![Key::RightBracket],
+            Action::ToggleBuildMode => vec![Key::B],
+            Action::OpenMenu => vec![Key::Tab, Key::Return],
+            Action::OpenMap => vec![Key::M],
+            Action::PlotCourse => vec![Key::P],
+            Action::TakeDamage => vec![Key::L],
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/*
+    Generates a key's name, as used in the bindings file and shown to the player.
+
+    @param key The key.
+    @return String The name.
+*/
+pub fn key_name(key: Key) -> String {
+    format!("{:?}", key)
+}
+
+/*
+    Finds a key by its name. Piston only names keys through Debug, so this looks through
+    the key codes it knows.
+
+    @param name The name.
+    @return Option<Key> The key, if there is one by that name.
+*/
+pub fn key_from_name(name: &str) -> Option<Key> {
+    (0..0x80)
+        .chain(0x4000_0039..0x4000_0120)
+        .map(Key::from)
+        .find(|&key| key != Key::Unknown && key_name(key) == name)
+}
+
+/**
+    Errors that can occur while reading bindings.
+
+    @variant Io The file could not be read or written.
+    @variant Malformed The bindings are invalid, with the line and the reason.
+*/
+#[derive(Debug)]
+pub enum BindingsError {
+    Io(io::Error),
+    Malformed(usize, String),
+}
+
+impl From<io::Error> for BindingsError {
+    fn from(error: io::Error) -> Self {
+        BindingsError::Io(error)
+    }
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BindingsError::Io(ref e) => write!(f, "{}", e),
+            BindingsError::Malformed(line, ref reason) => write!(f, "line {}: {}", line, reason),
+        }
+    }
+}
+
+/**
+    Reasons a key can't be bound to an action.
+
+    @variant Reserved The game keeps the key for itself.
+    @variant Taken The key is already bound to another action.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BindError {
+    Reserved(Key),
+    Taken(Key, Action),
+}
+
+impl fmt::Display for BindError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BindError::Reserved(key) => write!(f, "{} is kept for the game", key_name(key)),
+            BindError::Taken(key, action) => {
+                write!(f, "{} is already bound to {}", key_name(key), action)
+            }
+        }
+    }
+}
+
+/**
+    Implementation of the Bindings object.
+
+    @field keys Each action with the keys bound to it, in the order of ACTIONS.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    pub keys: Vec<(Action, Vec<Key>)>,
+}
+
+impl Bindings {
+    /*
+        Bindings constructor, with every action on its default keys.
+
+        @return Bindings Returns self.
+    */
+    pub fn new() -> Self {
+        Bindings {
+            keys: ACTIONS
+                .iter()
+                .map(|action| (*action, action.default_keys()))
+                .collect(),
+        }
+    }
+
+    /*
+        Reads the bindings file. Every action keeps its default keys if there isn't one.
+
+        @return Result<Bindings, BindingsError> The bindings.
+    */
+    pub fn load() -> Result<Bindings, BindingsError> {
+        match fs::read_to_string(BINDINGS_FILE) {
+            Ok(text) => Bindings::parse(&text),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Bindings::new()),
+            Err(e) => Err(BindingsError::Io(e)),
+        }
+    }
+
+    /*
+        Writes the bindings to the bindings file.
+
+        @return Result<(), BindingsError> Whether the file was written.
+    */
+    pub fn save(&self) -> Result<(), BindingsError> {
+        fs::write(BINDINGS_FILE, self.write())?;
+        Ok(())
+    }
+
+    /*
+        Reads bindings from text.
+
+        @param text The bindings text.
+        @return Result<Bindings, BindingsError> The bindings.
+    */
+    pub fn parse(text: &str) -> Result<Bindings, BindingsError> {
+        let malformed = |line: usize, reason: String| Err(BindingsError::Malformed(line, reason));
+        let mut bindings = Bindings {
+            keys: ACTIONS.iter().map(|action| (*action, vec![])).collect(),
+        };
+        let mut listed = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            let (name, keys) = match line.find('=') {
+                Some(split) => (line[..split].trim(), line[split + 1..].trim()),
+                None => return malformed(line_number, "expected action = keys".to_string()),
+            };
+            let action = match Action::from_name(name) {
+                Some(action) => action,
+                None => return malformed(line_number, format!("unknown action '{}'", name)),
+            };
+            if listed.contains(&action) {
+                return malformed(line_number, format!("{} is listed twice", name));
+            }
+            listed.push(action);
+            for key_text in keys.split_whitespace() {
+                let key = match key_from_name(key_text) {
+                    Some(key) => key,
+                    None => return malformed(line_number, format!("unknown key '{}'", key_text)),
+                };
+                if let Err(e) = bindings.check(action, key) {
+                    return malformed(line_number, format!("{}", e));
+                }
+                bindings.keys_mut(action).push(key);
+            }
+        }
+
+        // Actions left out keep whichever of their default keys are still free.
+        for action in ACTIONS.iter().filter(|action| !listed.contains(action)) {
+            for key in action.default_keys() {
+                if bindings.check(*action, key).is_ok() {
+                    bindings.keys_mut(*action).push(key);
+                }
+            }
+        }
+        Ok(bindings)
+    }
+
+    /*
+        Writes the bindings as text, in the bindings file format.
+
+        @return String The bindings text.
+    */
+    pub fn write(&self) -> String {
+        let mut out = "; Controls, as action = keys.\n".to_string();
+        for &(action, ref keys) in &self.keys {
+            let names: Vec<String> = keys.iter().map(|&key| key_name(key)).collect();
+            out.push_str(&format!("{} = {}\n", action.name(), names.join(" ")));
+        }
+        out
+    }
+
+    /*
+        Finds the keys bound to an action.
+
+        @param action The action.
+        @return &[Key] The keys, possibly none.
+    */
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.keys
+            .iter()
+            .find(|&&(bound, _)| bound == action)
+            .map(|(_, keys)| &keys[..])
+            .unwrap_or(&[])
+    }
+
+    /*
+        Finds the keys bound to an action, to change them.
+
+        @param action The action.
+        @return &mut Vec<Key> The keys.
+    */
+    fn keys_mut(&mut self, action: Action) -> &mut Vec<Key> {
+        let index = ACTIONS
+            .iter()
+            .position(|&listed| listed == action)
+            .expect("Every action is in ACTIONS");
+        &mut self.keys[index].1
+    }
+
+    /*
+        Finds the action a key is bound to.
+
+        @param key The key.
+        @return Option<Action> The action, if the key is bound.
+    */
+    pub fn action_for(&self, key: Key) -> Option<Action> {
+        self.keys
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|&(action, _)| action)
+    }
+
+    /*
+        Describes the keys bound to an action, e.g. "Tab/Return".
+
+        @param action The action.
+        @return String The key names, or "Unbound".
+    */
+    pub fn describe(&self, action: Action) -> String {
+        let names: Vec<String> = self.keys(action).iter().map(|&key| key_name(key)).collect();
+        if names.is_empty() {
+            "Unbound".to_string()
+        } else {
+            names.join("/")
+        }
+    }
+
+    /*
+        Determines whether a key could be bound to an action without a conflict.
+
+        @param action The action.
+        @param key The key.
+        @return Result<(), BindError> Why it can't, if it can't.
+    */
+    pub fn check(&self, action: Action, key: Key) -> Result<(), BindError> {
+        if RESERVED_KEYS.contains(&key) || (action == Action::OpenMenu && MENU_KEYS.contains(&key))
+        {
+            return Err(BindError::Reserved(key));
+        }
+        match self.action_for(key) {
+            Some(bound) if bound != action => Err(BindError::Taken(key, bound)),
+            _ => Ok(()),
+        }
+    }
+
+    /*
+        Binds an action to a single key, in place of the keys it had.
+
+        @param action The action.
+        @param key The key.
+        @return Result<(), BindError> Why the key couldn't be bound, if it couldn't.
+    */
+    pub fn rebind(&mut self, action: Action, key: Key) -> Result<(), BindError> {
+        self.check(action, key)?;
+        *self.keys_mut(action) = vec![key];
+        Ok(())
+    }
+}
+
+/*
+    Finds the action for a gamepad button. Face buttons interact, pick up, drop and
+    throw, Start opens the menu and the d-pad moves.
+
+    @param button The button's number.
+    @return Option<Action> The action, if the button has one.
+*/
+pub fn gamepad_action(button: u8) -> Option<Action> {
+    match button {
+        BUTTON_A => Some(Action::Interact),
+        BUTTON_B => Some(Action::ToggleHands),
+        BUTTON_X => Some(Action::DropItem),
+        BUTTON_Y => Some(Action::Throw),
+        BUTTON_BACK => Some(Action::ToggleBuildMode),
+        BUTTON_START => Some(Action::OpenMenu),
+        BUTTON_LEFT_SHOULDER => Some(Action::PreviousSlot),
+        BUTTON_RIGHT_SHOULDER => Some(Action::NextSlot),
+        BUTTON_DPAD_UP => Some(Action::MoveNorth),
+        BUTTON_DPAD_DOWN => Some(Action::MoveSouth),
+        BUTTON_DPAD_LEFT => Some(Action::MoveWest),
+        BUTTON_DPAD_RIGHT => Some(Action::MoveEast),
+        _ => None,
+    }
+}
+
+/**
+    Implementation of the Gamepad object, which follows the left stick.
+
+    @field axes Where the stick is, as reported, from -1 to 1 on each axis.
+    @field stick Where the stick moves towards, after the dead zone.
+*/
+pub struct Gamepad {
+    pub axes: (f64, f64),
+    pub stick: (f64, f64),
+}
+
+impl Gamepad {
+    /*
+        Gamepad constructor, with the stick at rest.
+
+        @return Gamepad Returns self.
+    */
+    pub fn new() -> Self {
+        Gamepad {
+            axes: (0.0, 0.0),
+            stick: (0.0, 0.0),
+        }
+    }
+
+    /*
+        Follows an axis of the left stick moving.
+
+        @param args The axis event.
+        @return Option<(f64, f64)> Where the stick now moves towards, if that changed.
+    */
+    pub fn move_axis(&mut self, args: ControllerAxisArgs) -> Option<(f64, f64)> {
+        match args.axis {
+            AXIS_LEFT_X => self.axes.0 = args.position,
+            AXIS_LEFT_Y => self.axes.1 = args.position,
+            _ => return None,
+        }
+        let stick = apply_dead_zone(self.axes);
+        if stick == self.stick {
+            return None;
+        }
+        self.stick = stick;
+        Some(stick)
+    }
+}
+
+/*
+    Ignores small stick movements, and rescales the rest so pushing just past the dead
+    zone starts slowly and pushing fully moves at full speed.
+
+    @param axes Where the stick is, from -1 to 1 on each axis.
+    @return (f64, f64) How far to move on each axis, at most 1 overall.
+*/
+pub fn apply_dead_zone(axes: (f64, f64)) -> (f64, f64) {
+    let distance = axes.0.hypot(axes.1);
+    if distance < STICK_DEAD_ZONE {
+        return (0.0, 0.0);
+    }
+    let scale = ((distance - STICK_DEAD_ZONE) / (1.0 - STICK_DEAD_ZONE)).min(1.0) / distance;
+    (axes.0 * scale, axes.1 * scale)
+}
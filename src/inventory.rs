@@ -0,0 +1,207 @@
+//! The Inventory holds a Creature's items in a row of slots.
+//!
+//! Items of the same type stack in one slot, up to a limit for their kind, and the
+//! total weight carried is capped. One slot is the "hand", which using and dropping act on.
+
+use item::*;
+
+pub const INVENTORY_SLOTS: usize = 8;
+pub const MAX_CARRY_WEIGHT: f64 = 100.0;
+
+/**
+    Implementation of the Stack object, a pile of identical items in one slot.
+
+    @field item The item the stack is made of.
+    @field count How many of the item are in the stack.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stack {
+    pub item: Item,
+    pub count: u32,
+}
+
+/**
+    Implementation of the Inventory object.
+
+    @field slots The slots, each empty or holding a stack.
+    @field hand The selected slot, which is used and dropped from.
+    @field max_weight The most weight that can be carried.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Inventory {
+    pub slots: Vec<Option<Stack>>,
+    pub hand: usize,
+    pub max_weight: f64,
+}
+
+impl Inventory {
+    /*
+        Inventory constructor.
+
+        @param slot_count The number of slots.
+        @param max_weight The most weight that can be carried.
+        @return Inventory Returns self.
+    */
+    pub fn new(slot_count: usize, max_weight: f64) -> Self {
+        Inventory {
+            slots: vec![None; slot_count],
+            hand: 0,
+            max_weight: max_weight,
+        }
+    }
+
+    /*
+        Calculates the weight of everything carried.
+
+        @return f64 The total weight.
+    */
+    pub fn weight(&self) -> f64 {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.as_ref())
+            .map(|stack| stack.item.weight * stack.count as f64)
+            .sum()
+    }
+
+    /*
+        Counts the items of a type across all slots.
+
+        @param item_type The item type.
+        @return u32 The number carried.
+    */
+    pub fn count(&self, item_type: &ItemType) -> u32 {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.as_ref())
+            .filter(|stack| stack.item.item_type == *item_type)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    /*
+        Determines if an item would fit, by weight and by slots.
+
+        @param item The item.
+        @return bool Whether the item fits.
+    */
+    pub fn can_add(&self, item: &Item) -> bool {
+        self.weight() + item.weight <= self.max_weight && self.slot_for(&item.item_type).is_some()
+    }
+
+    /*
+        Adds an item, onto a stack of its type if there's room, otherwise into an empty slot.
+        Prefers the hand when it's empty.
+
+        @param item The item.
+        @return bool Whether the item was added.
+    */
+    pub fn add(&mut self, mut item: Item) -> bool {
+        if self.weight() + item.weight > self.max_weight {
+            return false;
+        }
+        item.collected = true;
+        match self.slot_for(&item.item_type) {
+            Some(i) => {
+                match self.slots[i] {
+                    Some(ref mut stack) => stack.count += 1,
+                    None => self.slots[i] = Some(Stack { item: item, count: 1 }),
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /*
+        Finds the slot an item type would be added to.
+
+        @param item_type The item type.
+        @return Option<usize> The slot, if there's room.
+    */
+    fn slot_for(&self, item_type: &ItemType) -> Option<usize> {
+        let limit = item_type.stack_limit();
+        let stack = self.slots.iter().position(|slot| match *slot {
+            Some(ref stack) => stack.item.item_type == *item_type && stack.count < limit,
+            None => false,
+        });
+        if stack.is_some() {
+            return stack;
+        }
+        if self.slots[self.hand].is_none() {
+            return Some(self.hand);
+        }
+        self.slots.iter().position(|slot| slot.is_none())
+    }
+
+    /*
+        Finds the item in the hand.
+
+        @return Option<&Item> The held item, if any.
+    */
+    pub fn held(&self) -> Option<&Item> {
+        self.slots[self.hand].as_ref().map(|stack| &stack.item)
+    }
+
+    /*
+        Takes one item out of the hand.
+
+        @return Option<Item> The item, if the hand wasn't empty.
+    */
+    pub fn take_held(&mut self) -> Option<Item> {
+        let hand = self.hand;
+        self.take_from(hand)
+    }
+
+    /*
+        Takes one item out of a slot, emptying the slot with its last item.
+
+        @param slot The slot.
+        @return Option<Item> The item, if the slot wasn't empty.
+    */
+    fn take_from(&mut self, slot: usize) -> Option<Item> {
+        let (item, emptied) = match self.slots[slot] {
+            Some(ref mut stack) => {
+                stack.count -= 1;
+                (stack.item.clone(), stack.count == 0)
+            }
+            None => return None,
+        };
+        if emptied {
+            self.slots[slot] = None;
+        }
+        Some(item)
+    }
+
+    /*
+        Removes a number of items of a type, from any slots. Nothing is removed if there
+        aren't enough.
+
+        @param item_type The item type.
+        @param count The number to remove.
+        @return bool Whether the items were removed.
+    */
+    pub fn remove(&mut self, item_type: &ItemType, count: u32) -> bool {
+        if self.count(item_type) < count {
+            return false;
+        }
+        for _ in 0..count {
+            let slot = self.slots.iter().position(|slot| match *slot {
+                Some(ref stack) => stack.item.item_type == *item_type,
+                None => false,
+            });
+            if let Some(i) = slot {
+                self.take_from(i);
+            }
+        }
+        true
+    }
+
+    /*
+        Chooses the slot that is held, wrapping around the ends.
+
+        @param slot The slot.
+    */
+    pub fn select(&mut self, slot: usize) {
+        self.hand = slot % self.slots.len();
+    }
+}
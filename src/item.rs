@@ -6,8 +6,12 @@
 use piston_window::*;
 use std::collections::HashMap;
 use constants::*;
+use misc::*;
 
-#[derive(Clone)]
+pub const THROW_SPEED: f64 = 480.0;
+const ITEM_FRICTION: f64 = 300.0;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum ItemType {
     Interactable(InteractableType), // Can be thrown or interacted with (intent: used for fighting / interacting with world).
     Food(FoodType),                 // Can be thrown or consumed (intent: used for healing / buffs).
@@ -15,23 +19,86 @@ pub enum ItemType {
                                     // Other, // Can be thrown but provides passive effect (intent: used for passive effect).
 }
 
-#[derive(Clone)]
+impl ItemType {
+    /*
+        Generates the name of an item type, matching its key in the item prototypes.
+
+        @return &str The name of the item type.
+    */
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ItemType::Food(FoodType::Bisket) => "bisket",
+            ItemType::Interactable(InteractableType::Sword) => "sword",
+            ItemType::Resource(ResourceType::Logs) => "logs",
+            ItemType::Resource(ResourceType::Grune) => "grune",
+            ItemType::Resource(ResourceType::Planks) => "planks",
+        }
+    }
+
+    /*
+        Finds the item type with a given name.
+
+        @param name The name of the item type.
+        @return Option<ItemType> The item type, if the name is known.
+    */
+    pub fn from_name(name: &str) -> Option<ItemType> {
+        match name {
+            "bisket" => Some(ItemType::Food(FoodType::Bisket)),
+            "sword" => Some(ItemType::Interactable(InteractableType::Sword)),
+            "logs" => Some(ItemType::Resource(ResourceType::Logs)),
+            "grune" => Some(ItemType::Resource(ResourceType::Grune)),
+            "planks" => Some(ItemType::Resource(ResourceType::Planks)),
+            _ => None,
+        }
+    }
+
+    /*
+        Finds the sprite of an item type, if it has one.
+
+        @return Option<&str> The key of the sprite in the textures map.
+    */
+    pub fn image(&self) -> Option<&'static str> {
+        match *self {
+            ItemType::Food(FoodType::Bisket) => Some(IMG_ITEM_BISKET),
+            ItemType::Interactable(InteractableType::Sword) => Some(IMG_ITEM_SWORD),
+            ItemType::Resource(ResourceType::Logs) => Some(IMG_ITEM_LOGS),
+            ItemType::Resource(ResourceType::Grune) => Some(IMG_ITEM_GRUNE),
+            ItemType::Resource(ResourceType::Planks) => Some(IMG_ITEM_PLANKS),
+        }
+    }
+
+    /*
+        Determines how many items of this type fit in one inventory slot.
+
+        @return u32 The stack limit.
+    */
+    pub fn stack_limit(&self) -> u32 {
+        match *self {
+            ItemType::Interactable(_) => 1,
+            ItemType::Food(_) => 10,
+            ItemType::Resource(_) => 20,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum FoodType {
     Bisket,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum InteractableType {
     Sword,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ResourceType {
     Logs,
     Grune,
+    Planks,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 
 /**
     Implementation of the Item object.
@@ -44,16 +111,20 @@ pub enum ResourceType {
     @field x_vel Velocity of item when being thrown.
     @field y_vel Velocity of item when being thrown.
     @field weight The item's weight.
+    @field collected Whether the player has had the item before, so picking it up again isn't a new find.
+    @field aboard Whether the item lies on the ship rather than in the world below.
 */
 pub struct Item {
     pub x: f64,
     pub y: f64,
     pub item_type: ItemType,
-    damage: i32, // Damage done when thrown
-    pickupable: bool,
-    x_vel: f64,
-    y_vel: f64,
-    weight: f64,
+    pub damage: i32, // Damage done when thrown
+    pub pickupable: bool,
+    pub x_vel: f64,
+    pub y_vel: f64,
+    pub weight: f64,
+    pub collected: bool,
+    pub aboard: bool,
 }
 
 impl Item {
@@ -76,6 +147,8 @@ impl Item {
             x_vel: 0.0,
             y_vel: 0.0,
             weight: w,
+            collected: false,
+            aboard: false,
         }
     }
 
@@ -96,23 +169,90 @@ impl Item {
         trans_x: f64,
         trans_y: f64,
     ) {
-        match self.item_type {
-            ItemType::Food(FoodType::Bisket) => {
-                let img = IMG_ITEM_BISKET;
-                image(
-                    textures.get(img).expect(&format!("Not found: {:?}", img)),
-                    context
-                        .transform
-                        .trans(self.x, self.y)
-                        .trans(trans_x, trans_y)
-                        .scale(IMAGE_SCALE, IMAGE_SCALE),
-                    graphics,
-                );
-            }
-            _ => {}
+        if let Some(img) = self.item_type.image() {
+            image(
+                textures.get(img).expect(&format!("Not found: {:?}", img)),
+                context
+                    .transform
+                    .trans(self.x, self.y)
+                    .trans(trans_x, trans_y)
+                    .scale(IMAGE_SCALE, IMAGE_SCALE),
+                graphics,
+            );
+        }
+    }
+
+    /*
+        Launches the item in a direction at throwing speed.
+
+        @param dir The direction to throw in.
+    */
+    pub fn throw(&mut self, dir: Direction) {
+        match dir {
+            Direction::N => self.throw_towards(0.0, -1.0),
+            Direction::S => self.throw_towards(0.0, 1.0),
+            Direction::W => self.throw_towards(-1.0, 0.0),
+            Direction::E => self.throw_towards(1.0, 0.0),
         }
     }
 
+    /*
+        Launches the item at throwing speed along any heading, e.g. towards the cursor.
+
+        @param dx How far the heading goes horizontally, of any length.
+        @param dy How far the heading goes vertically, of any length.
+    */
+    pub fn throw_towards(&mut self, dx: f64, dy: f64) {
+        let length = dx.hypot(dy);
+        if length == 0.0 {
+            return;
+        }
+        self.x_vel = dx / length * THROW_SPEED;
+        self.y_vel = dy / length * THROW_SPEED;
+    }
+
+    /*
+        Determines if the item is flying through the air.
+
+        @return bool Whether the item has any velocity.
+    */
+    pub fn is_moving(&self) -> bool {
+        self.x_vel != 0.0 || self.y_vel != 0.0
+    }
+
+    /*
+        Slows a moving item by friction, heavier items slowing faster.
+
+        @param dt The time step in seconds.
+    */
+    pub fn update_velocity(&mut self, dt: f64) {
+        let speed = self.x_vel.hypot(self.y_vel);
+        if speed == 0.0 {
+            return;
+        }
+        let slowed = (speed - ITEM_FRICTION * self.weight.sqrt() * dt).max(0.0);
+        self.x_vel *= slowed / speed;
+        self.y_vel *= slowed / speed;
+    }
+
+    /*
+        Calculates where the item will be after a time step.
+
+        @param dt The time step in seconds.
+        @return (f64, f64) The future x and y position.
+    */
+    pub fn to_be_location(&self, dt: f64) -> (f64, f64) {
+        (self.x + self.x_vel * dt, self.y + self.y_vel * dt)
+    }
+
+    /*
+        Stops the item where it is.
+    */
+    pub fn stop(&mut self) {
+        self.x_vel = 0.0;
+        self.y_vel = 0.0;
+    }
+
     /*
         Generates a clone of the item using prototype.
         @param x The x value where the clone is placed.
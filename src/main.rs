@@ -16,8 +16,22 @@ mod test;
 mod ship;
 mod misc;
 mod map;
+mod map_view;
 mod item;
+mod inventory;
+mod save;
 mod constants;
+mod world;
+mod timestep;
+mod blueprint;
+mod achievement;
+mod ai;
+mod crafting;
+mod crew;
+mod dialogue;
+mod event;
+mod input;
+mod quest;
 
 use piston_window::*;
 use game::Game;
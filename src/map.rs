@@ -1,89 +1,443 @@
 //! Map generates and manages the tileset for the map using Perlin and Worley generations.
+//! The map is open sky with floating islands, each with its own terrain.
+//! The sky never ends, so the map is split into chunks that are generated from the seed
+//! as the player approaches and dropped again once far away.
+//! Tiles changed since generation are kept apart from the chunks, by chunk, and applied
+//! again whenever their chunk is regenerated.
+//! Islands the player has found are remembered, even once their chunks are dropped,
+//! along with the markers the player places on the map.
 //! Draws the Map with proper tilesets based on generation.
 
 use noise::*;
 use rand::*;
+use rand::distributions::IndependentSample;
+use rand::distributions::normal::Normal;
 use tile::{Tile, TileType};
 use constants::*;
 use piston_window::*;
-use std::cmp;
 use std::collections::HashMap;
 
+pub const CHUNK_SIZE: usize = 128;
+pub const LOAD_RADIUS: i32 = 1;
+pub const EVICT_RADIUS: i32 = 2;
 const STEP_SIZE: f64 = 0.1;
 const BASE_WEIGHT: f64 = 0.2;
 const WATER_CHANGE_RATE: i32 = 10;
+const ISLAND_MARGIN: usize = 4;
+const ISLAND_CHANCE: f64 = 0.7;
+const ISLAND_EDGE: f64 = 0.25;
+const GRUNE_CHANCE: f64 = 0.08;
+const ISLAND_MEAN: f64 = 75.0;
+const ISLAND_STANDARD_DEV: f64 = 10.0;
+const ISLAND_LOWERBOUND: f64 = 10.0;
+const ISLAND_UPPERBOUND: f64 = 200.0;
 
 /*
     Implementation of the Map object.
 
-    @field tiles A 2D vector of all the tiles in the map.
+    @field seed The master seed every noise layer and texture roll is derived from.
+    @field chunks The loaded chunks, keyed by chunk coordinates.
+    @field changes The tiles changed since generation, keyed by chunk coordinates, then by
+    position within the chunk. Kept while the chunk is evicted.
     @field grass_dirt_map A HashMap used for drawing grass graphics.
     @field stone_map A HashMap used for drawing stone graphics.
     @field under_portal The tile under the portal.
+    @field discovered The islands the player has found, keyed by id.
+    @field markers The tiles the player has marked on the map, in the order placed.
+    @field regrowing The tiles with picked plants still growing back.
     @field frames_since_last_draw Used for water animation.
 */
 pub struct Map {
-    pub tiles: Vec<Vec<Tile>>,
+    pub seed: u64,
+    chunks: HashMap<(i32, i32), Chunk>,
+    changes: HashMap<(i32, i32), HashMap<(usize, usize), Tile>>,
     grass_dirt_map: HashMap<(bool, bool, bool, bool), (Option<String>, f64, f64, f64)>,
     stone_map: HashMap<(bool, bool, bool, bool), (Option<String>, f64, f64, f64)>,
     pub under_portal: Tile,
+    discovered: HashMap<u64, Island>,
+    markers: Vec<(i32, i32)>,
+    regrowing: Vec<(i32, i32)>,
     frames_since_last_draw: i32,
 }
 
+/*
+    Implementation of the Chunk object, a square of the map.
+
+    @field tiles A 2D vector of the chunk's tiles.
+    @field island The island floating in the chunk, if any.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chunk {
+    pub tiles: Vec<Vec<Tile>>,
+    pub island: Option<Island>,
+}
+
 impl Map {
     /*
-        Map constructor.
+        Map constructor using a random seed.
 
-        @param width The width of the map.
-        @param height The height of the map.
         @return Map Returns itself.
     */
-    pub fn new(width: usize, height: usize) -> Self {
-        // Easy reference to tile types.
-        let air = Tile::new(TileType::Air);
-        let grass_floor = Tile::new(TileType::GrassFloor);
-        let water = Tile::new(TileType::Water);
-        let dirt_floor = Tile::new(TileType::DirtFloor);
-        let stone_wall = Tile::new(TileType::StoneWall);
-        let tree = Tile::new(TileType::Tree);
-
-        let mut map_tiles = vec![vec![air.clone(); height]; width];
-        let worley_arr = generate_worley(width, height, STEP_SIZE / 2.0);
-        let perlin_arr = generate_perlin(width, height, STEP_SIZE);
-        let perlin_arr = add_base_weight(&perlin_arr, BASE_WEIGHT);
-        let moist = generate_perlin(width, height, STEP_SIZE);
-
-        // Create the 2D vector of TileTypes based on random generation above.
-        for i in 0..map_tiles.len() {
-            for j in 0..map_tiles[i].len() {
-                let num = worley_arr[i][j] * perlin_arr[i][j];
-                if num <= 0.1 {
-                    map_tiles[i][j] = water.clone();
-                } else if num <= 0.2 {
-                    map_tiles[i][j] = dirt_floor.clone();
-                } else if num <= 0.6 {
-                    if moist[i][j] >= 0.7 {
-                        map_tiles[i][j] = tree.clone();
-                    } else {
-                        map_tiles[i][j] = grass_floor.clone();
+    pub fn new() -> Self {
+        Map::with_seed(random())
+    }
+
+    /*
+        Map constructor using a given seed. The same seed always produces the same tiles.
+        No chunks are loaded until asked for.
+
+        @param seed The master seed for generation.
+        @return Map Returns itself.
+    */
+    pub fn with_seed(seed: u64) -> Self {
+        Map {
+            seed: seed,
+            chunks: HashMap::new(),
+            changes: HashMap::new(),
+            grass_dirt_map: populate_grass_dirt_map(),
+            stone_map: populate_stone_map(),
+            under_portal: Tile::new(TileType::Air),
+            discovered: HashMap::new(),
+            markers: vec![],
+            regrowing: vec![],
+            frames_since_last_draw: 0,
+        }
+    }
+
+    /*
+        Generates the chunks near a position and evicts the ones that have become distant.
+
+        @param x The x position, in tiles.
+        @param y The y position, in tiles.
+    */
+    pub fn load_around(&mut self, x: f64, y: f64) {
+        let (center_x, center_y) = chunk_key(x.floor() as i32, y.floor() as i32);
+        for chunk_x in center_x - LOAD_RADIUS..center_x + LOAD_RADIUS + 1 {
+            for chunk_y in center_y - LOAD_RADIUS..center_y + LOAD_RADIUS + 1 {
+                self.load_chunk(chunk_x, chunk_y);
+            }
+        }
+        self.chunks.retain(|&(chunk_x, chunk_y), _| {
+            (chunk_x - center_x).abs() <= EVICT_RADIUS && (chunk_y - center_y).abs() <= EVICT_RADIUS
+        });
+    }
+
+    /*
+        Generates a chunk from the seed, unless it's already loaded, and applies the
+        changes made to it.
+
+        @param chunk_x The chunk's horizontal index.
+        @param chunk_y The chunk's vertical index.
+    */
+    pub fn load_chunk(&mut self, chunk_x: i32, chunk_y: i32) {
+        let seed = self.seed;
+        let changes = &self.changes;
+        self.chunks.entry((chunk_x, chunk_y)).or_insert_with(|| {
+            let mut chunk = generate_chunk(seed, chunk_x, chunk_y);
+            if let Some(changed) = changes.get(&(chunk_x, chunk_y)) {
+                for (&(i, j), tile) in changed {
+                    chunk.tiles[i][j] = tile.clone();
+                }
+            }
+            chunk
+        });
+    }
+
+    /*
+        Remembers an island as found.
+
+        @param island The island.
+        @return bool Whether the island wasn't already known.
+    */
+    pub fn discover(&mut self, island: &Island) -> bool {
+        self.discovered.insert(island.id, island.clone()).is_none()
+    }
+
+    /*
+        Remembers the island in a chunk as found, e.g. from saved data. The island is
+        placed from the seed, so the chunk doesn't need to be loaded.
+
+        @param chunk_x The chunk's horizontal index.
+        @param chunk_y The chunk's vertical index.
+    */
+    pub fn discover_chunk(&mut self, chunk_x: i32, chunk_y: i32) {
+        if let Some(island) = place_island(self.seed, chunk_x, chunk_y) {
+            self.discover(&island);
+        }
+    }
+
+    /*
+        Finds the islands that have been found, loaded or not.
+
+        @return Vec<&Island> The islands, ordered by id.
+    */
+    pub fn discovered_islands(&self) -> Vec<&Island> {
+        let mut islands: Vec<&Island> = self.discovered.values().collect();
+        islands.sort_by_key(|island| island.id);
+        islands
+    }
+
+    /*
+        Marks a tile on the map.
+
+        @param x The x position of the tile.
+        @param y The y position of the tile.
+        @return bool Whether the tile wasn't already marked.
+    */
+    pub fn add_marker(&mut self, x: i32, y: i32) -> bool {
+        if self.markers.contains(&(x, y)) {
+            return false;
+        }
+        self.markers.push((x, y));
+        true
+    }
+
+    /*
+        Removes the marker closest to a position, if it's close enough.
+
+        @param x The x position, in tiles.
+        @param y The y position, in tiles.
+        @param reach How far away the marker may be, in tiles.
+        @return bool Whether a marker was removed.
+    */
+    pub fn remove_marker_near(&mut self, x: f64, y: f64, reach: f64) -> bool {
+        let distance = |&(marker_x, marker_y): &(i32, i32)| {
+            (marker_x as f64 + 0.5 - x).hypot(marker_y as f64 + 0.5 - y)
+        };
+        let mut closest: Option<usize> = None;
+        for (i, marker) in self.markers.iter().enumerate() {
+            match closest {
+                Some(c) if distance(&self.markers[c]) <= distance(marker) => {}
+                _ if distance(marker) <= reach => closest = Some(i),
+                _ => {}
+            }
+        }
+        match closest {
+            Some(i) => {
+                self.markers.remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /*
+        Lists the marked tiles.
+
+        @return &[(i32, i32)] The tiles' x and y positions, in the order placed.
+    */
+    pub fn markers(&self) -> &[(i32, i32)] {
+        &self.markers
+    }
+
+    /*
+        Summarizes the terrain in a grid of square cells, each showing its most common
+        tile type. Cells whose corner is in an unloaded chunk are left empty, so cells
+        should line up with chunks.
+
+        @param left The x position of the grid's left edge, in tiles.
+        @param top The y position of the grid's top edge, in tiles.
+        @param columns The number of cells across.
+        @param rows The number of cells down.
+        @param cell The width and height of each cell, in tiles.
+        @return Vec<Vec<Option<TileType>>> Each cell's tile type, indexed by column then row.
+    */
+    pub fn summarize(
+        &self,
+        left: i32,
+        top: i32,
+        columns: usize,
+        rows: usize,
+        cell: usize,
+    ) -> Vec<Vec<Option<TileType>>> {
+        let mut summary = vec![vec![None; rows]; columns];
+        for (column, cells) in summary.iter_mut().enumerate() {
+            for (row, summarized) in cells.iter_mut().enumerate() {
+                let cell_x = left + (column * cell) as i32;
+                let cell_y = top + (row * cell) as i32;
+                if !self.chunks.contains_key(&chunk_key(cell_x, cell_y)) {
+                    continue;
+                }
+                // Count each type, keeping the first found on ties.
+                let mut counts: Vec<(TileType, usize)> = vec![];
+                for x in cell_x..cell_x + cell as i32 {
+                    for y in cell_y..cell_y + cell as i32 {
+                        if let Some(tile) = self.tile(x, y) {
+                            match counts.iter().position(|count| count.0 == tile.tile_type) {
+                                Some(i) => counts[i].1 += 1,
+                                None => counts.push((tile.tile_type.clone(), 1)),
+                            }
+                        }
+                    }
+                }
+                let mut most: Option<(TileType, usize)> = None;
+                for (tile_type, count) in counts {
+                    match most {
+                        Some((_, most_count)) if most_count >= count => {}
+                        _ => most = Some((tile_type, count)),
                     }
-                } else {
-                    map_tiles[i][j] = stone_wall.clone();
                 }
+                *summarized = most.map(|(tile_type, _)| tile_type);
+            }
+        }
+        summary
+    }
+
+    /*
+        Finds the tiles changed since generation, loaded or not, in a fixed order.
+
+        @return Vec<((i32, i32), &Tile)> The tiles' positions, with the tiles.
+    */
+    pub fn changed_tiles(&self) -> Vec<((i32, i32), &Tile)> {
+        let mut changed = vec![];
+        for (&(chunk_x, chunk_y), tiles) in &self.changes {
+            for (&(i, j), tile) in tiles {
+                let x = chunk_x * CHUNK_SIZE as i32 + i as i32;
+                let y = chunk_y * CHUNK_SIZE as i32 + j as i32;
+                changed.push(((x, y), tile));
+            }
+        }
+        changed.sort_by_key(|&(position, _)| position);
+        changed
+    }
+
+    /*
+        Replaces a chunk's tiles, e.g. from saved data. Only the tiles that differ from
+        generation are kept as changes.
 
-                if random::<f64>() < 0.10 {
-                    map_tiles[i][j].texture = true;
+        @param chunk_x The chunk's horizontal index.
+        @param chunk_y The chunk's vertical index.
+        @param tiles The chunk's tiles.
+    */
+    pub fn insert_chunk(&mut self, chunk_x: i32, chunk_y: i32, tiles: Vec<Vec<Tile>>) {
+        let generated = generate_chunk(self.seed, chunk_x, chunk_y);
+        for (i, column) in tiles.into_iter().enumerate() {
+            for (j, tile) in column.into_iter().enumerate() {
+                if tile != generated.tiles[i][j] {
+                    let x = chunk_x * CHUNK_SIZE as i32 + i as i32;
+                    let y = chunk_y * CHUNK_SIZE as i32 + j as i32;
+                    self.set_tile(x, y, tile);
                 }
             }
         }
+    }
 
-        Map {
-            tiles: map_tiles,
-            grass_dirt_map: populate_grass_dirt_map(),
-            stone_map: populate_stone_map(),
-            under_portal: air,
-            frames_since_last_draw: 0,
+    /*
+        Grows picked plants back over time, even in evicted chunks. Only the tiles still
+        growing are stepped.
+
+        @param dt The time step in seconds.
+    */
+    pub fn update(&mut self, dt: f64) {
+        let chunks = &mut self.chunks;
+        let changes = &mut self.changes;
+        self.regrowing.retain(|&(x, y)| {
+            let key = chunk_key(x, y);
+            let (i, j) = (local_index(x), local_index(y));
+            let tile = match changes.get_mut(&key).and_then(|changed| changed.get_mut(&(i, j))) {
+                Some(tile) => tile,
+                None => return false,
+            };
+            tile.regrow = (tile.regrow - dt).max(0.0);
+            if let Some(chunk) = chunks.get_mut(&key) {
+                chunk.tiles[i][j].regrow = tile.regrow;
+            }
+            tile.regrow > 0.0
+        });
+    }
+
+    /*
+        Finds a tile by its position in the world.
+
+        @param x The x position of the tile.
+        @param y The y position of the tile.
+        @return Option<&Tile> The tile, if its chunk is loaded.
+    */
+    pub fn tile(&self, x: i32, y: i32) -> Option<&Tile> {
+        let (chunk_x, chunk_y) = chunk_key(x, y);
+        self.chunks
+            .get(&(chunk_x, chunk_y))
+            .map(|chunk| &chunk.tiles[local_index(x)][local_index(y)])
+    }
+
+    /*
+        Changes a tile, generating its chunk if needed. The change is kept once the chunk
+        is evicted, and applied again when it's regenerated.
+
+        @param x The x position of the tile.
+        @param y The y position of the tile.
+        @param tile The new tile.
+    */
+    pub fn set_tile(&mut self, x: i32, y: i32, tile: Tile) {
+        let (chunk_x, chunk_y) = chunk_key(x, y);
+        self.load_chunk(chunk_x, chunk_y);
+        if tile.regrow > 0.0 && !self.regrowing.contains(&(x, y)) {
+            self.regrowing.push((x, y));
+        }
+        let (i, j) = (local_index(x), local_index(y));
+        if let Some(chunk) = self.chunks.get_mut(&(chunk_x, chunk_y)) {
+            chunk.tiles[i][j] = tile.clone();
+        }
+        self.changes
+            .entry((chunk_x, chunk_y))
+            .or_default()
+            .insert((i, j), tile);
+    }
+
+    /*
+        Finds the islands in loaded chunks.
+
+        @return Vec<&Island> The islands, ordered by id.
+    */
+    pub fn islands(&self) -> Vec<&Island> {
+        let mut islands: Vec<&Island> = self.chunks
+            .values()
+            .filter_map(|chunk| chunk.island.as_ref())
+            .collect();
+        islands.sort_by_key(|island| island.id);
+        islands
+    }
+
+    /*
+        Finds the island covering a tile.
+
+        @param x The x position of the tile.
+        @param y The y position of the tile.
+        @return Option<&Island> The island, if the tile is within one's bounds.
+    */
+    pub fn island_at(&self, x: i32, y: i32) -> Option<&Island> {
+        match self.chunks.get(&chunk_key(x, y)) {
+            Some(&Chunk {
+                island: Some(ref island),
+                ..
+            }) if island.contains(x, y) => Some(island),
+            _ => None,
+        }
+    }
+
+    /*
+        Finds the loaded island whose centre is closest to a position.
+
+        @param x The x position, in tiles.
+        @param y The y position, in tiles.
+        @return Option<&Island> The closest island, if there are any.
+    */
+    pub fn nearest_island(&self, x: f64, y: f64) -> Option<&Island> {
+        let distance = |island: &Island| {
+            let (center_x, center_y) = island.center();
+            (center_x - x).powi(2) + (center_y - y).powi(2)
+        };
+        let mut nearest: Option<&Island> = None;
+        for chunk in self.chunks.values() {
+            if let Some(ref island) = chunk.island {
+                match nearest {
+                    Some(n) if distance(n) < distance(island)
+                        || (distance(n) == distance(island) && n.id < island.id) => {}
+                    _ => nearest = Some(island),
+                }
+            }
         }
+        nearest
     }
 
     /*
@@ -113,23 +467,16 @@ impl Map {
     ) {
         let draw_start_i = ((player_x - w_width / 2.0) - IMAGE_SIZE_SCALED) / IMAGE_SIZE_SCALED;
         let draw_start_j = ((player_y - w_height / 2.0) - IMAGE_SIZE_SCALED) / IMAGE_SIZE_SCALED;
-        let draw_start_i = cmp::max(0, draw_start_i as i32) as usize;
-        let draw_start_j = cmp::max(0, draw_start_j as i32) as usize;
+        let draw_end_i = (player_x + w_width / 2.0) / IMAGE_SIZE_SCALED;
+        let draw_end_j = (player_y + w_height / 2.0) / IMAGE_SIZE_SCALED;
 
-        for i in draw_start_i..self.tiles.len() {
-            if i as f64 * IMAGE_SIZE_SCALED > player_x + w_width / 2.0 {
-                // Off screen.
-                break;
-            }
-            for j in draw_start_j..self.tiles[i].len() {
-                if j as f64 * IMAGE_SIZE_SCALED > player_y + w_height / 2.0 {
-                    // Off screen.
-                    break;
-                }
+        // Only tiles on screen are drawn.
+        for i in draw_start_i.floor() as i32..draw_end_i.floor() as i32 + 1 {
+            for j in draw_start_j.floor() as i32..draw_end_j.floor() as i32 + 1 {
                 // Retrieve set of information on what tile to draw, and its rotation/translation.
                 if let (Some(img), rot, shift_x, shift_y) = self.what_to_draw(i, j) {
-                    if img == IMG_TREE {
-                        // Special handling to draw grass under trees.
+                    if img == IMG_TREE || img == IMG_GRUNE {
+                        // Special handling to draw grass under trees and plants.
                         if let (Some(grass_img), grass_rot, grass_shift_x, grass_shift_y) =
                             self.get_grass_tile_info(i, j)
                         {
@@ -187,6 +534,21 @@ impl Map {
         }
     }
 
+    /*
+        Determines whether the tile at a position is of some type.
+
+        @param x The x position of the tile.
+        @param y The y position of the tile.
+        @param tile_type The type to look for.
+        @return bool Whether the tile is loaded and of that type.
+    */
+    fn is_tile_type(&self, x: i32, y: i32, tile_type: TileType) -> bool {
+        match self.tile(x, y) {
+            Some(tile) => tile.tile_type == tile_type,
+            None => false,
+        }
+    }
+
     /*
         Determines what tile / sprite to draw at a given x,y.
 
@@ -194,19 +556,28 @@ impl Map {
         @param y The y location.
         @return A tuple containing the image string for the textures map, the rotation degree, x translation, and y translation.
     */
-    fn what_to_draw(&mut self, x: usize, y: usize) -> (Option<String>, f64, f64, f64) {
+    fn what_to_draw(&mut self, x: i32, y: i32) -> (Option<String>, f64, f64, f64) {
         let img;
         let rot = 0.0;
         let shift_x = 0.0;
         let shift_y = 0.0;
-        match self.tiles[x][y].tile_type {
+        let tile = match self.tile(x, y) {
+            Some(tile) => tile.clone(),
+            None => return (None, rot, shift_x, shift_y),
+        };
+        match tile.tile_type {
             TileType::Water => {
+                let mut texture = tile.texture;
                 if self.frames_since_last_draw > WATER_CHANGE_RATE {
-                    self.tiles[x][y].texture = random();
+                    texture = random();
+                    let (chunk_x, chunk_y) = chunk_key(x, y);
+                    if let Some(chunk) = self.chunks.get_mut(&(chunk_x, chunk_y)) {
+                        chunk.tiles[local_index(x)][local_index(y)].texture = texture;
+                    }
                     self.frames_since_last_draw = 0;
                 }
                 self.frames_since_last_draw += 1;
-                match self.tiles[x][y].texture {
+                match texture {
                     false => img = Some(IMG_WATER.to_string()),
                     true => img = Some(IMG_WATER_TEXTURE.to_string()),
                 }
@@ -215,31 +586,10 @@ impl Map {
             // TODO Separate into its own function.
             // Determines the correct stone tile from the stone HashMap.
             TileType::StoneWall => {
-                let mut up = false;
-                let mut left = false;
-                let mut right = false;
-                let mut down = false;
-
-                if x > 0 {
-                    if self.tiles[x - 1][y].tile_type == TileType::StoneWall {
-                        left = true;
-                    }
-                }
-                if x < self.tiles.len() - 1 {
-                    if self.tiles[x + 1][y].tile_type == TileType::StoneWall {
-                        right = true;
-                    }
-                }
-                if y > 0 {
-                    if self.tiles[x][y - 1].tile_type == TileType::StoneWall {
-                        up = true;
-                    }
-                }
-                if y < self.tiles[x].len() - 1 {
-                    if self.tiles[x][y + 1].tile_type == TileType::StoneWall {
-                        down = true;
-                    }
-                }
+                let left = self.is_tile_type(x - 1, y, TileType::StoneWall);
+                let right = self.is_tile_type(x + 1, y, TileType::StoneWall);
+                let up = self.is_tile_type(x, y - 1, TileType::StoneWall);
+                let down = self.is_tile_type(x, y + 1, TileType::StoneWall);
 
                 // Retrieve from map.
                 let key = (right, down, left, up);
@@ -251,7 +601,7 @@ impl Map {
                             .clone();
                         return temp;
                     } else {
-                        match self.tiles[x][y].texture {
+                        match tile.texture {
                             false => img = Some(IMG_STONE_WALL.to_string()),
                             true => img = Some(IMG_STONE_WALL_TEXTURE.to_string()),
                         }
@@ -266,13 +616,20 @@ impl Map {
                 return self.get_grass_tile_info(x, y);
             }
 
-            TileType::DirtFloor => match self.tiles[x][y].texture {
+            TileType::DirtFloor => match tile.texture {
                 false => img = Some(IMG_DIRT_FLOOR.to_string()),
                 true => img = Some(IMG_DIRT_FLOOR_TEXTURE.to_string()),
             },
             TileType::Tree => {
                 img = Some(IMG_TREE.to_string());
             }
+            TileType::Grune => {
+                // Picked plants look like grass until they grow back.
+                if !tile.is_ripe() {
+                    return self.get_grass_tile_info(x, y);
+                }
+                img = Some(IMG_GRUNE.to_string());
+            }
             TileType::Portal => {
                 img = Some(IMG_PORTAL.to_string());
             }
@@ -290,32 +647,11 @@ impl Map {
         @param y The y position of the tile.
         @return A tuple containing the image string for the textures map, the rotation degree, x translation, and y translation.
     */
-    fn get_grass_tile_info(&self, x: usize, y: usize) -> (Option<String>, f64, f64, f64) {
-        let mut up = false;
-        let mut left = false;
-        let mut right = false;
-        let mut down = false;
-
-        if x > 0 {
-            if self.tiles[x - 1][y].tile_type == TileType::DirtFloor {
-                left = true;
-            }
-        }
-        if x < self.tiles.len() - 1 {
-            if self.tiles[x + 1][y].tile_type == TileType::DirtFloor {
-                right = true;
-            }
-        }
-        if y > 0 {
-            if self.tiles[x][y - 1].tile_type == TileType::DirtFloor {
-                up = true;
-            }
-        }
-        if y < self.tiles[x].len() - 1 {
-            if self.tiles[x][y + 1].tile_type == TileType::DirtFloor {
-                down = true;
-            }
-        }
+    fn get_grass_tile_info(&self, x: i32, y: i32) -> (Option<String>, f64, f64, f64) {
+        let left = self.is_tile_type(x - 1, y, TileType::DirtFloor);
+        let right = self.is_tile_type(x + 1, y, TileType::DirtFloor);
+        let up = self.is_tile_type(x, y - 1, TileType::DirtFloor);
+        let down = self.is_tile_type(x, y + 1, TileType::DirtFloor);
 
         // Retrieve from HashMap.
         let key = (right, down, left, up);
@@ -328,7 +664,11 @@ impl Map {
                     .clone();
                 return grass_tile;
             } else {
-                match self.tiles[x][y].texture {
+                let textured = match self.tile(x, y) {
+                    Some(tile) => tile.texture,
+                    None => false,
+                };
+                match textured {
                     false => img = Some(IMG_GRASS_FLOOR.to_string()),
                     true => img = Some(IMG_GRASS_FLOOR_TEXTURE.to_string()),
                 }
@@ -341,17 +681,90 @@ impl Map {
     }
 }
 
+/*
+    Determines which chunk a tile is in.
+
+    @param x The x position of the tile.
+    @param y The y position of the tile.
+    @return (i32, i32) The chunk coordinates.
+*/
+pub fn chunk_key(x: i32, y: i32) -> (i32, i32) {
+    (
+        x.div_euclid(CHUNK_SIZE as i32),
+        y.div_euclid(CHUNK_SIZE as i32),
+    )
+}
+
+/*
+    Determines a tile's index within its chunk.
+
+    @param n The x or y position of the tile.
+    @return usize The index along that axis.
+*/
+fn local_index(n: i32) -> usize {
+    n.rem_euclid(CHUNK_SIZE as i32) as usize
+}
+
+/*
+    Generates a chunk of sky from the seed, with its island stamped in.
+
+    @param seed The master seed.
+    @param chunk_x The chunk's horizontal index.
+    @param chunk_y The chunk's vertical index.
+    @return Chunk The generated chunk.
+*/
+fn generate_chunk(seed: u64, chunk_x: i32, chunk_y: i32) -> Chunk {
+    let mut tiles = vec![vec![Tile::new(TileType::Air); CHUNK_SIZE]; CHUNK_SIZE];
+    let island = place_island(seed, chunk_x, chunk_y);
+    if let Some(ref island) = island {
+        let island_tiles = generate_island_tiles(island);
+        let offset_x = local_index(island.x);
+        let offset_y = local_index(island.y);
+        for i in 0..island.width {
+            for j in 0..island.height {
+                if island_tiles[i][j].tile_type != TileType::Air {
+                    tiles[offset_x + i][offset_y + j] = island_tiles[i][j].clone();
+                }
+            }
+        }
+    }
+    Chunk {
+        tiles: tiles,
+        island: island,
+    }
+}
+
+/*
+    Creates the random number generator for one chunk of the map.
+    Every chunk gets its own generator, so chunks can be generated in any order.
+
+    @param seed The master seed.
+    @param chunk_x The chunk's horizontal index.
+    @param chunk_y The chunk's vertical index.
+    @return StdRng A generator that is deterministic for the seed and chunk.
+*/
+fn chunk_rng(seed: u64, chunk_x: i32, chunk_y: i32) -> StdRng {
+    StdRng::from_seed(
+        &[
+            seed as usize,
+            (seed >> 32) as usize,
+            chunk_x as u32 as usize,
+            chunk_y as u32 as usize,
+        ][..],
+    )
+}
+
 /*
     Generates perlin noise to be used in procedural map gen.
 
     @param width The width of the map.
     @param height The height of the map.
     @param step Step value of the noise.
+    @param seed Seed of the noise layer.
     @return Vec<Vec<f64>> Used for generating map.
 */
-fn generate_perlin(width: usize, height: usize, step: f64) -> Vec<Vec<f64>> {
-    let mut rng = thread_rng();
-    let noise = Perlin::new().set_seed(rng.gen::<u32>());
+fn generate_perlin(width: usize, height: usize, step: f64, seed: u32) -> Vec<Vec<f64>> {
+    let noise = Perlin::new().set_seed(seed);
     let mut xpos = 0.0;
     let mut ypos = 0.0;
     let mut arr = vec![vec![0.0; height]; width];
@@ -390,11 +803,11 @@ fn add_base_weight(arr: &Vec<Vec<f64>>, base: f64) -> Vec<Vec<f64>> {
     @param width The width of the map.
     @param height The height of the map.
     @param step Step value of the noise.
+    @param seed Seed of the noise layer.
     @return Vec<Vec<f64>> Used for generating map.
 */
-fn generate_worley(width: usize, height: usize, step: f64) -> Vec<Vec<f64>> {
-    let mut rng = thread_rng();
-    let noise = Worley::new().set_seed(rng.gen::<u32>()).enable_range(true);
+fn generate_worley(width: usize, height: usize, step: f64, seed: u32) -> Vec<Vec<f64>> {
+    let noise = Worley::new().set_seed(seed).enable_range(true);
     let mut xpos = 0.0;
     let mut ypos = 0.0;
     let mut arr = vec![vec![0.0; height]; width];
@@ -650,56 +1063,206 @@ fn populate_stone_map() -> HashMap<(bool, bool, bool, bool), (Option<String>, f6
 
 fn what_to_draw_tile(tile_type: &TileType) -> Option<String> {
     match *tile_type {
-        TileType::GrassFloor | TileType::Tree => Some(IMG_GRASS_FLOOR.to_string()),
+        TileType::GrassFloor | TileType::Tree | TileType::Grune => {
+            Some(IMG_GRASS_FLOOR.to_string())
+        }
         TileType::DirtFloor => Some(IMG_DIRT_FLOOR.to_string()),
         _ => None,
     }
 }
-// struct Island {
-//     pub tiles: Vec<Vec<Tile>>,
-//     pub x: f64,
-//     pub y: f64,
-// }
-
-// impl Island {
-//     fn new() -> Self {
-//         Island {
-//             tiles: vec![vec![]],
-//             x: 0.0,
-//             y: 0.0
-//         }
-//     }
-// }
-
-// fn generate_weighted_circle(size: usize) -> Vec<Vec<f64>> {
-//     let mut circle_arr = vec![vec![0.0; size]; size];
-//     let sizef = size as f64;
-//     let middle: f64 = (sizef - 1) / 2.0;
-//     for i in 0..size {
-//         for j in 0..size {
-//             let x: f64 = middle - i as f64;
-//             let x = x * x;
-//             let y: f64 = middle - j as f64;
-//             let y = y * y;
-//             let val = (middle - (x + y).sqrt()) / middle;
-//             let val = val * 1.5;
-//             let val = val.max(0.0);
-//             let val = val.min(1.0);
-//             circle_arr[i][j] = val;
-//         }
-//     }
-//     circle_arr
-// }
-// const ISLAND_MEAN: f64 = 75.0;
-// const ISLAND_STANDERD_DEV: f64 = 10.0;
-// const ISLAND_LOWERBOUND: f64 = 10.0;
-// const ISLAND_UPPERBOUND: f64 = 200.0;
-// fn generate_island_size() -> usize {
-//     let mut normal = distributions::normal::Normal::new(ISLAND_MEAN, ISLAND_STANDERD_DEV);
-//     let mut island_size = normal.sample(&mut thread_rng());
-//     while island_size <= ISLAND_LOWERBOUND && island_size >= ISLAND_UPPERBOUND {
-//         island_size = normal.sample(&mut thread_rng());
-//     }
-//     let island_size = island_size as usize;
-//     island_size
-// }
+/*
+    Implementation of the Island object.
+
+    @field id Unique id of the island, derived from its chunk.
+    @field x The x position of the island's left edge, in tiles.
+    @field y The y position of the island's top edge, in tiles.
+    @field width The island's width, in tiles.
+    @field height The island's height, in tiles.
+    @field seed Seed for the island's own terrain noise.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Island {
+    pub id: u64,
+    pub x: i32,
+    pub y: i32,
+    pub width: usize,
+    pub height: usize,
+    seed: u64,
+}
+
+impl Island {
+    /*
+        Determines whether a tile is within the island's bounds.
+
+        @param x The x position of the tile.
+        @param y The y position of the tile.
+        @return bool Whether the tile is within the island.
+    */
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x
+            && x < self.x + self.width as i32
+            && y >= self.y
+            && y < self.y + self.height as i32
+    }
+
+    /*
+        Determines the chunk the island floats in.
+
+        @return (i32, i32) The chunk's horizontal and vertical index.
+    */
+    pub fn chunk(&self) -> (i32, i32) {
+        chunk_key(self.x, self.y)
+    }
+
+    /*
+        Determines the centre of the island.
+
+        @return (f64, f64) The x and y position of the centre, in tiles.
+    */
+    pub fn center(&self) -> (f64, f64) {
+        (
+            self.x as f64 + self.width as f64 / 2.0,
+            self.y as f64 + self.height as f64 / 2.0,
+        )
+    }
+}
+
+/*
+    Decides whether an island floats in a chunk, and where. Each chunk holds
+    at most one island, surrounded by a border of air.
+
+    @param seed The master seed.
+    @param chunk_x The chunk's horizontal index.
+    @param chunk_y The chunk's vertical index.
+    @return Option<Island> The island, without its terrain.
+*/
+fn place_island(seed: u64, chunk_x: i32, chunk_y: i32) -> Option<Island> {
+    let mut rng = chunk_rng(seed, chunk_x, chunk_y);
+    if rng.gen::<f64>() >= ISLAND_CHANCE {
+        return None;
+    }
+    let size = generate_island_size(&mut rng);
+    let free_space = CHUNK_SIZE - 2 * ISLAND_MARGIN - size;
+    let offset_x = ISLAND_MARGIN + rng.gen_range(0, free_space + 1);
+    let offset_y = ISLAND_MARGIN + rng.gen_range(0, free_space + 1);
+    Some(Island {
+        id: island_id(chunk_x, chunk_y),
+        x: chunk_x * CHUNK_SIZE as i32 + offset_x as i32,
+        y: chunk_y * CHUNK_SIZE as i32 + offset_y as i32,
+        width: size,
+        height: size,
+        seed: rng.gen(),
+    })
+}
+
+/*
+    Determines the id of the island in a chunk. Chunks near the origin get small ids.
+
+    @param chunk_x The chunk's horizontal index.
+    @param chunk_y The chunk's vertical index.
+    @return u64 The island's id.
+*/
+fn island_id(chunk_x: i32, chunk_y: i32) -> u64 {
+    // Map negative indices to odd numbers, then pair the two numbers up.
+    let zigzag = |n: i32| ((n << 1) ^ (n >> 31)) as u32 as u64;
+    let a = zigzag(chunk_x);
+    let b = zigzag(chunk_y);
+    (a + b) * (a + b + 1) / 2 + b
+}
+
+/*
+    Generates the terrain of an island from its own noise layers.
+    Tiles fade to air towards the island's edge.
+
+    @param island The island.
+    @return Vec<Vec<Tile>> The island's tiles, relative to its top left corner.
+*/
+fn generate_island_tiles(island: &Island) -> Vec<Vec<Tile>> {
+    let mut rng = StdRng::from_seed(&[island.seed as usize, (island.seed >> 32) as usize][..]);
+
+    // Easy reference to tile types.
+    let air = Tile::new(TileType::Air);
+    let grass_floor = Tile::new(TileType::GrassFloor);
+    let water = Tile::new(TileType::Water);
+    let dirt_floor = Tile::new(TileType::DirtFloor);
+    let stone_wall = Tile::new(TileType::StoneWall);
+    let tree = Tile::new(TileType::Tree);
+    let grune = Tile::new(TileType::Grune);
+
+    let width = island.width;
+    let height = island.height;
+    let mut island_tiles = vec![vec![air.clone(); height]; width];
+    let circle = generate_weighted_circle(width);
+    let shore = generate_perlin(width, height, STEP_SIZE, rng.gen());
+    let worley_arr = generate_worley(width, height, STEP_SIZE / 2.0, rng.gen());
+    let perlin_arr = generate_perlin(width, height, STEP_SIZE, rng.gen());
+    let perlin_arr = add_base_weight(&perlin_arr, BASE_WEIGHT);
+    let moist = generate_perlin(width, height, STEP_SIZE, rng.gen());
+
+    // Create the 2D vector of TileTypes based on random generation above.
+    for i in 0..width {
+        for j in 0..height {
+            // Ragged coastline around a weighted circle.
+            if circle[i][j] * (shore[i][j] + 0.5) < ISLAND_EDGE {
+                continue;
+            }
+            let num = worley_arr[i][j] * perlin_arr[i][j];
+            if num <= 0.1 {
+                island_tiles[i][j] = water.clone();
+            } else if num <= 0.2 {
+                island_tiles[i][j] = dirt_floor.clone();
+            } else if num <= 0.6 {
+                if moist[i][j] >= 0.7 {
+                    island_tiles[i][j] = tree.clone();
+                } else if moist[i][j] >= 0.55 && rng.gen::<f64>() < GRUNE_CHANCE {
+                    island_tiles[i][j] = grune.clone();
+                } else {
+                    island_tiles[i][j] = grass_floor.clone();
+                }
+            } else {
+                island_tiles[i][j] = stone_wall.clone();
+            }
+
+            if rng.gen::<f64>() < 0.10 {
+                island_tiles[i][j].texture = true;
+            }
+        }
+    }
+    island_tiles
+}
+
+/*
+    Generates a square of weights that are highest in the middle and fall to 0 at the edge.
+
+    @param size The width and height of the square.
+    @return Vec<Vec<f64>> The weights, from 0 to 1.
+*/
+fn generate_weighted_circle(size: usize) -> Vec<Vec<f64>> {
+    let mut circle_arr = vec![vec![0.0; size]; size];
+    let middle: f64 = (size as f64 - 1.0) / 2.0;
+    for (i, column) in circle_arr.iter_mut().enumerate() {
+        for (j, weight) in column.iter_mut().enumerate() {
+            let x = middle - i as f64;
+            let y = middle - j as f64;
+            let val = (middle - (x * x + y * y).sqrt()) / middle;
+            *weight = (val * 1.5).clamp(0.0, 1.0);
+        }
+    }
+    circle_arr
+}
+
+/*
+    Generates a normally distributed island size that fits in a chunk.
+
+    @param rng The chunk's random number generator.
+    @return usize The width and height of the island.
+*/
+fn generate_island_size(rng: &mut StdRng) -> usize {
+    let normal = Normal::new(ISLAND_MEAN, ISLAND_STANDARD_DEV);
+    let upper_bound = ISLAND_UPPERBOUND.min((CHUNK_SIZE - 2 * ISLAND_MARGIN) as f64);
+    let mut island_size = normal.ind_sample(rng);
+    while island_size < ISLAND_LOWERBOUND || island_size > upper_bound {
+        island_size = normal.ind_sample(rng);
+    }
+    island_size as usize
+}
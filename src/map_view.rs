@@ -0,0 +1,469 @@
+//! The map view shows the world from above, summarized by terrain colour.
+//! A minimap around the ship is drawn over the game, and the world map screen
+//! can be panned and zoomed to find discovered islands and the player's markers.
+//! Positions here are in tiles, rather than the pixels the world is drawn with.
+
+use piston_window::*;
+use constants::*;
+use misc::Direction;
+use world::{PlayerLocation, World};
+
+pub const ZOOM_LEVELS: [f64; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+const DEFAULT_ZOOM: usize = 2;
+const PAN_STEP: f64 = 0.25;
+const CELL_PIXELS: f64 = 4.0;
+const MARKER_REACH_PIXELS: f64 = 12.0;
+const MINIMAP_CELLS: usize = 32;
+const MINIMAP_CELL: usize = 4;
+pub const MINIMAP_SIZE: f64 = MINIMAP_CELLS as f64 * CELL_PIXELS;
+
+const BACKGROUND_COLOR: [f32; 4] = [0.05, 0.05, 0.1, 0.8];
+const SHIP_COLOR: [f32; 4] = [1.0, 0.5, 0.1, 1.0];
+const PLAYER_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const MARKER_COLOR: [f32; 4] = [1.0, 0.3, 0.3, 1.0];
+const ISLAND_COLOR: [f32; 4] = [0.6, 1.0, 0.6, 1.0];
+const QUEST_COLOR: [f32; 4] = [1.0, 0.9, 0.5, 1.0];
+const WAYPOINT_COLOR: [f32; 4] = [0.4, 0.9, 1.0, 1.0];
+
+/*
+    Implementation of the MapView object, the part of the world shown on the world map.
+
+    @field center_x The x position in the middle of the window, in tiles.
+    @field center_y The y position in the middle of the window, in tiles.
+    @field zoom The index of the zoom level in ZOOM_LEVELS.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapView {
+    pub center_x: f64,
+    pub center_y: f64,
+    pub zoom: usize,
+}
+
+impl MapView {
+    /*
+        MapView constructor, centred on the origin at the default zoom.
+
+        @return MapView Returns itself.
+    */
+    pub fn new() -> Self {
+        MapView {
+            center_x: 0.0,
+            center_y: 0.0,
+            zoom: DEFAULT_ZOOM,
+        }
+    }
+
+    /*
+        Moves the view so a position is in the middle of the window.
+
+        @param x The x position, in tiles.
+        @param y The y position, in tiles.
+    */
+    pub fn center_on(&mut self, x: f64, y: f64) {
+        self.center_x = x;
+        self.center_y = y;
+    }
+
+    /*
+        Determines how large a tile is drawn at the current zoom.
+
+        @return f64 The width of a tile, in pixels.
+    */
+    pub fn scale(&self) -> f64 {
+        ZOOM_LEVELS[self.zoom]
+    }
+
+    /*
+        Zooms in a level, keeping the middle of the window in place.
+
+        @return bool Whether the view wasn't already zoomed in all the way.
+    */
+    pub fn zoom_in(&mut self) -> bool {
+        if self.zoom + 1 < ZOOM_LEVELS.len() {
+            self.zoom += 1;
+            return true;
+        }
+        false
+    }
+
+    /*
+        Zooms out a level, keeping the middle of the window in place.
+
+        @return bool Whether the view wasn't already zoomed out all the way.
+    */
+    pub fn zoom_out(&mut self) -> bool {
+        if self.zoom > 0 {
+            self.zoom -= 1;
+            return true;
+        }
+        false
+    }
+
+    /*
+        Moves the view a quarter of the window in a direction.
+
+        @param dir The direction.
+        @param width The window's width.
+        @param height The window's height.
+    */
+    pub fn pan(&mut self, dir: Direction, width: f64, height: f64) {
+        let step_x = PAN_STEP * width / self.scale();
+        let step_y = PAN_STEP * height / self.scale();
+        match dir {
+            Direction::N => self.center_y -= step_y,
+            Direction::S => self.center_y += step_y,
+            Direction::E => self.center_x += step_x,
+            Direction::W => self.center_x -= step_x,
+        }
+    }
+
+    /*
+        Turns a position in the world into a point in the window.
+
+        @param x The x position, in tiles.
+        @param y The y position, in tiles.
+        @param width The window's width.
+        @param height The window's height.
+        @return (f64, f64) The point's x and y in the window.
+    */
+    pub fn to_screen(&self, x: f64, y: f64, width: f64, height: f64) -> (f64, f64) {
+        (
+            width / 2.0 + (x - self.center_x) * self.scale(),
+            height / 2.0 + (y - self.center_y) * self.scale(),
+        )
+    }
+
+    /*
+        Turns a point in the window into a position in the world.
+
+        @param screen_x The point's x in the window.
+        @param screen_y The point's y in the window.
+        @param width The window's width.
+        @param height The window's height.
+        @return (f64, f64) The x and y position, in tiles.
+    */
+    pub fn to_tiles(&self, screen_x: f64, screen_y: f64, width: f64, height: f64) -> (f64, f64) {
+        (
+            self.center_x + (screen_x - width / 2.0) / self.scale(),
+            self.center_y + (screen_y - height / 2.0) / self.scale(),
+        )
+    }
+
+    /*
+        Determines how many tiles wide each cell of terrain is drawn. Cells are a power of
+        two wide, so they line up with chunks, and never smaller than a few pixels.
+
+        @return usize The width of a cell, in tiles.
+    */
+    pub fn cell_size(&self) -> usize {
+        let mut cell = 1;
+        while (cell as f64) * self.scale() < CELL_PIXELS {
+            cell *= 2;
+        }
+        cell
+    }
+
+    /*
+        Determines how close to a marker a click or the crosshair has to be to remove it.
+
+        @return f64 The distance, in tiles.
+    */
+    pub fn marker_reach(&self) -> f64 {
+        MARKER_REACH_PIXELS / self.scale()
+    }
+
+    /*
+        Draws the world map over the whole window: the loaded terrain, discovered islands,
+        the ship, the player and the markers, with a crosshair in the middle.
+
+        @param world The game world.
+        @param glyphs Glyphs library for labelling islands.
+        @param context The drawing context for Piston.
+        @param graphics Graphics engine.
+        @param width The window's width.
+        @param height The window's height.
+    */
+    pub fn draw(
+        &self,
+        world: &World,
+        glyphs: &mut Glyphs,
+        context: &Context,
+        graphics: &mut G2d,
+        width: f64,
+        height: f64,
+    ) {
+        let scale = self.scale();
+        let transform = context.transform;
+
+        // Summarize the terrain, starting from a cell that lines up with the grid.
+        let cell = self.cell_size();
+        let (view_left, view_top) = self.to_tiles(0.0, 0.0, width, height);
+        let left = (view_left.floor() as i32).div_euclid(cell as i32) * cell as i32;
+        let top = (view_top.floor() as i32).div_euclid(cell as i32) * cell as i32;
+        let columns = (width / scale / cell as f64).ceil() as usize + 2;
+        let rows = (height / scale / cell as f64).ceil() as usize + 2;
+        let summary = world.map.summarize(left, top, columns, rows, cell);
+        let cell_pixels = cell as f64 * scale;
+        for (column, cells) in summary.iter().enumerate() {
+            for (row, tile_type) in cells.iter().enumerate() {
+                if let Some(ref tile_type) = *tile_type {
+                    let (x, y) = self.to_screen(
+                        (left + (column * cell) as i32) as f64,
+                        (top + (row * cell) as i32) as f64,
+                        width,
+                        height,
+                    );
+                    Rectangle::new(tile_type.map_color()).draw(
+                        [x, y, cell_pixels, cell_pixels],
+                        &context.draw_state,
+                        transform,
+                        graphics,
+                    );
+                }
+            }
+        }
+
+        // Outline discovered islands, with quest targets in the quest colour.
+        let targets: Vec<(u64, &str)> = world
+            .quests
+            .active()
+            .iter()
+            .filter_map(|quest| {
+                let target = world.quests.progress.get(&quest.name)?.target?;
+                Some((target, quest.title.as_str()))
+            })
+            .collect();
+        for island in world.map.discovered_islands() {
+            let (x, y) = self.to_screen(island.x as f64, island.y as f64, width, height);
+            let bounds = [
+                x,
+                y,
+                island.width as f64 * scale,
+                island.height as f64 * scale,
+            ];
+            let quest = targets.iter().find(|&&(id, _)| id == island.id);
+            let (color, label) = match quest {
+                Some(&(_, title)) => (QUEST_COLOR, format!("Island {}: {}", island.id, title)),
+                None => (ISLAND_COLOR, format!("Island {}", island.id)),
+            };
+            Rectangle::new_border(color, 1.0).draw(bounds, &context.draw_state, transform, graphics);
+            text(
+                color,
+                16,
+                &label,
+                glyphs,
+                transform.trans(x, y - 4.0),
+                graphics,
+            ).expect(&format!("Error drawing {}", label));
+        }
+
+        // Mark where crew at the wheel are steering to.
+        if let Some((waypoint_x, waypoint_y)) = world.waypoint {
+            let (x, y) = self.to_screen(
+                waypoint_x / IMAGE_SIZE_SCALED,
+                waypoint_y / IMAGE_SIZE_SCALED,
+                width,
+                height,
+            );
+            Rectangle::new_border(WAYPOINT_COLOR, 1.0).draw(
+                [x - 5.0, y - 5.0, 10.0, 10.0],
+                &context.draw_state,
+                transform,
+                graphics,
+            );
+        }
+
+        for (i, &(marker_x, marker_y)) in world.map.markers().iter().enumerate() {
+            let (x, y) = self.to_screen(
+                marker_x as f64 + 0.5,
+                marker_y as f64 + 0.5,
+                width,
+                height,
+            );
+            Rectangle::new(MARKER_COLOR).draw(
+                [x - 4.0, y - 4.0, 8.0, 8.0],
+                &context.draw_state,
+                transform,
+                graphics,
+            );
+            let label = format!("{}", i + 1);
+            text(
+                MARKER_COLOR,
+                16,
+                &label,
+                glyphs,
+                transform.trans(x + 6.0, y + 4.0),
+                graphics,
+            ).expect(&format!("Error drawing {}", label));
+        }
+
+        // The ship at its size, but never too small to see.
+        let (ship_x, ship_y) = self.to_screen(
+            world.ship.x / IMAGE_SIZE_SCALED,
+            world.ship.y / IMAGE_SIZE_SCALED,
+            width,
+            height,
+        );
+        Rectangle::new(SHIP_COLOR).draw(
+            [
+                ship_x,
+                ship_y,
+                (world.ship.width * scale).max(4.0),
+                (world.ship.height * scale).max(4.0),
+            ],
+            &context.draw_state,
+            transform,
+            graphics,
+        );
+        if world.player_location == PlayerLocation::InWorld {
+            let (player_x, player_y) = self.to_screen(
+                world.player.x / IMAGE_SIZE_SCALED + 0.5,
+                world.player.y / IMAGE_SIZE_SCALED + 0.5,
+                width,
+                height,
+            );
+            Rectangle::new(PLAYER_COLOR).draw(
+                [player_x - 2.0, player_y - 2.0, 4.0, 4.0],
+                &context.draw_state,
+                transform,
+                graphics,
+            );
+        }
+
+        // Crosshair where markers are placed from the keyboard.
+        let line = Line::new([1.0, 1.0, 1.0, 0.6], 0.5);
+        line.draw(
+            [width / 2.0 - 8.0, height / 2.0, width / 2.0 + 8.0, height / 2.0],
+            &context.draw_state,
+            transform,
+            graphics,
+        );
+        line.draw(
+            [width / 2.0, height / 2.0 - 8.0, width / 2.0, height / 2.0 + 8.0],
+            &context.draw_state,
+            transform,
+            graphics,
+        );
+    }
+}
+
+/*
+    Finds what the maps are centred on: the ship, or the player when ashore.
+
+    @param world The game world.
+    @return (f64, f64) The x and y position, in tiles.
+*/
+pub fn focus(world: &World) -> (f64, f64) {
+    let (x, y) = match world.player_location {
+        PlayerLocation::OnShip => world.ship_center(),
+        PlayerLocation::InWorld => (
+            world.player.x + IMAGE_SIZE_SCALED / 2.0,
+            world.player.y + IMAGE_SIZE_SCALED / 2.0,
+        ),
+    };
+    (x / IMAGE_SIZE_SCALED, y / IMAGE_SIZE_SCALED)
+}
+
+/*
+    Draws a small map of the terrain around the ship, or around the player when ashore,
+    with the ship, the player and any markers nearby.
+
+    @param world The game world.
+    @param context The drawing context for Piston.
+    @param graphics Graphics engine.
+    @param x The x position of the minimap's left edge in the window.
+    @param y The y position of the minimap's top edge in the window.
+*/
+pub fn draw_minimap(world: &World, context: &Context, graphics: &mut G2d, x: f64, y: f64) {
+    let transform = context.transform;
+    let bounds = [x, y, MINIMAP_SIZE, MINIMAP_SIZE];
+    Rectangle::new(BACKGROUND_COLOR).draw(bounds, &context.draw_state, transform, graphics);
+
+    // Each cell covers a few tiles, lined up with the grid so it doesn't shimmer.
+    let (center_x, center_y) = focus(world);
+    let cell = MINIMAP_CELL as i32;
+    let half = (MINIMAP_CELLS * MINIMAP_CELL / 2) as i32;
+    let left = (center_x.floor() as i32 - half).div_euclid(cell) * cell;
+    let top = (center_y.floor() as i32 - half).div_euclid(cell) * cell;
+    let summary = world
+        .map
+        .summarize(left, top, MINIMAP_CELLS, MINIMAP_CELLS, MINIMAP_CELL);
+    for (column, cells) in summary.iter().enumerate() {
+        for (row, tile_type) in cells.iter().enumerate() {
+            if let Some(ref tile_type) = *tile_type {
+                Rectangle::new(tile_type.map_color()).draw(
+                    [
+                        x + column as f64 * CELL_PIXELS,
+                        y + row as f64 * CELL_PIXELS,
+                        CELL_PIXELS,
+                        CELL_PIXELS,
+                    ],
+                    &context.draw_state,
+                    transform,
+                    graphics,
+                );
+            }
+        }
+    }
+
+    // Finds where a position in tiles is on the minimap, if it's on it at all.
+    let pixels = CELL_PIXELS / MINIMAP_CELL as f64;
+    let place = |tile_x: f64, tile_y: f64| {
+        let (map_x, map_y) = (
+            x + (tile_x - left as f64) * pixels,
+            y + (tile_y - top as f64) * pixels,
+        );
+        if map_x >= x && map_x < x + MINIMAP_SIZE && map_y >= y && map_y < y + MINIMAP_SIZE {
+            Some((map_x, map_y))
+        } else {
+            None
+        }
+    };
+    for &(marker_x, marker_y) in world.map.markers() {
+        if let Some((map_x, map_y)) = place(marker_x as f64 + 0.5, marker_y as f64 + 0.5) {
+            Rectangle::new(MARKER_COLOR).draw(
+                [map_x - 2.0, map_y - 2.0, 4.0, 4.0],
+                &context.draw_state,
+                transform,
+                graphics,
+            );
+        }
+    }
+    if let Some((map_x, map_y)) = place(
+        world.ship.x / IMAGE_SIZE_SCALED,
+        world.ship.y / IMAGE_SIZE_SCALED,
+    ) {
+        Rectangle::new(SHIP_COLOR).draw(
+            [
+                map_x,
+                map_y,
+                world.ship.width * pixels,
+                world.ship.height * pixels,
+            ],
+            &context.draw_state,
+            transform,
+            graphics,
+        );
+    }
+    if world.player_location == PlayerLocation::InWorld {
+        let (player_x, player_y) = (
+            world.player.x / IMAGE_SIZE_SCALED + 0.5,
+            world.player.y / IMAGE_SIZE_SCALED + 0.5,
+        );
+        if let Some((map_x, map_y)) = place(player_x, player_y) {
+            Rectangle::new(PLAYER_COLOR).draw(
+                [map_x - 1.0, map_y - 1.0, 2.0, 2.0],
+                &context.draw_state,
+                transform,
+                graphics,
+            );
+        }
+    }
+
+    Rectangle::new_border([1.0, 1.0, 1.0, 0.8], 1.0).draw(
+        bounds,
+        &context.draw_state,
+        transform,
+        graphics,
+    );
+}
@@ -1,10 +1,11 @@
 //! Homes miscellaneous features for the game.
 
-use piston_window::{ButtonState, Key};
+use input::Action;
 
 pub trait Moveable {
-    fn handle_input(&mut self, state: &ButtonState, key: &Option<Key>);
-    fn update_position(&mut self);
+    fn handle_input(&mut self, action: Action, active: bool);
+    fn handle_analog(&mut self, x: f64, y: f64);
+    fn update_position(&mut self, dt: f64);
     fn update_self_velocity(&mut self);
 }
 
@@ -32,3 +33,57 @@ impl Direction {
         }
     }
 }
+
+/*
+    Adds or removes a direction from a list of directions being moved in.
+
+    @param directions The directions being moved in.
+    @param dir The direction that changed.
+    @param moving Whether movement in the direction starts or stops.
+*/
+pub fn update_directions(directions: &mut Vec<Direction>, dir: Direction, moving: bool) {
+    if let Some(index) = directions.iter().position(|&x| x == dir) {
+        if !moving {
+            directions.remove(index);
+        }
+    } else if moving {
+        directions.push(dir);
+    }
+}
+
+/*
+    Determines if two rectangles overlap.
+
+    @param a The first rectangle, as x, y, width and height.
+    @param b The second rectangle, as x, y, width and height.
+    @return bool Whether they overlap.
+*/
+pub fn overlaps(a: [f64; 4], b: [f64; 4]) -> bool {
+    a[0] < b[0] + b[2] && b[0] < a[0] + a[2] && a[1] < b[1] + b[3] && b[1] < a[1] + a[3]
+}
+
+/*
+    Turns a point in the window into a point in the world, undoing the translation
+    the world was drawn with.
+
+    @param screen_x The point's x in the window.
+    @param screen_y The point's y in the window.
+    @param trans_x The horizontal translation the world was drawn with.
+    @param trans_y The vertical translation the world was drawn with.
+    @return (f64, f64) The point's x and y in the world.
+*/
+pub fn screen_to_world(screen_x: f64, screen_y: f64, trans_x: f64, trans_y: f64) -> (f64, f64) {
+    (screen_x - trans_x, screen_y - trans_y)
+}
+
+/*
+    Linearly interpolates between two values.
+
+    @param start The value at alpha 0.
+    @param end The value at alpha 1.
+    @param alpha How far between start and end.
+    @return f64 The interpolated value.
+*/
+pub fn lerp(start: f64, end: f64, alpha: f64) -> f64 {
+    start + (end - start) * alpha
+}
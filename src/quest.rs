@@ -0,0 +1,405 @@
+//! Quests give the player goals beyond wandering.
+//!
+//! Each quest has stages, done in order, and each stage has an objective that progresses
+//! as game events come in. Finishing the last stage grants the quest's reward.
+//! Quests start as soon as the quest they require, if any, is finished.
+
+use event::{GameEvent, Observer};
+use item::*;
+use std::collections::{HashMap, HashSet};
+
+/**
+    What a stage of a quest asks of the player.
+
+    @variant Collect Pick up or harvest a number of items of a type.
+    @variant ReachIsland Set foot on any island not reached before. The nearest is pointed out.
+    @variant Recruit Recruit a number of islanders.
+    @variant Defeat Defeat a number of enemies.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum Objective {
+    Collect(ItemType, u32),
+    ReachIsland,
+    Recruit(u32),
+    Defeat(u32),
+}
+
+impl Objective {
+    /*
+        Determines how many events finish the objective.
+
+        @return u32 The number of events.
+    */
+    pub fn goal(&self) -> u32 {
+        match *self {
+            Objective::Collect(_, count) | Objective::Recruit(count) | Objective::Defeat(count) => {
+                count
+            }
+            Objective::ReachIsland => 1,
+        }
+    }
+
+    /*
+        Determines whether an event progresses the objective.
+
+        @param event The event.
+        @return bool Whether the event counts.
+    */
+    pub fn counts(&self, event: &GameEvent) -> bool {
+        match (self, event) {
+            (Objective::Collect(wanted, _), GameEvent::Collected(item_type)) => wanted == item_type,
+            (Objective::ReachIsland, GameEvent::IslandReached(_)) => true,
+            (Objective::Recruit(_), GameEvent::Recruited(_)) => true,
+            (Objective::Defeat(_), GameEvent::EnemyDefeated) => true,
+            _ => false,
+        }
+    }
+}
+
+/**
+    Implementation of the Stage object.
+
+    @field description What to do, shown in the journal and on the HUD.
+    @field objective What finishes the stage.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stage {
+    pub description: String,
+    pub objective: Objective,
+}
+
+impl Stage {
+    /*
+        Stage constructor.
+
+        @param description What to do.
+        @param objective What finishes the stage.
+        @return Stage Returns self.
+    */
+    pub fn new(description: &str, objective: Objective) -> Self {
+        Stage {
+            description: description.to_string(),
+            objective: objective,
+        }
+    }
+}
+
+/**
+    Implementation of the Quest object.
+
+    @field name The quest's name, used in saves.
+    @field title The quest's title, shown in the journal and on the HUD.
+    @field requires The name of the quest that must be finished first, if any.
+    @field stages The stages, done in order.
+    @field reward The item types given on finishing, and how many of each.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quest {
+    pub name: String,
+    pub title: String,
+    pub requires: Option<String>,
+    pub stages: Vec<Stage>,
+    pub reward: Vec<(ItemType, u32)>,
+}
+
+impl Quest {
+    /*
+        Quest constructor.
+
+        @param name The quest's name.
+        @param title The quest's title.
+        @param requires The name of the quest that must be finished first, if any.
+        @param stages The stages, done in order.
+        @param reward The item types given on finishing, and how many of each.
+        @return Quest Returns self.
+    */
+    pub fn new(
+        name: &str,
+        title: &str,
+        requires: Option<&str>,
+        stages: Vec<Stage>,
+        reward: Vec<(ItemType, u32)>,
+    ) -> Self {
+        Quest {
+            name: name.to_string(),
+            title: title.to_string(),
+            requires: requires.map(|name| name.to_string()),
+            stages: stages,
+            reward: reward,
+        }
+    }
+}
+
+/**
+    Implementation of the QuestProgress object, how far a started quest has got.
+
+    @field stage The index of the current stage, or the number of stages once finished.
+    @field count Events counted towards the current stage's objective.
+    @field target The island pointed out for a ReachIsland stage, once one is chosen.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuestProgress {
+    pub stage: usize,
+    pub count: u32,
+    pub target: Option<u64>,
+}
+
+/**
+    Implementation of the QuestLog object.
+
+    @field quests Registry of every quest, in the order they're listed.
+    @field progress Progress through the quests that have started, keyed by quest name.
+    @field reached Ids of the islands the player has set foot on.
+    @field unrewarded Indices of the quests finished whose rewards haven't been given yet.
+*/
+pub struct QuestLog {
+    pub quests: Vec<Quest>,
+    pub progress: HashMap<String, QuestProgress>,
+    pub reached: HashSet<u64>,
+    pub unrewarded: Vec<usize>,
+}
+
+impl QuestLog {
+    /*
+        QuestLog constructor. Quests that require nothing start straight away.
+
+        @param quests Registry of every quest.
+        @return QuestLog Returns self.
+    */
+    pub fn new(quests: Vec<Quest>) -> Self {
+        let mut log = QuestLog {
+            quests: quests,
+            progress: HashMap::new(),
+            reached: HashSet::new(),
+            unrewarded: vec![],
+        };
+        log.start_available();
+        log
+    }
+
+    /*
+        Starts the quests whose required quest is finished.
+    */
+    pub fn start_available(&mut self) {
+        let available: Vec<String> = self.quests
+            .iter()
+            .filter(|quest| !self.progress.contains_key(&quest.name))
+            .filter(|quest| match quest.requires {
+                Some(ref required) => self.is_finished(required),
+                None => true,
+            })
+            .map(|quest| quest.name.clone())
+            .collect();
+        for name in available {
+            self.progress.insert(
+                name,
+                QuestProgress {
+                    stage: 0,
+                    count: 0,
+                    target: None,
+                },
+            );
+        }
+    }
+
+    /*
+        Determines whether a quest has been finished.
+
+        @param name The quest's name.
+        @return bool Whether every stage is done.
+    */
+    pub fn is_finished(&self, name: &str) -> bool {
+        match (self.quest(name), self.progress.get(name)) {
+            (Some(quest), Some(progress)) => progress.stage >= quest.stages.len(),
+            _ => false,
+        }
+    }
+
+    /*
+        Finds a quest in the registry.
+
+        @param name The quest's name.
+        @return Option<&Quest> The quest, if there is one by that name.
+    */
+    pub fn quest(&self, name: &str) -> Option<&Quest> {
+        self.quests.iter().find(|quest| quest.name == name)
+    }
+
+    /*
+        Lists the quests that have started but aren't finished.
+
+        @return Vec<&Quest> The quests, in registry order.
+    */
+    pub fn active(&self) -> Vec<&Quest> {
+        self.quests
+            .iter()
+            .filter(|quest| self.progress.contains_key(&quest.name) && !self.is_finished(&quest.name))
+            .collect()
+    }
+
+    /*
+        Lists the finished quests.
+
+        @return Vec<&Quest> The quests, in registry order.
+    */
+    pub fn finished(&self) -> Vec<&Quest> {
+        self.quests
+            .iter()
+            .filter(|quest| self.is_finished(&quest.name))
+            .collect()
+    }
+
+    /*
+        Finds the stage an active quest is at.
+
+        @param quest The quest.
+        @return Option<&Stage> The stage, if the quest is active.
+    */
+    pub fn current_stage<'a>(&self, quest: &'a Quest) -> Option<&'a Stage> {
+        let progress = self.progress.get(&quest.name)?;
+        quest.stages.get(progress.stage)
+    }
+
+    /*
+        Describes what to do next in an active quest, e.g. "Gather 4 logs (2/4)".
+
+        @param quest The quest.
+        @return String The description, empty if the quest isn't active.
+    */
+    pub fn describe(&self, quest: &Quest) -> String {
+        match (self.current_stage(quest), self.progress.get(&quest.name)) {
+            (Some(stage), Some(progress)) if stage.objective.goal() > 1 => format!(
+                "{} ({}/{})",
+                stage.description,
+                progress.count,
+                stage.objective.goal()
+            ),
+            (Some(stage), _) => stage.description.clone(),
+            _ => String::new(),
+        }
+    }
+
+    /*
+        Lists the active quests at a ReachIsland stage with no island chosen yet.
+
+        @return Vec<String> The quests' names.
+    */
+    pub fn needing_target(&self) -> Vec<String> {
+        self.active()
+            .into_iter()
+            .filter(|quest| {
+                let stage = self.current_stage(quest);
+                let progress = &self.progress[&quest.name];
+                stage.map(|stage| &stage.objective) == Some(&Objective::ReachIsland)
+                    && progress.target.is_none()
+            })
+            .map(|quest| quest.name.clone())
+            .collect()
+    }
+
+    /*
+        Chooses the island to reach for a quest's current stage.
+
+        @param name The quest's name.
+        @param island The island's id.
+    */
+    pub fn set_target(&mut self, name: &str, island: u64) {
+        if let Some(progress) = self.progress.get_mut(name) {
+            progress.target = Some(island);
+        }
+    }
+
+    /*
+        Moves the active quests along by an event, starting any quests that were waiting
+        on the ones it finishes.
+
+        @param event The event.
+        @return Vec<usize> The indices of the quests finished, whose rewards are due.
+    */
+    pub fn handle(&mut self, event: &GameEvent) -> Vec<usize> {
+        if let GameEvent::IslandReached(id) = *event {
+            if !self.reached.insert(id) {
+                return vec![];
+            }
+        }
+        let mut finished = vec![];
+        for (i, quest) in self.quests.iter().enumerate() {
+            let progress = match self.progress.get_mut(&quest.name) {
+                Some(progress) => progress,
+                None => continue,
+            };
+            let goal = match quest.stages.get(progress.stage) {
+                Some(stage) if stage.objective.counts(event) => {
+                    stage.objective.goal()
+                }
+                _ => continue,
+            };
+            progress.count += 1;
+            if progress.count < goal {
+                continue;
+            }
+            progress.stage += 1;
+            progress.count = 0;
+            progress.target = None;
+            if progress.stage == quest.stages.len() {
+                finished.push(i);
+            }
+        }
+        if !finished.is_empty() {
+            self.start_available();
+        }
+        finished
+    }
+}
+
+impl Observer for QuestLog {
+    /*
+        Moves the quests along by an event, keeping the finished ones to be rewarded.
+
+        @param event The event.
+    */
+    fn notify(&mut self, event: &GameEvent) {
+        let finished = self.handle(event);
+        self.unrewarded.extend(finished);
+    }
+}
+
+/*
+    Creates the registry of every quest in the game.
+
+    @return Vec<Quest> The quests, in the order shown in the journal.
+*/
+pub fn generate_quests() -> Vec<Quest> {
+    vec![
+        Quest::new(
+            "landfall",
+            "Landfall",
+            None,
+            vec![
+                Stage::new("Take the portal down to an island", Objective::ReachIsland),
+                Stage::new(
+                    "Chop trees and gather logs",
+                    Objective::Collect(ItemType::Resource(ResourceType::Logs), 4),
+                ),
+            ],
+            vec![(ItemType::Food(FoodType::Bisket), 2)],
+        ),
+        Quest::new(
+            "all_hands",
+            "All hands",
+            Some("landfall"),
+            vec![Stage::new("Talk an islander into joining the crew", Objective::Recruit(1))],
+            vec![(ItemType::Interactable(InteractableType::Sword), 1)],
+        ),
+        Quest::new(
+            "far_shores",
+            "Far shores",
+            Some("all_hands"),
+            vec![
+                Stage::new("Sail to an island you haven't set foot on", Objective::ReachIsland),
+                Stage::new("Defeat enemies", Objective::Defeat(3)),
+            ],
+            vec![(ItemType::Resource(ResourceType::Planks), 8)],
+        ),
+    ]
+}
@@ -0,0 +1,887 @@
+//! Save handles writing the game state to disk and reading it back.
+//! Saves are plain text, versioned, and stored in numbered slots.
+//! Saves from older versions load with the sections added since left empty.
+//!
+//! Each line of a save is a key followed by its values, separated by spaces.
+//! Tile grids are stored one row per line, one character per tile.
+//! Only map tiles changed since generation are stored, the rest is regenerated from the seed.
+//! Each changed tile is stored with how long it has left to grow back.
+//! Saves before version 15 stored whole chunks, with the plants growing back listed after.
+//! Only recruited crew are stored, islanders are repopulated like enemies.
+//! Discovered islands are stored by chunk, and placed again from the seed.
+//! Map markers are stored by tile, in the order placed.
+//! Story flags set by dialogue are stored by name, in order.
+//! Quests are stored by name with their stage, so quests added later start fresh.
+//!
+//! Achievement progress belongs to the player rather than a world, so it's kept in its own
+//! file next to the slots, and isn't touched by saving or loading them.
+
+use creature::{Creature, CreatureState};
+use crew::{CrewMember, Role};
+use inventory::{Inventory, Stack};
+use item::{Item, ItemType};
+use map::{Map, CHUNK_SIZE};
+use quest::QuestProgress;
+use ship::{is_connected, Ship};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use tile::{Tile, TileType};
+use world::{PlayerLocation, World};
+
+pub const SAVE_VERSION: u32 = 15;
+pub const OLDEST_SAVE_VERSION: u32 = 5;
+pub const SAVE_SLOTS: usize = 3;
+pub const ACHIEVEMENTS_VERSION: u32 = 1;
+const SAVE_HEADER: &str = "AOE_SAVE";
+const ACHIEVEMENTS_HEADER: &str = "AOE_ACHIEVEMENTS";
+const ACHIEVEMENTS_FILE: &str = "achievements.txt";
+const SAVE_DIR: &str = "saves";
+
+/**
+    Errors that can occur while saving or loading.
+
+    @variant Io The file could not be read or written.
+    @variant UnsupportedVersion The save was written by an unknown or too old version.
+    @variant Malformed The save could not be parsed, with the line number and reason.
+*/
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    UnsupportedVersion(u32),
+    Malformed(usize, String),
+}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SaveError::Io(ref e) => write!(f, "{}", e),
+            SaveError::UnsupportedVersion(v) => write!(f, "unsupported save version {}", v),
+            SaveError::Malformed(line, ref reason) => write!(f, "line {}: {}", line, reason),
+        }
+    }
+}
+
+/**
+    Implementation of the SaveData object, the state recovered from a save.
+
+    @field player The main player.
+    @field ship The player's airship.
+    @field player_location Player's worldly position.
+    @field items_in_game Set of all items in the game.
+    @field crew The recruited crew.
+    @field flags Story flags set by dialogue.
+    @field cleared_islands Ids of the islands whose enemies have all been defeated.
+    @field quests Progress through the quests that have started, keyed by quest name.
+    @field reached Ids of the islands the player has set foot on.
+    @field map The world map.
+*/
+pub struct SaveData {
+    pub player: Creature,
+    pub ship: Ship,
+    pub player_location: PlayerLocation,
+    pub items_in_game: Vec<Item>,
+    pub crew: Vec<CrewMember>,
+    pub flags: HashSet<String>,
+    pub cleared_islands: HashSet<u64>,
+    pub quests: HashMap<String, QuestProgress>,
+    pub reached: HashSet<u64>,
+    pub map: Map,
+}
+
+/*
+    Writes the game state into the save format.
+
+    @param world The game world.
+    @return String The save text.
+*/
+pub fn write_save(world: &World) -> String {
+    let mut out = format!("{} {}\n", SAVE_HEADER, SAVE_VERSION);
+    write_creature(&mut out, &world.player);
+    let location = match world.player_location {
+        PlayerLocation::OnShip => "OnShip",
+        PlayerLocation::InWorld => "InWorld",
+    };
+    out.push_str(&format!("location {}\n", location));
+    write_ship(&mut out, &world.ship);
+    out.push_str(&format!("items {}\n", world.items_in_game.len()));
+    for item in &world.items_in_game {
+        write_item(&mut out, "item", item);
+    }
+    let crew: Vec<&CrewMember> = world.crew.iter().filter(|m| m.recruited).collect();
+    out.push_str(&format!("crew {}\n", crew.len()));
+    for member in crew {
+        write_crew_member(&mut out, member);
+    }
+    let mut flags: Vec<&String> = world.flags.iter().collect();
+    flags.sort();
+    out.push_str(&format!("flags {}\n", flags.len()));
+    for flag in flags {
+        out.push_str(&format!("flag {}\n", flag));
+    }
+    let mut cleared: Vec<&u64> = world.cleared_islands.iter().collect();
+    cleared.sort();
+    out.push_str(&format!("cleared {}\n", cleared.len()));
+    for island in cleared {
+        out.push_str(&format!("clear {}\n", island));
+    }
+    write_quests(&mut out, &world.quests.progress, &world.quests.reached);
+    write_map(&mut out, &world.map);
+    out
+}
+
+/*
+    Reads the game state from the save format.
+
+    @param text The save text.
+    @return Result<SaveData, SaveError> The recovered state.
+*/
+pub fn read_save(text: &str) -> Result<SaveData, SaveError> {
+    let mut reader = SaveReader::new(text);
+    let header = reader.fields(SAVE_HEADER, 1)?;
+    let version: u32 = reader.parse(header[0])?;
+    if !(OLDEST_SAVE_VERSION..=SAVE_VERSION).contains(&version) {
+        return Err(SaveError::UnsupportedVersion(version));
+    }
+
+    let player = read_creature(&mut reader, version)?;
+    let location = match reader.fields("location", 1)?[0] {
+        "OnShip" => PlayerLocation::OnShip,
+        "InWorld" => PlayerLocation::InWorld,
+        other => return Err(reader.error(&format!("unknown location {:?}", other))),
+    };
+    let ship = read_ship(&mut reader)?;
+    let count: usize = {
+        let fields = reader.fields("items", 1)?;
+        reader.parse(fields[0])?
+    };
+    let mut items = vec![];
+    for _ in 0..count {
+        items.push(read_item(&mut reader, "item", version)?);
+    }
+    let mut crew = vec![];
+    if version >= 6 {
+        let count: usize = {
+            let fields = reader.fields("crew", 1)?;
+            reader.parse(fields[0])?
+        };
+        for _ in 0..count {
+            crew.push(read_crew_member(&mut reader, version)?);
+        }
+    }
+    let mut flags = HashSet::new();
+    if version >= 8 {
+        let count: usize = {
+            let fields = reader.fields("flags", 1)?;
+            reader.parse(fields[0])?
+        };
+        for _ in 0..count {
+            flags.insert(reader.fields("flag", 1)?[0].to_string());
+        }
+    }
+    let mut cleared_islands = HashSet::new();
+    if version >= 13 {
+        let count: usize = {
+            let fields = reader.fields("cleared", 1)?;
+            reader.parse(fields[0])?
+        };
+        for _ in 0..count {
+            let fields = reader.fields("clear", 1)?;
+            cleared_islands.insert(reader.parse(fields[0])?);
+        }
+    }
+    let (quests, reached) = if version >= 9 {
+        read_quests(&mut reader)?
+    } else {
+        (HashMap::new(), HashSet::new())
+    };
+    let map = read_map(&mut reader, version)?;
+
+    Ok(SaveData {
+        player: player,
+        ship: ship,
+        player_location: location,
+        items_in_game: items,
+        crew: crew,
+        flags: flags,
+        cleared_islands: cleared_islands,
+        quests: quests,
+        reached: reached,
+        map: map,
+    })
+}
+
+/*
+    Determines the file used by a save slot.
+
+    @param slot The slot number, starting at 1.
+    @return PathBuf The path of the slot's file.
+*/
+pub fn slot_path(slot: usize) -> PathBuf {
+    PathBuf::from(SAVE_DIR).join(format!("slot_{}.sav", slot))
+}
+
+/*
+    Writes save text to a slot, creating the save folder if needed.
+
+    @param slot The slot number, starting at 1.
+    @param text The save text.
+*/
+pub fn save_to_slot(slot: usize, text: &str) -> Result<(), SaveError> {
+    fs::create_dir_all(SAVE_DIR)?;
+    fs::write(slot_path(slot), text)?;
+    Ok(())
+}
+
+/*
+    Reads the game state from a slot.
+
+    @param slot The slot number, starting at 1.
+    @return Result<SaveData, SaveError> The recovered state.
+*/
+pub fn load_from_slot(slot: usize) -> Result<SaveData, SaveError> {
+    let text = fs::read_to_string(slot_path(slot))?;
+    read_save(&text)
+}
+
+/*
+    Finds the most recently written save slot, used to "Continue".
+
+    @return Option<usize> The slot number, if any save exists.
+*/
+pub fn latest_slot() -> Option<usize> {
+    let mut latest = None;
+    for slot in 1..SAVE_SLOTS + 1 {
+        if let Ok(modified) = fs::metadata(slot_path(slot)).and_then(|m| m.modified()) {
+            match latest {
+                Some((_, time)) if time >= modified => {}
+                _ => latest = Some((slot, modified)),
+            }
+        }
+    }
+    latest.map(|(slot, _)| slot)
+}
+
+/*
+    Writes the player's achievement progress into its file format.
+
+    @param counts How much has been counted towards each achievement, keyed by name.
+    @return String The achievements text.
+*/
+pub fn write_achievements(counts: &HashMap<String, u32>) -> String {
+    let mut names: Vec<&String> = counts.keys().collect();
+    names.sort();
+    let mut out = format!("{} {}\n", ACHIEVEMENTS_HEADER, ACHIEVEMENTS_VERSION);
+    out.push_str(&format!("achievements {}\n", names.len()));
+    for name in names {
+        out.push_str(&format!("achievement {} {}\n", name, counts[name]));
+    }
+    out
+}
+
+/*
+    Reads the player's achievement progress from its file format.
+
+    @param text The achievements text.
+    @return Result<HashMap<String, u32>, SaveError> How much has been counted towards each
+    achievement, keyed by name.
+*/
+pub fn read_achievements(text: &str) -> Result<HashMap<String, u32>, SaveError> {
+    let mut reader = SaveReader::new(text);
+    let header = reader.fields(ACHIEVEMENTS_HEADER, 1)?;
+    let version: u32 = reader.parse(header[0])?;
+    if version != ACHIEVEMENTS_VERSION {
+        return Err(SaveError::UnsupportedVersion(version));
+    }
+    let count: usize = {
+        let fields = reader.fields("achievements", 1)?;
+        reader.parse(fields[0])?
+    };
+    let mut counts = HashMap::new();
+    for _ in 0..count {
+        let fields = reader.fields("achievement", 2)?;
+        counts.insert(fields[0].to_string(), reader.parse(fields[1])?);
+    }
+    Ok(counts)
+}
+
+/*
+    Writes achievements text to its file, creating the save folder if needed.
+
+    @param text The achievements text.
+*/
+pub fn save_achievements(text: &str) -> Result<(), SaveError> {
+    fs::create_dir_all(SAVE_DIR)?;
+    fs::write(PathBuf::from(SAVE_DIR).join(ACHIEVEMENTS_FILE), text)?;
+    Ok(())
+}
+
+/*
+    Reads the player's achievement progress from its file. Nothing is counted yet if there
+    is no file.
+
+    @return Result<HashMap<String, u32>, SaveError> The progress, keyed by achievement name.
+*/
+pub fn load_achievements() -> Result<HashMap<String, u32>, SaveError> {
+    match fs::read_to_string(PathBuf::from(SAVE_DIR).join(ACHIEVEMENTS_FILE)) {
+        Ok(text) => read_achievements(&text),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(e) => Err(SaveError::Io(e)),
+    }
+}
+
+/*
+    Reads a save line by line, keeping track of the line number for errors.
+*/
+struct SaveReader<'a> {
+    lines: ::std::str::Lines<'a>,
+    line_number: usize,
+}
+
+impl<'a> SaveReader<'a> {
+    fn new(text: &'a str) -> Self {
+        SaveReader {
+            lines: text.lines(),
+            line_number: 0,
+        }
+    }
+
+    /*
+        Creates an error for the current line.
+    */
+    fn error(&self, reason: &str) -> SaveError {
+        SaveError::Malformed(self.line_number, reason.to_string())
+    }
+
+    /*
+        Reads the next raw line.
+    */
+    fn line(&mut self) -> Result<&'a str, SaveError> {
+        self.line_number += 1;
+        match self.lines.next() {
+            Some(line) => Ok(line),
+            None => Err(self.error("unexpected end of save")),
+        }
+    }
+
+    /*
+        Reads the next line, which must start with the key and have the given number of values.
+
+        @param key The expected key.
+        @param count The expected number of values after the key.
+        @return Result<Vec<&str>, SaveError> The values.
+    */
+    fn fields(&mut self, key: &str, count: usize) -> Result<Vec<&'a str>, SaveError> {
+        let line = self.line()?;
+        let mut fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.is_empty() || fields[0] != key {
+            return Err(self.error(&format!("expected {:?}", key)));
+        }
+        fields.remove(0);
+        if fields.len() != count {
+            return Err(self.error(&format!(
+                "expected {} values for {:?}, found {}",
+                count,
+                key,
+                fields.len()
+            )));
+        }
+        Ok(fields)
+    }
+
+    /*
+        Parses a single value from the current line.
+    */
+    fn parse<T: FromStr>(&self, field: &str) -> Result<T, SaveError> {
+        field
+            .parse()
+            .map_err(|_| self.error(&format!("invalid value {:?}", field)))
+    }
+
+    /*
+        Reads a grid of tiles stored one row per line.
+
+        @param width The number of tiles in a row.
+        @param height The number of rows.
+        @return Result<Vec<Vec<Tile>>, SaveError> The tiles, indexed by x then y.
+    */
+    fn tiles(&mut self, width: usize, height: usize) -> Result<Vec<Vec<Tile>>, SaveError> {
+        let mut tiles = vec![Vec::with_capacity(height); width];
+        for _ in 0..height {
+            let line = self.line()?;
+            if line.chars().count() != width {
+                return Err(self.error(&format!("expected a row of {} tiles", width)));
+            }
+            for (x, c) in line.chars().enumerate() {
+                match char_to_tile(c) {
+                    Some(tile) => tiles[x].push(tile),
+                    None => return Err(self.error(&format!("unknown tile {:?}", c))),
+                }
+            }
+        }
+        Ok(tiles)
+    }
+}
+
+fn write_creature(out: &mut String, creature: &Creature) {
+    let state = match creature.creature_state {
+        CreatureState::Normal => "Normal",
+        CreatureState::ControllingShip => "ControllingShip",
+    };
+    out.push_str(&format!(
+        "creature {} {} {} {}\n",
+        creature.x, creature.y, creature.health, state
+    ));
+    write_inventory(out, &creature.inventory);
+}
+
+fn read_creature(reader: &mut SaveReader, version: u32) -> Result<Creature, SaveError> {
+    let fields = reader.fields("creature", 4)?;
+    let mut creature = Creature::new();
+    creature.x = reader.parse(fields[0])?;
+    creature.y = reader.parse(fields[1])?;
+    creature.health = reader.parse(fields[2])?;
+    creature.creature_state = match fields[3] {
+        "Normal" => CreatureState::Normal,
+        "ControllingShip" => CreatureState::ControllingShip,
+        other => return Err(reader.error(&format!("unknown creature state {:?}", other))),
+    };
+    creature.inventory = read_inventory(reader, version)?;
+    Ok(creature)
+}
+
+fn write_crew_member(out: &mut String, member: &CrewMember) {
+    let station = match member.station {
+        Some((x, y)) => format!("{},{}", x, y),
+        None => "none".to_string(),
+    };
+    let home = match member.home {
+        Some(island) => island.to_string(),
+        None => "none".to_string(),
+    };
+    out.push_str(&format!(
+        "member {} {} {} {} {}\n",
+        member.name,
+        member.role.name(),
+        member.on_ship,
+        station,
+        home
+    ));
+    write_creature(out, &member.creature);
+}
+
+fn read_crew_member(reader: &mut SaveReader, version: u32) -> Result<CrewMember, SaveError> {
+    // Stations were added in version 7, and homes in version 11.
+    let count = match version {
+        0..=6 => 3,
+        7..=10 => 4,
+        _ => 5,
+    };
+    let fields = reader.fields("member", count)?;
+    let role = match Role::from_name(fields[1]) {
+        Some(role) => role,
+        None => return Err(reader.error(&format!("unknown role {:?}", fields[1]))),
+    };
+    let on_ship: bool = reader.parse(fields[2])?;
+    let station = match fields.get(3).cloned().unwrap_or("none") {
+        "none" => None,
+        station => {
+            let numbers: Vec<&str> = station.split(',').collect();
+            if numbers.len() != 2 {
+                return Err(reader.error(&format!("invalid station {:?}", station)));
+            }
+            Some((reader.parse(numbers[0])?, reader.parse(numbers[1])?))
+        }
+    };
+    let mut member = CrewMember::new(fields[0], role, 0.0, 0.0);
+    let creature = read_creature(reader, version)?;
+    member.creature.x = creature.x;
+    member.creature.y = creature.y;
+    member.creature.health = creature.health;
+    member.creature.inventory = creature.inventory;
+    member.recruited = true;
+    member.on_ship = on_ship;
+    member.station = station;
+    member.home = match fields.get(4).cloned().unwrap_or("none") {
+        "none" => None,
+        island => Some(reader.parse(island)?),
+    };
+    Ok(member)
+}
+
+fn write_quests(out: &mut String, quests: &HashMap<String, QuestProgress>, reached: &HashSet<u64>) {
+    let mut names: Vec<&String> = quests.keys().collect();
+    names.sort();
+    out.push_str(&format!("quests {}\n", names.len()));
+    for name in names {
+        let progress = &quests[name];
+        let target = match progress.target {
+            Some(island) => island.to_string(),
+            None => "none".to_string(),
+        };
+        out.push_str(&format!(
+            "quest {} {} {} {}\n",
+            name, progress.stage, progress.count, target
+        ));
+    }
+    let mut reached: Vec<&u64> = reached.iter().collect();
+    reached.sort();
+    out.push_str(&format!("reached {}\n", reached.len()));
+    for island in reached {
+        out.push_str(&format!("landed {}\n", island));
+    }
+}
+
+fn read_quests(
+    reader: &mut SaveReader,
+) -> Result<(HashMap<String, QuestProgress>, HashSet<u64>), SaveError> {
+    let count: usize = {
+        let fields = reader.fields("quests", 1)?;
+        reader.parse(fields[0])?
+    };
+    let mut quests = HashMap::new();
+    for _ in 0..count {
+        let fields = reader.fields("quest", 4)?;
+        let target = match fields[3] {
+            "none" => None,
+            island => Some(reader.parse(island)?),
+        };
+        let progress = QuestProgress {
+            stage: reader.parse(fields[1])?,
+            count: reader.parse(fields[2])?,
+            target: target,
+        };
+        quests.insert(fields[0].to_string(), progress);
+    }
+    let count: usize = {
+        let fields = reader.fields("reached", 1)?;
+        reader.parse(fields[0])?
+    };
+    let mut reached = HashSet::new();
+    for _ in 0..count {
+        let fields = reader.fields("landed", 1)?;
+        reached.insert(reader.parse(fields[0])?);
+    }
+    Ok((quests, reached))
+}
+
+fn write_inventory(out: &mut String, inventory: &Inventory) {
+    out.push_str(&format!(
+        "inventory {} {} {}\n",
+        inventory.slots.len(),
+        inventory.hand,
+        inventory.max_weight
+    ));
+    for slot in &inventory.slots {
+        match *slot {
+            Some(ref stack) => {
+                out.push_str(&format!("slot {}\n", stack.count));
+                write_item(out, "stack", &stack.item);
+            }
+            None => out.push_str("slot none\n"),
+        }
+    }
+}
+
+fn read_inventory(reader: &mut SaveReader, version: u32) -> Result<Inventory, SaveError> {
+    let fields = reader.fields("inventory", 3)?;
+    let slot_count: usize = reader.parse(fields[0])?;
+    let hand: usize = reader.parse(fields[1])?;
+    if hand >= slot_count {
+        return Err(reader.error("hand is not one of the slots"));
+    }
+    let mut inventory = Inventory::new(slot_count, reader.parse(fields[2])?);
+    inventory.hand = hand;
+    for i in 0..slot_count {
+        let fields = reader.fields("slot", 1)?;
+        if fields[0] == "none" {
+            continue;
+        }
+        let count = reader.parse(fields[0])?;
+        inventory.slots[i] = Some(Stack {
+            item: read_item(reader, "stack", version)?,
+            count: count,
+        });
+    }
+    Ok(inventory)
+}
+
+fn write_item(out: &mut String, key: &str, item: &Item) {
+    out.push_str(&format!(
+        "{} {} {} {} {} {} {} {} {} {} {}\n",
+        key,
+        item.item_type.name(),
+        item.x,
+        item.y,
+        item.damage,
+        item.pickupable,
+        item.x_vel,
+        item.y_vel,
+        item.weight,
+        item.collected,
+        item.aboard
+    ));
+}
+
+fn read_item(reader: &mut SaveReader, key: &str, version: u32) -> Result<Item, SaveError> {
+    // Whether an item was collected was added in version 12, and whether it's aboard in 14.
+    let count = match version {
+        0..=11 => 8,
+        12..=13 => 9,
+        _ => 10,
+    };
+    let fields = reader.fields(key, count)?;
+    item_from_fields(reader, &fields)
+}
+
+fn item_from_fields(reader: &SaveReader, fields: &[&str]) -> Result<Item, SaveError> {
+    let item_type = match ItemType::from_name(fields[0]) {
+        Some(item_type) => item_type,
+        None => return Err(reader.error(&format!("unknown item {:?}", fields[0]))),
+    };
+    let mut item = Item::new(
+        item_type,
+        reader.parse(fields[3])?,
+        reader.parse(fields[4])?,
+        reader.parse(fields[7])?,
+    );
+    item.x = reader.parse(fields[1])?;
+    item.y = reader.parse(fields[2])?;
+    item.x_vel = reader.parse(fields[5])?;
+    item.y_vel = reader.parse(fields[6])?;
+    if let Some(collected) = fields.get(8) {
+        item.collected = reader.parse(collected)?;
+    }
+    if let Some(aboard) = fields.get(9) {
+        item.aboard = reader.parse(aboard)?;
+    }
+    Ok(item)
+}
+
+fn write_ship(out: &mut String, ship: &Ship) {
+    out.push_str(&format!(
+        "ship {} {} {} {} {}\n",
+        ship.x,
+        ship.y,
+        ship.tiles.len(),
+        ship.tiles[0].len(),
+        ship.speed
+    ));
+    write_tiles(out, &ship.tiles);
+}
+
+fn read_ship(reader: &mut SaveReader) -> Result<Ship, SaveError> {
+    let fields = reader.fields("ship", 5)?;
+    let x = reader.parse(fields[0])?;
+    let y = reader.parse(fields[1])?;
+    let width = reader.parse(fields[2])?;
+    let height = reader.parse(fields[3])?;
+    let speed = reader.parse(fields[4])?;
+    if width == 0 || height == 0 {
+        return Err(reader.error("a ship needs at least one tile"));
+    }
+    let tiles = reader.tiles(width, height)?;
+    let wheels = tiles
+        .iter()
+        .flat_map(|column| column.iter())
+        .filter(|tile| tile.tile_type == TileType::Wheel)
+        .count();
+    if wheels != 1 {
+        return Err(reader.error("a ship has exactly one wheel"));
+    }
+    if !is_connected(&tiles) {
+        return Err(reader.error("the ship's tiles are not all connected"));
+    }
+    let mut ship = Ship::from_tiles(tiles);
+    ship.x = x;
+    ship.y = y;
+    ship.speed = speed;
+    Ok(ship)
+}
+
+fn write_map(out: &mut String, map: &Map) {
+    let changed = map.changed_tiles();
+    out.push_str(&format!("map {} {}\n", map.seed, changed.len()));
+    out.push_str(&format!("under_portal {}\n", tile_to_char(&map.under_portal)));
+    let discovered = map.discovered_islands();
+    out.push_str(&format!("discovered {}\n", discovered.len()));
+    for island in discovered {
+        let (chunk_x, chunk_y) = island.chunk();
+        out.push_str(&format!("island {} {}\n", chunk_x, chunk_y));
+    }
+    let markers = map.markers();
+    out.push_str(&format!("markers {}\n", markers.len()));
+    for &(x, y) in markers {
+        out.push_str(&format!("marker {} {}\n", x, y));
+    }
+    for ((x, y), tile) in changed {
+        out.push_str(&format!("tile {} {} {} {}\n", x, y, tile_to_char(tile), tile.regrow));
+    }
+}
+
+fn read_changed_tile(reader: &mut SaveReader, map: &mut Map) -> Result<(), SaveError> {
+    let fields = reader.fields("tile", 4)?;
+    let x = reader.parse(fields[0])?;
+    let y = reader.parse(fields[1])?;
+    let c: char = reader.parse(fields[2])?;
+    let mut tile = match char_to_tile(c) {
+        Some(tile) => tile,
+        None => return Err(reader.error(&format!("unknown tile {:?}", c))),
+    };
+    tile.regrow = reader.parse(fields[3])?;
+    map.set_tile(x, y, tile);
+    Ok(())
+}
+
+fn read_regrowing(reader: &mut SaveReader, tiles: &mut [Vec<Tile>]) -> Result<(), SaveError> {
+    let count: usize = {
+        let fields = reader.fields("regrowing", 1)?;
+        reader.parse(fields[0])?
+    };
+    for _ in 0..count {
+        let fields = reader.fields("plant", 3)?;
+        let x: usize = reader.parse(fields[0])?;
+        let y: usize = reader.parse(fields[1])?;
+        if x >= tiles.len() || y >= tiles[x].len() {
+            return Err(reader.error("plant is outside the chunk"));
+        }
+        tiles[x][y].regrow = reader.parse(fields[2])?;
+    }
+    Ok(())
+}
+
+fn read_map(reader: &mut SaveReader, version: u32) -> Result<Map, SaveError> {
+    let fields = reader.fields("map", 2)?;
+    let seed = reader.parse(fields[0])?;
+    let count: usize = reader.parse(fields[1])?;
+    let under_portal: char = {
+        let fields = reader.fields("under_portal", 1)?;
+        reader.parse(fields[0])?
+    };
+    let under_portal = match char_to_tile(under_portal) {
+        Some(tile) => tile,
+        None => return Err(reader.error(&format!("unknown tile {:?}", under_portal))),
+    };
+    let mut map = Map::with_seed(seed);
+    map.under_portal = under_portal;
+    let discovered: usize = if version >= 7 {
+        let fields = reader.fields("discovered", 1)?;
+        reader.parse(fields[0])?
+    } else {
+        0
+    };
+    for _ in 0..discovered {
+        let fields = reader.fields("island", 2)?;
+        let chunk_x = reader.parse(fields[0])?;
+        let chunk_y = reader.parse(fields[1])?;
+        map.discover_chunk(chunk_x, chunk_y);
+    }
+    let markers: usize = if version >= 10 {
+        let fields = reader.fields("markers", 1)?;
+        reader.parse(fields[0])?
+    } else {
+        0
+    };
+    for _ in 0..markers {
+        let fields = reader.fields("marker", 2)?;
+        let x = reader.parse(fields[0])?;
+        let y = reader.parse(fields[1])?;
+        map.add_marker(x, y);
+    }
+    // The count is of changed tiles since version 15, and of whole chunks before.
+    for _ in 0..count {
+        if version >= 15 {
+            read_changed_tile(reader, &mut map)?;
+            continue;
+        }
+        let fields = reader.fields("chunk", 2)?;
+        let chunk_x = reader.parse(fields[0])?;
+        let chunk_y = reader.parse(fields[1])?;
+        let mut tiles = reader.tiles(CHUNK_SIZE, CHUNK_SIZE)?;
+        read_regrowing(reader, &mut tiles)?;
+        map.insert_chunk(chunk_x, chunk_y, tiles);
+    }
+    Ok(map)
+}
+
+/*
+    Writes a grid of tiles one row per line.
+
+    @param out The save text.
+    @param tiles The tiles, indexed by x then y.
+*/
+fn write_tiles(out: &mut String, tiles: &[Vec<Tile>]) {
+    let height = tiles.first().map_or(0, |column| column.len());
+    for y in 0..height {
+        for column in tiles {
+            out.push(tile_to_char(&column[y]));
+        }
+        out.push('\n');
+    }
+}
+
+/*
+    Generates the save character of a tile. Textured tiles are upper case.
+
+    @param tile The tile.
+    @return char The character for the tile.
+*/
+fn tile_to_char(tile: &Tile) -> char {
+    let c = match tile.tile_type {
+        TileType::WoodFloor => 'w',
+        TileType::StoneWall => 's',
+        TileType::GrassFloor => 'g',
+        TileType::DirtFloor => 'd',
+        TileType::Tree => 't',
+        TileType::Air => 'a',
+        TileType::Water => 'r',
+        TileType::Wheel => 'h',
+        TileType::Portal => 'p',
+        TileType::Workbench => 'b',
+        TileType::Grune => 'n',
+        TileType::Lookout => 'l',
+        TileType::Cannon => 'c',
+    };
+    if tile.texture {
+        c.to_ascii_uppercase()
+    } else {
+        c
+    }
+}
+
+/*
+    Finds the tile for a save character.
+
+    @param c The character.
+    @return Option<Tile> The tile, if the character is known.
+*/
+fn char_to_tile(c: char) -> Option<Tile> {
+    let tile_type = match c.to_ascii_lowercase() {
+        'w' => TileType::WoodFloor,
+        's' => TileType::StoneWall,
+        'g' => TileType::GrassFloor,
+        'd' => TileType::DirtFloor,
+        't' => TileType::Tree,
+        'a' => TileType::Air,
+        'r' => TileType::Water,
+        'h' => TileType::Wheel,
+        'p' => TileType::Portal,
+        'b' => TileType::Workbench,
+        'n' => TileType::Grune,
+        'l' => TileType::Lookout,
+        'c' => TileType::Cannon,
+        _ => return None,
+    };
+    let mut tile = Tile::new(tile_type);
+    tile.texture = c.is_ascii_uppercase();
+    Some(tile)
+}
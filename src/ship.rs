@@ -1,10 +1,116 @@
-//! The Ship object manages the tile vector, position, movement, drawing, and generation of the player's ship.
+//! The Ship object manages the tile vector, position, movement, building, drawing, and generation of the player's ship.
 
+use blueprint::Blueprint;
+use item::{ItemType, ResourceType};
 use tile::*;
+use input::Action;
 use misc::*;
 use piston_window::*;
 use constants::*;
 use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fmt;
+
+const SHIP_MAX_SPEED: f64 = 360.0;
+const SHIP_MASS_PER_TILE: f64 = 10.0;
+const SHIP_DRAG_PER_TILE: f64 = 15.0;
+const SHIP_THRUST: f64 = 45000.0;
+const SHIP_TURN_RATE: f64 = PI;
+const SHIP_STOP_SPEED: f64 = 1.0;
+
+/**
+    Changes that can be made to the ship in build mode.
+
+    @variant AddFloor Adds a wooden floor tile to the ship's edge.
+    @variant RemoveFloor Removes a wooden floor tile.
+    @variant MoveWheel Moves the wheel onto a floor tile.
+    @variant MovePortal Moves the portal onto a floor tile.
+    @variant PlaceWorkbench Turns a floor tile into a workbench.
+    @variant PlaceLookout Turns a floor tile into a lookout.
+    @variant PlaceCannon Turns a floor tile into a cannon.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BuildAction {
+    AddFloor,
+    RemoveFloor,
+    MoveWheel,
+    MovePortal,
+    PlaceWorkbench,
+    PlaceLookout,
+    PlaceCannon,
+}
+
+impl BuildAction {
+    /*
+        Determines how much of its material the change costs.
+
+        @return u32 The number of logs or planks.
+    */
+    pub fn cost(&self) -> u32 {
+        match *self {
+            BuildAction::AddFloor | BuildAction::PlaceWorkbench => 1,
+            BuildAction::PlaceLookout => 2,
+            BuildAction::PlaceCannon => 4,
+            _ => 0,
+        }
+    }
+
+    /*
+        Determines what the change is paid for with. Lookouts and cannons are built from
+        planks, which are made at a workbench.
+
+        @return ResourceType The material.
+    */
+    pub fn material(&self) -> ResourceType {
+        match *self {
+            BuildAction::PlaceLookout | BuildAction::PlaceCannon => ResourceType::Planks,
+            _ => ResourceType::Logs,
+        }
+    }
+}
+
+/**
+    Reasons a change to the ship can be refused.
+
+    @variant NotBuilding The player isn't in build mode.
+    @variant NotOnEdge New floor must touch the ship.
+    @variant Occupied Something is already there.
+    @variant NotFloor The change needs a plain wooden floor tile.
+    @variant WouldSplit The ship would break into pieces.
+    @variant StandingThere The player is standing on the tile.
+    @variant Blocked New floor would be inside something solid below the ship.
+    @variant NoMaterials The player isn't carrying enough of the material to build with.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum BuildError {
+    NotBuilding,
+    NotOnEdge,
+    Occupied,
+    NotFloor,
+    WouldSplit,
+    StandingThere,
+    Blocked,
+    NoMaterials(ResourceType),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match *self {
+            BuildError::NotBuilding => "Not in build mode",
+            BuildError::NotOnEdge => "New floor must touch the ship",
+            BuildError::Occupied => "Something is already there",
+            BuildError::NotFloor => "Needs a wooden floor tile",
+            BuildError::WouldSplit => "The ship would break apart",
+            BuildError::StandingThere => "You're standing there",
+            BuildError::Blocked => "Something solid is in the way",
+            BuildError::NoMaterials(ref material) => {
+                let name = ItemType::Resource(material.clone()).name();
+                return write!(f, "Not enough {} to build", name);
+            }
+        };
+        write!(f, "{}", reason)
+    }
+}
 
 /**
     Implementation of the Ship object.
@@ -15,7 +121,17 @@ use std::collections::HashMap;
     @field self_vel_x Ship's horizontal velocity.
     @field self_vel_y Ship's vertical velocity.
     @field directions A vector of the ship's direction.
-    @field speed Ship's maximum speed when moving.
+    @field analog How far a stick steers the ship on each axis, overriding directions.
+    @field throttle How much of the wheel's thrust and top speed is used, from 0 to 1.
+    @field speed Ship's maximum speed when moving, in pixels per second.
+    @field heading The angle the ship is thrusting towards, in radians.
+    @field target_heading The angle the wheel is steering towards, if any.
+    @field mass Ship's mass, resisting acceleration.
+    @field thrust Force the wheel pushes the ship with.
+    @field drag Air resistance, slowing the ship down.
+    @field turn_rate How fast the heading turns, in radians per second.
+    @field last_x Ship's horizontal position before the latest update, for interpolation.
+    @field last_y Ship's vertical position before the latest update, for interpolation.
     @field width Ship's thiccness.
     @field height Ship's height.
 */
@@ -26,72 +142,352 @@ pub struct Ship {
     pub self_vel_x: f64,
     pub self_vel_y: f64,
     directions: Vec<Direction>,
-    speed: f64,
+    analog: Option<(f64, f64)>,
+    pub throttle: f64,
+    pub speed: f64,
+    pub heading: f64,
+    target_heading: Option<f64>,
+    pub mass: f64,
+    pub thrust: f64,
+    pub drag: f64,
+    pub turn_rate: f64,
+    pub last_x: f64,
+    pub last_y: f64,
     pub width: f64,
     pub height: f64,
 }
 
 impl Ship {
     /*
-        Ship constructor.
-        
-        @param ship_tiles The 2D tileset of the ship, from Game.
+        Ship constructor from a blueprint.
+
+        @param blueprint The blueprint of the ship.
         @return Ship Returns itself.
     */
-    pub fn new(ship_tiles: Vec<Vec<i32>>) -> Self {
-        // Populate ship's tileset with proper TileMaterial.
-        let air = Tile::new(TileType::Air);
-        let wood_floor = Tile::new(TileType::WoodFloor);
-        let control = Tile::new(TileType::Wheel);
-        let portal = Tile::new(TileType::Portal);
-        let w = ship_tiles[0].len();
-        let h = ship_tiles.len();
-        let mut temp_tiles = vec![vec![air.clone(); h]; w];
-
-        for i in 0..ship_tiles.len() {
-            for j in 0..ship_tiles[i].len() {
-                match ship_tiles[i][j] {
-                    0 => temp_tiles[j][i] = air.clone(),
-                    1 => temp_tiles[j][i] = wood_floor.clone(),
-                    2 => temp_tiles[j][i] = control.clone(),
-                    3 => temp_tiles[j][i] = portal.clone(),
-                    _ => {}
-                }
-            }
-        }
+    pub fn from_blueprint(blueprint: &Blueprint) -> Self {
+        let tiles = blueprint
+            .tiles
+            .iter()
+            .map(|column| column.iter().map(|t| Tile::new(t.clone())).collect())
+            .collect();
+        let mut ship = Ship::from_tiles(tiles);
+        ship.speed = blueprint.speed;
+        ship
+    }
+
+    /*
+        Ship constructor from an existing tileset, e.g. from saved data.
 
-        Ship {
-            tiles: temp_tiles,
+        @param tiles The 2D tileset of the ship, indexed by x then y.
+        @return Ship Returns itself.
+    */
+    pub fn from_tiles(tiles: Vec<Vec<Tile>>) -> Self {
+        let w = tiles.len();
+        let h = tiles[0].len();
+        let mut ship = Ship {
+            tiles: tiles,
             x: 0.0,
             y: 0.0,
             self_vel_x: 0.0,
             self_vel_y: 0.0,
             directions: vec![],
-            speed: 6.0,
+            analog: None,
+            throttle: 1.0,
+            speed: SHIP_MAX_SPEED,
+            heading: -PI / 2.0,
+            target_heading: None,
+            mass: 0.0,
+            thrust: 0.0,
+            drag: 0.0,
+            turn_rate: SHIP_TURN_RATE,
+            last_x: 0.0,
+            last_y: 0.0,
             width: w as f64,
             height: h as f64,
+        };
+        ship.tune();
+        ship
+    }
+
+    /*
+        Sets mass, thrust and drag from the ship's size. Bigger ships are heavier
+        and catch more air, and their wheel pushes harder but not proportionally,
+        so they are slower to get going and have a lower top speed.
+    */
+    pub fn tune(&mut self) {
+        let tile_count = self.tile_count() as f64;
+        self.mass = SHIP_MASS_PER_TILE * tile_count;
+        self.drag = SHIP_DRAG_PER_TILE * tile_count;
+        self.thrust = SHIP_THRUST * tile_count.sqrt();
+    }
+
+    /*
+        Counts the tiles that make up the ship, ignoring Air.
+
+        @return usize The number of tiles.
+    */
+    pub fn tile_count(&self) -> usize {
+        self.tiles
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .filter(|tile| tile.tile_type != TileType::Air)
+                    .count()
+            })
+            .sum()
+    }
+
+    /*
+        Finds the type of one of the ship's tiles. Positions outside the ship are Air.
+
+        @param x The tile's column, relative to the ship.
+        @param y The tile's row, relative to the ship.
+        @return TileType The tile's type.
+    */
+    pub fn tile_type_at(&self, x: i32, y: i32) -> TileType {
+        if x < 0 || y < 0 || x >= self.tiles.len() as i32 || y >= self.tiles[0].len() as i32 {
+            return TileType::Air;
+        }
+        self.tiles[x as usize][y as usize].tile_type.clone()
+    }
+
+    /*
+        Finds the tiles crew can be stationed on.
+
+        @return Vec<(usize, usize)> The stations' columns and rows, relative to the ship.
+    */
+    pub fn stations(&self) -> Vec<(usize, usize)> {
+        let mut stations = vec![];
+        for (x, column) in self.tiles.iter().enumerate() {
+            for (y, tile) in column.iter().enumerate() {
+                if tile.tile_type.is_station() {
+                    stations.push((x, y));
+                }
+            }
+        }
+        stations
+    }
+
+    /*
+        Finds the ship's portal, where the player and crew arrive aboard.
+
+        @return Option<(usize, usize)> The portal's column and row, relative to the ship.
+    */
+    pub fn portal(&self) -> Option<(usize, usize)> {
+        for (x, column) in self.tiles.iter().enumerate() {
+            for (y, tile) in column.iter().enumerate() {
+                if tile.tile_type == TileType::Portal {
+                    return Some((x, y));
+                }
+            }
+        }
+        None
+    }
+
+    /*
+        Makes a change to the ship if it keeps the ship in one piece.
+
+        @param action The change to make.
+        @param x The tile's column, relative to the ship. May be just outside it.
+        @param y The tile's row, relative to the ship. May be just outside it.
+        @return Result<(), BuildError> Why the change was refused, if it was.
+    */
+    pub fn build(&mut self, action: BuildAction, x: i32, y: i32) -> Result<(), BuildError> {
+        let current = self.tile_type_at(x, y);
+        match action {
+            BuildAction::AddFloor => {
+                if current != TileType::Air {
+                    return Err(BuildError::Occupied);
+                }
+                let touches_ship = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                    .iter()
+                    .any(|&(dx, dy)| self.tile_type_at(x + dx, y + dy) != TileType::Air);
+                if !touches_ship {
+                    return Err(BuildError::NotOnEdge);
+                }
+                self.change_tile(x, y, Tile::new(TileType::WoodFloor));
+            }
+            BuildAction::RemoveFloor => {
+                if current != TileType::WoodFloor {
+                    return Err(BuildError::NotFloor);
+                }
+                let mut without = self.tiles.clone();
+                without[x as usize][y as usize] = Tile::new(TileType::Air);
+                if !is_connected(&without) {
+                    return Err(BuildError::WouldSplit);
+                }
+                self.change_tile(x, y, Tile::new(TileType::Air));
+            }
+            BuildAction::MoveWheel | BuildAction::MovePortal => {
+                if current != TileType::WoodFloor {
+                    return Err(BuildError::NotFloor);
+                }
+                let tile_type = match action {
+                    BuildAction::MoveWheel => TileType::Wheel,
+                    _ => TileType::Portal,
+                };
+                // The old spot becomes plain floor.
+                for column in self.tiles.iter_mut() {
+                    for tile in column.iter_mut() {
+                        if tile.tile_type == tile_type {
+                            *tile = Tile::new(TileType::WoodFloor);
+                        }
+                    }
+                }
+                self.change_tile(x, y, Tile::new(tile_type));
+            }
+            BuildAction::PlaceWorkbench | BuildAction::PlaceLookout | BuildAction::PlaceCannon => {
+                if current != TileType::WoodFloor {
+                    return Err(BuildError::NotFloor);
+                }
+                let tile_type = match action {
+                    BuildAction::PlaceWorkbench => TileType::Workbench,
+                    BuildAction::PlaceLookout => TileType::Lookout,
+                    _ => TileType::Cannon,
+                };
+                self.change_tile(x, y, Tile::new(tile_type));
+            }
+        }
+        Ok(())
+    }
+
+    /*
+        Changes one of the ship's tiles, growing the ship if the tile is just outside it
+        and shrinking it if an edge is left empty. The ship's position moves with its
+        top left corner, so tiles stay where they are in the world.
+
+        @param x The tile's column, relative to the ship.
+        @param y The tile's row, relative to the ship.
+        @param tile The new tile.
+    */
+    pub fn change_tile(&mut self, x: i32, y: i32, tile: Tile) {
+        let mut x = x;
+        let mut y = y;
+
+        // Grow to fit the tile.
+        while x < 0 {
+            let height = self.tiles[0].len();
+            self.tiles.insert(0, vec![Tile::new(TileType::Air); height]);
+            self.x -= IMAGE_SIZE_SCALED;
+            x += 1;
+        }
+        while x >= self.tiles.len() as i32 {
+            let height = self.tiles[0].len();
+            self.tiles.push(vec![Tile::new(TileType::Air); height]);
+        }
+        while y < 0 {
+            for column in self.tiles.iter_mut() {
+                column.insert(0, Tile::new(TileType::Air));
+            }
+            self.y -= IMAGE_SIZE_SCALED;
+            y += 1;
         }
+        while y >= self.tiles[0].len() as i32 {
+            for column in self.tiles.iter_mut() {
+                column.push(Tile::new(TileType::Air));
+            }
+        }
+        self.tiles[x as usize][y as usize] = tile;
+
+        // Trim empty edges.
+        let is_air = |tile: &Tile| tile.tile_type == TileType::Air;
+        while self.tiles.len() > 1 && self.tiles[0].iter().all(&is_air) {
+            self.tiles.remove(0);
+            self.x += IMAGE_SIZE_SCALED;
+        }
+        while self.tiles.len() > 1 && self.tiles[self.tiles.len() - 1].iter().all(&is_air) {
+            self.tiles.pop();
+        }
+        while self.tiles[0].len() > 1 && self.tiles.iter().all(|column| is_air(&column[0])) {
+            for column in self.tiles.iter_mut() {
+                column.remove(0);
+            }
+            self.y += IMAGE_SIZE_SCALED;
+        }
+        while self.tiles[0].len() > 1
+            && self.tiles.iter().all(|column| is_air(&column[column.len() - 1]))
+        {
+            for column in self.tiles.iter_mut() {
+                column.pop();
+            }
+        }
+
+        self.width = self.tiles.len() as f64;
+        self.height = self.tiles[0].len() as f64;
+        self.tune();
+    }
+
+    /*
+        Applies a tick of flight: turns towards the wheel's heading, thrusts
+        along the current heading, then slows down from drag.
+
+        @param dt The time step in seconds.
+    */
+    pub fn update_velocity(&mut self, dt: f64) {
+        if let Some(target) = self.target_heading {
+            self.heading = turn_towards(self.heading, target, self.turn_rate * dt);
+            let acceleration = self.thrust * self.throttle / self.mass;
+            self.self_vel_x += self.heading.cos() * acceleration * dt;
+            self.self_vel_y += self.heading.sin() * acceleration * dt;
+        }
+
+        let drag = (1.0 - self.drag / self.mass * dt).max(0.0);
+        self.self_vel_x *= drag;
+        self.self_vel_y *= drag;
+
+        // Speed throttling, on the overall speed so diagonals aren't faster.
+        let speed = self.self_vel_x.hypot(self.self_vel_y);
+        let max_speed = match self.target_heading {
+            Some(_) => self.speed * self.throttle,
+            None => self.speed,
+        };
+        if speed > max_speed {
+            self.self_vel_x *= max_speed / speed;
+            self.self_vel_y *= max_speed / speed;
+        } else if speed < SHIP_STOP_SPEED && self.target_heading.is_none() {
+            self.self_vel_x = 0.0;
+            self.self_vel_y = 0.0;
+        }
+    }
+
+    /*
+        Remembers the current position, so drawing can interpolate from it.
+    */
+    pub fn store_last_position(&mut self) {
+        self.last_x = self.x;
+        self.last_y = self.y;
     }
 
     /*
-        Determines the position the ship is approaching.
-        Used for collision detection.
+        Calculates where to draw the ship between its last and current position.
 
-        @return f64 the x,y coordinates the ship is approaching.
+        @param alpha How far between the last and current update, from 0 to 1.
+        @return (f64, f64) The x and y position to draw at.
     */
-    pub fn x_to_be_location(&self) -> f64 {
-        self.x + self.self_vel_x
+    pub fn render_position(&self, alpha: f64) -> (f64, f64) {
+        (lerp(self.last_x, self.x, alpha), lerp(self.last_y, self.y, alpha))
     }
-    pub fn y_to_be_location(&self) -> f64 {
-        self.y + self.self_vel_y
+
+    /*
+        Steers the ship towards a heading, as crew at the wheel do.
+
+        @param heading The angle to thrust towards, in radians.
+    */
+    pub fn steer_towards(&mut self, heading: f64) {
+        self.directions = vec![];
+        self.analog = None;
+        self.throttle = 1.0;
+        self.target_heading = Some(heading);
     }
 
     /*
         Resets the ship's direction.
-        Currently unused.
     */
     pub fn reset_dir(&mut self) {
         self.directions = vec![];
+        self.analog = None;
+        self.target_heading = None;
     }
 
     /*
@@ -158,6 +554,38 @@ impl Ship {
                             graphics,
                         );
                     }
+                    TileType::Workbench | TileType::Lookout | TileType::Cannon => {
+                        let img = IMG_WOOD_FLOOR;
+                        image(
+                            textures.get(img).expect(&format!("Not found: {:?}", img)),
+                            context
+                                .transform
+                                .trans(
+                                    self.x + i as f64 * IMAGE_SIZE_SCALED,
+                                    self.y + j as f64 * IMAGE_SIZE_SCALED,
+                                )
+                                .trans(trans_x, trans_y)
+                                .scale(IMAGE_SCALE, IMAGE_SCALE),
+                            graphics,
+                        );
+                        let img = match self.tiles[i][j].tile_type {
+                            TileType::Lookout => IMG_LOOKOUT,
+                            TileType::Cannon => IMG_CANNON,
+                            _ => IMG_WORKBENCH,
+                        };
+                        image(
+                            textures.get(img).expect(&format!("Not found: {:?}", img)),
+                            context
+                                .transform
+                                .trans(
+                                    self.x + i as f64 * IMAGE_SIZE_SCALED,
+                                    self.y + j as f64 * IMAGE_SIZE_SCALED,
+                                )
+                                .trans(trans_x, trans_y)
+                                .scale(IMAGE_SCALE, IMAGE_SCALE),
+                            graphics,
+                        );
+                    }
                     TileType::Portal => {
                         let img = IMG_WOOD_FLOOR;
                         image(
@@ -197,111 +625,128 @@ impl Moveable for Ship {
     /*
         Handles input to the ship with player is controlling it.
 
-        @param state The Button State (e.g. pressed).
-        @param key Some key that is pressed (e.g. W, A, S, D).
+        @param action The action, of which only movement is used.
+        @param active Whether the action starts or stops.
     */
-    fn handle_input(&mut self, state: &ButtonState, key: &Option<Key>) {
-        match *key {
-            // Update movement / velocity based on key.
-            Some(Key::W) => {
-                let dir = Direction::N;
-                if let Some(index) = self.directions.iter().position(|&x| x == dir) {
-                    if *state == ButtonState::Release {
-                        self.directions.remove(index);
-                    }
-                } else {
-                    if *state == ButtonState::Press {
-                        self.directions.push(dir);
-                    }
-                }
-            }
-            Some(Key::A) => {
-                let dir = Direction::W;
-                if let Some(index) = self.directions.iter().position(|&x| x == dir) {
-                    if *state == ButtonState::Release {
-                        self.directions.remove(index);
-                    }
-                } else {
-                    if *state == ButtonState::Press {
-                        self.directions.push(dir);
-                    }
-                }
-            }
-            Some(Key::S) => {
-                let dir = Direction::S;
-                if let Some(index) = self.directions.iter().position(|&x| x == dir) {
-                    if *state == ButtonState::Release {
-                        self.directions.remove(index);
-                    }
-                } else {
-                    if *state == ButtonState::Press {
-                        self.directions.push(dir);
-                    }
-                }
-            }
-            Some(Key::D) => {
-                let dir = Direction::E;
-                if let Some(index) = self.directions.iter().position(|&x| x == dir) {
-                    if *state == ButtonState::Release {
-                        self.directions.remove(index);
-                    }
-                } else {
-                    if *state == ButtonState::Press {
-                        self.directions.push(dir);
-                    }
-                }
-            }
-            _ => {}
+    fn handle_input(&mut self, action: Action, active: bool) {
+        if let Some(dir) = action.direction() {
+            update_directions(&mut self.directions, dir, active);
         }
     }
 
+    /*
+        Handles a stick steering the ship, thrusting by how far it's pushed.
+
+        @param x How far to steer horizontally, from -1 to 1.
+        @param y How far to steer vertically, from -1 to 1.
+    */
+    fn handle_analog(&mut self, x: f64, y: f64) {
+        self.analog = if x == 0.0 && y == 0.0 { None } else { Some((x, y)) };
+    }
+
     /*
         Updates ship position using velocity.
+
+        @param dt The time step in seconds.
     */
-    fn update_position(&mut self) {
-        self.x += self.self_vel_x;
-        self.y += self.self_vel_y;
+    fn update_position(&mut self, dt: f64) {
+        self.x += self.self_vel_x * dt;
+        self.y += self.self_vel_y * dt;
     }
 
     /*
-        Updates ship velocity based on moving direction.
+        Updates where the ship is steered based on moving direction.
+        Velocity follows gradually in update_velocity.
     */
     fn update_self_velocity(&mut self) {
+        if let Some((x, y)) = self.analog {
+            self.target_heading = Some(f64::atan2(y, x));
+            self.throttle = x.hypot(y).min(1.0);
+            return;
+        }
+        self.throttle = 1.0;
+
         let mut dx = 0.0;
         let mut dy = 0.0;
 
         // Control which direction ship accelerates in.
         for dir in &self.directions {
             match *dir {
-                Direction::N => dy -= self.speed,
-                Direction::S => dy += self.speed,
-                Direction::W => dx -= self.speed,
-                Direction::E => dx += self.speed,
+                Direction::N => dy -= 1.0,
+                Direction::S => dy += 1.0,
+                Direction::W => dx -= 1.0,
+                Direction::E => dx += 1.0,
             }
         }
 
-        self.self_vel_x += dx;
-        self.self_vel_y += dy;
+        self.target_heading = if dx == 0.0 && dy == 0.0 {
+            None
+        } else {
+            Some(f64::atan2(dy, dx))
+        };
+    }
+}
 
-        // Speed throttling.
-        if self.self_vel_x > self.speed {
-            self.self_vel_x = self.speed;
-        }
-        if self.self_vel_x < -self.speed {
-            self.self_vel_x = -self.speed;
-        }
-        if self.self_vel_y > self.speed {
-            self.self_vel_y = self.speed;
+/*
+    Turns an angle towards another by at most some amount, the short way round.
+
+    @param angle The current angle, in radians.
+    @param target The angle to turn towards, in radians.
+    @param max_turn The most the angle can change.
+    @return f64 The new angle.
+*/
+fn turn_towards(angle: f64, target: f64, max_turn: f64) -> f64 {
+    let mut difference = (target - angle) % (2.0 * PI);
+    if difference > PI {
+        difference -= 2.0 * PI;
+    } else if difference < -PI {
+        difference += 2.0 * PI;
+    }
+    if difference.abs() <= max_turn {
+        target
+    } else {
+        angle + max_turn * difference.signum()
+    }
+}
+
+/*
+    Determines whether a ship's tiles, ignoring Air, form a single piece.
+
+    @param tiles The ship's tiles.
+    @return bool Whether every tile can be reached from every other.
+*/
+pub fn is_connected(tiles: &[Vec<Tile>]) -> bool {
+    let is_part = |x: i32, y: i32| {
+        x >= 0 && y >= 0 && (x as usize) < tiles.len() && (y as usize) < tiles[0].len()
+            && tiles[x as usize][y as usize].tile_type != TileType::Air
+    };
+    let mut parts = vec![];
+    for x in 0..tiles.len() as i32 {
+        for y in 0..tiles[0].len() as i32 {
+            if is_part(x, y) {
+                parts.push((x, y));
+            }
         }
-        if self.self_vel_y < -self.speed {
-            self.self_vel_y = -self.speed;
+    }
+    if parts.is_empty() {
+        return false;
+    }
+
+    // Flood fill from the first tile.
+    let mut reached = vec![parts[0]];
+    let mut to_visit = vec![parts[0]];
+    while let Some((x, y)) = to_visit.pop() {
+        for &(dx, dy) in &[(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let next = (x + dx, y + dy);
+            if is_part(next.0, next.1) && !reached.contains(&next) {
+                reached.push(next);
+                to_visit.push(next);
+            }
         }
     }
+    reached.len() == parts.len()
 }
 
-// TODO
-// fn change_tile() {}
-
 // pub fn update(&mut self) {
 //     self.update_position();
 // }
@@ -0,0 +1,125 @@
+//! Testing of the event bus and achievements.
+
+#[cfg(test)]
+
+mod tests {
+    use achievement::*;
+    use event::*;
+    use item::*;
+    use save::*;
+
+    /*
+        An observer that remembers every event it's told about.
+    */
+    struct Recorder {
+        seen: Vec<GameEvent>,
+    }
+
+    impl Observer for Recorder {
+        fn notify(&mut self, event: &GameEvent) {
+            self.seen.push(event.clone());
+        }
+    }
+
+    #[test]
+    fn bus_dispatches_in_order() {
+        let mut bus = EventBus::new();
+        let mut first = Recorder { seen: vec![] };
+        let mut second = Recorder { seen: vec![] };
+        bus.publish(GameEvent::DamageTaken(2));
+        bus.publish(GameEvent::EnemyDefeated);
+        bus.dispatch(&mut [&mut first, &mut second]);
+        let expected = vec![GameEvent::DamageTaken(2), GameEvent::EnemyDefeated];
+        assert_eq!(first.seen, expected);
+        assert_eq!(second.seen, expected);
+
+        // Events are only handed out once.
+        assert!(bus.pending.is_empty());
+        bus.dispatch(&mut [&mut first]);
+        assert_eq!(first.seen.len(), 2);
+    }
+
+    #[test]
+    fn achievements_unlock_once() {
+        let mut achievements = Achievements::new(vec![
+            Achievement::new("bruised", "Bruised", "Take 5 damage", Trigger::DamageTaken, 5),
+            Achievement::new("hoarder", "Hoarder", "Pick up 2 items", Trigger::Collected, 2),
+        ]);
+        let bruised = achievements.registry[0].clone();
+        let logs = GameEvent::Collected(ItemType::Resource(ResourceType::Logs));
+
+        // Damage counts by how much was taken.
+        achievements.notify(&GameEvent::DamageTaken(3));
+        achievements.notify(&logs);
+        assert_eq!(achievements.count(&bruised), 3);
+        assert!(achievements.take_unlocked().is_empty());
+        achievements.notify(&GameEvent::DamageTaken(4));
+        achievements.notify(&logs);
+        assert!(achievements.is_unlocked(&bruised));
+        assert_eq!(achievements.count(&bruised), 5);
+        assert_eq!(achievements.take_unlocked(), vec!["Bruised", "Hoarder"]);
+
+        // Unlocked achievements stay quiet.
+        achievements.notify(&GameEvent::DamageTaken(1));
+        achievements.notify(&logs);
+        assert!(achievements.take_unlocked().is_empty());
+    }
+
+    #[test]
+    fn achievements_file_round_trips() {
+        let mut achievements = Achievements::new(generate_achievements());
+        achievements.notify(&GameEvent::EnemyDefeated);
+        achievements.notify(&GameEvent::Recruited("Gil".to_string()));
+        let text = write_achievements(&achievements.counts);
+        let counts = read_achievements(&text).expect("achievements did not load");
+        assert_eq!(counts, achievements.counts);
+        assert_eq!(counts["sky_warden"], 1);
+
+        match read_achievements("AOE_ACHIEVEMENTS 1\nachievements 1\nachievement captain\n") {
+            Err(SaveError::Malformed(line, _)) => assert_eq!(line, 3),
+            _ => panic!("expected a malformed achievements error"),
+        }
+        for achievement in &achievements.registry {
+            assert!(!achievement.name.contains(' '), "{} can't be saved", achievement.name);
+        }
+    }
+
+    #[test]
+    fn world_publishes_events() {
+        use misc::*;
+        use test::fixtures::*;
+        use timestep::*;
+        use world::*;
+
+        let mut test_world = quiet_world();
+        let counted = |test_world: &World, name: &str| {
+            let achievements = &test_world.achievements;
+            let achievement = achievements
+                .registry
+                .iter()
+                .find(|achievement| achievement.name == name)
+                .unwrap();
+            achievements.count(achievement)
+        };
+
+        // The island below is discovered straight away.
+        test_world.update(FIXED_DT);
+        assert_eq!(counted(&test_world, "cartographer"), 1);
+
+        // Walking onto a new tile counts, staying on it doesn't.
+        let start = test_world.player_ship_tile();
+        test_world.execute(Command::StartMoving(Direction::E));
+        while test_world.player_ship_tile() == start {
+            test_world.update(FIXED_DT);
+        }
+        test_world.execute(Command::StopMoving(Direction::E));
+        test_world.update(FIXED_DT);
+        assert_eq!(counted(&test_world, "first_steps"), 1);
+
+        test_world.execute(Command::TakeDamage);
+        test_world.execute(Command::TakeDamage);
+        test_world.update(FIXED_DT);
+        assert_eq!(counted(&test_world, "thick_skinned"), 2);
+        assert!(test_world.events.pending.is_empty());
+    }
+}
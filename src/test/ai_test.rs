@@ -0,0 +1,62 @@
+//! Testing of the Ai object.
+
+#[cfg(test)]
+
+mod tests {
+
+    #[test]
+    fn ai_reacts_to_player() {
+        use ai::*;
+
+        let test_ai = Ai::new(100.0, 20.0, 1, 5);
+
+        // Out of sight, or unreachable, the creature keeps to itself.
+        assert_eq!(test_ai.decide(20, None), AiState::Wander);
+        assert_eq!(test_ai.decide(20, Some(150.0)), AiState::Wander);
+
+        assert_eq!(test_ai.decide(20, Some(80.0)), AiState::Chase);
+        assert_eq!(test_ai.decide(20, Some(10.0)), AiState::Attack);
+
+        // Badly hurt creatures run, even from further than they can see.
+        assert_eq!(test_ai.decide(5, Some(10.0)), AiState::Flee);
+        assert_eq!(test_ai.decide(5, Some(140.0)), AiState::Flee);
+        assert_eq!(test_ai.decide(5, Some(200.0)), AiState::Wander);
+    }
+
+    #[test]
+    fn ai_idles_and_wanders_on_timer() {
+        use ai::*;
+        use rand::*;
+
+        let mut rng = StdRng::from_seed(&[1usize][..]);
+        let mut test_ai = Ai::new(100.0, 20.0, 1, 5);
+
+        test_ai.enter(AiState::Wander, &mut rng);
+        assert!(test_ai.timer > 0.0);
+        assert_eq!(test_ai.decide(20, None), AiState::Wander);
+
+        test_ai.timer = 0.0;
+        let next = test_ai.decide(20, None);
+        assert_eq!(next, AiState::Idle);
+        test_ai.enter(next, &mut rng);
+        assert!(test_ai.timer > 0.0);
+
+        // Re-entering the same state doesn't restart it.
+        test_ai.timer = 0.5;
+        test_ai.enter(AiState::Idle, &mut rng);
+        assert_eq!(test_ai.timer, 0.5);
+    }
+
+    #[test]
+    fn directions_point_at_target() {
+        use ai::*;
+        use misc::*;
+
+        assert_eq!(
+            directions_towards(0.0, 0.0, 50.0, -50.0),
+            vec![Direction::E, Direction::N]
+        );
+        assert_eq!(directions_towards(0.0, 0.0, 0.0, 50.0), vec![Direction::S]);
+        assert!(directions_towards(10.0, 10.0, 10.0, 10.0).is_empty());
+    }
+}
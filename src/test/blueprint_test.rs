@@ -0,0 +1,76 @@
+//! Testing of ship blueprints.
+
+#[cfg(test)]
+
+mod tests {
+
+    const LEGEND: &str = "legend:\n. = air\n# = wood_floor\nW = wheel\nP = portal\n\nlayout:\n";
+
+    #[test]
+    fn default_ship_loads() {
+        use blueprint::*;
+        use tile::*;
+
+        let test_blueprint = Blueprint::load(DEFAULT_SHIP).expect("default ship did not load");
+
+        assert_eq!(test_blueprint.name, "Skiff");
+        assert_eq!(test_blueprint.tiles.len(), 7);
+        let (x, y) = test_blueprint.spawn;
+        assert!(test_blueprint.tiles[x][y] != TileType::Air);
+
+        // The copy built into the game, used when the file can't be read, is the same ship.
+        assert_eq!(Blueprint::built_in(), test_blueprint);
+
+        drop(test_blueprint);
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        use blueprint::*;
+        use test::fixtures::*;
+
+        let error_at = |text: &str| {
+            error_position(Blueprint::parse(text), |e| match *e {
+                BlueprintError::Malformed(line, column, _) => Some((line, column)),
+                _ => None,
+            })
+        };
+
+        // Unknown characters, ragged rows and bad settings.
+        assert_eq!(error_at(&format!("{}#W#\n#X#\n", LEGEND)), (9, 2));
+        assert_eq!(error_at(&format!("{}#W#\n#P\n", LEGEND)), (9, 3));
+        assert_eq!(error_at(&format!("speed = fast\n{}#W#\n#P#\n", LEGEND)), (1, 9));
+        assert_eq!(error_at("legend:\n# = lava\n"), (2, 5));
+
+        // Ships need one wheel, a portal and to be in one piece.
+        assert_eq!(error_at(&format!("{}#W#\n#W#\n", LEGEND)), (9, 2));
+        assert_eq!(error_at(&format!("{}#W#\n###\n", LEGEND)), (7, 1));
+        assert_eq!(error_at(&format!("{}#W.P\n", LEGEND)), (7, 1));
+        assert_eq!(
+            error_at(&format!("spawn = 0 1\n{}#W#\n.P.\n", LEGEND)),
+            (1, 9)
+        );
+    }
+
+    #[test]
+    fn ships_export_and_reload() {
+        use blueprint::*;
+        use ship::*;
+
+        let test_blueprint = Blueprint::parse(&format!(
+            "name = Raft\nspeed = 200\nspawn = 0 1\n{}.P.\n#W#\n",
+            LEGEND
+        ))
+        .unwrap();
+        let mut test_ship = Ship::from_blueprint(&test_blueprint);
+        assert_eq!(test_ship.speed, 200.0);
+
+        test_ship.build(BuildAction::AddFloor, 3, 1).unwrap();
+        let exported = Blueprint::from_ship(&test_ship, "Raft", (3, 1));
+        let reloaded = Blueprint::parse(&exported.to_text()).expect("export did not parse");
+
+        assert_eq!(reloaded, exported);
+        assert_eq!(reloaded.tiles.len(), 4);
+        assert_eq!(reloaded.speed, 200.0);
+    }
+}
@@ -0,0 +1,102 @@
+//! Testing of crafting recipes.
+
+#[cfg(test)]
+
+mod tests {
+    use crafting::*;
+    use item::*;
+    use test::fixtures::*;
+    use tile::*;
+    use world::*;
+
+    /*
+        Creates a world with the player standing at a workbench on the ship.
+    */
+    fn workbench_world() -> World {
+        let mut test_world = quiet_world();
+        let (x, y) = test_world.player_ship_tile();
+        test_world
+            .ship
+            .change_tile(x, y, Tile::new(TileType::Workbench));
+        test_world
+    }
+
+    /*
+        Finds a recipe in the registry by name.
+    */
+    fn recipe_index(test_world: &World, name: &str) -> usize {
+        test_world
+            .recipes
+            .iter()
+            .position(|recipe| recipe.name == name)
+            .expect("recipe not found")
+    }
+
+    #[test]
+    fn logs_become_planks_and_swords() {
+        let mut test_world = workbench_world();
+        let logs = test_world.item_prototypes["logs"].clone();
+        let grune = test_world.item_prototypes["grune"].clone();
+        for _ in 0..3 {
+            test_world.player.pickup_item(logs.clone());
+        }
+        test_world.player.pickup_item(grune);
+
+        let planks = recipe_index(&test_world, "planks");
+        test_world.execute(Command::Craft(planks));
+        assert_eq!(test_world.craft_error, None);
+        let inventory = &test_world.player.inventory;
+        assert_eq!(inventory.count(&logs.item_type), 2);
+        assert_eq!(
+            inventory.count(&ItemType::Resource(ResourceType::Planks)),
+            4
+        );
+
+        let sword = recipe_index(&test_world, "sword");
+        assert_eq!(test_world.craft(sword), Ok(()));
+        let inventory = &test_world.player.inventory;
+        assert_eq!(inventory.count(&logs.item_type), 0);
+        assert_eq!(
+            inventory.count(&ItemType::Interactable(InteractableType::Sword)),
+            1
+        );
+    }
+
+    #[test]
+    fn crafting_needs_materials_and_station() {
+        let mut test_world = workbench_world();
+        let planks = recipe_index(&test_world, "planks");
+        assert_eq!(test_world.craft(planks), Err(CraftError::MissingMaterials));
+        assert_eq!(test_world.craft(99), Err(CraftError::UnknownRecipe));
+
+        // Away from the workbench, even with logs.
+        let logs = test_world.item_prototypes["logs"].clone();
+        test_world.player.pickup_item(logs.clone());
+        test_world.player.x += 2.0 * ::constants::IMAGE_SIZE_SCALED;
+        assert_eq!(
+            test_world.craft(planks),
+            Err(CraftError::NeedsStation(TileType::Workbench))
+        );
+        assert_eq!(test_world.player.inventory.count(&logs.item_type), 1);
+    }
+
+    #[test]
+    fn full_inventory_keeps_materials() {
+        let mut test_world = workbench_world();
+        let logs = test_world.item_prototypes["logs"].clone();
+        let bisket = test_world.item_prototypes["bisket"].clone();
+
+        // A stack of logs, and every other slot taken.
+        test_world.player.pickup_item(logs.clone());
+        test_world.player.pickup_item(logs.clone());
+        let slots = test_world.player.inventory.slots.len() as u32;
+        for _ in 0..(slots - 1) * bisket.item_type.stack_limit() {
+            test_world.player.pickup_item(bisket.clone());
+        }
+        let before = test_world.player.inventory.clone();
+
+        let planks = recipe_index(&test_world, "planks");
+        assert_eq!(test_world.craft(planks), Err(CraftError::InventoryFull));
+        assert_eq!(test_world.player.inventory, before);
+    }
+}
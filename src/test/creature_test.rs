@@ -16,7 +16,7 @@ mod tests {
 
         test_player.self_vel_x = 5.0;
         test_player.self_vel_y = 7.0;
-        test_player.update_position();
+        test_player.update_position(1.0);
 
         // Player's position should move according to its velocity.
         assert_eq!(test_player.x, 5.0);
@@ -25,7 +25,7 @@ mod tests {
         test_player.other_vel_x = 4.0;
         test_player.other_vel_y = 4.0;
 
-        test_player.update_position();
+        test_player.update_position(1.0);
 
         // Player's position should move according to its velocity and velocities acting on it.
         assert_eq!(test_player.x, 14.0);
@@ -34,7 +34,7 @@ mod tests {
         test_player.self_vel_x = 0.0;
         test_player.self_vel_y = 0.0;
 
-        test_player.update_position();
+        test_player.update_position(1.0);
 
         // Player's position should move according only to velocities acting on it.
         assert_eq!(test_player.x, 18.0);
@@ -0,0 +1,193 @@
+//! Testing of crew stations and the tasks crew do at them.
+
+#[cfg(test)]
+
+mod tests {
+    use constants::*;
+    use crew::*;
+    use test::fixtures::*;
+    use tile::*;
+    use timestep::*;
+    use world::*;
+
+    /*
+        Creates a world in open sky with a crew member aboard, and a lookout,
+        workbench and cannon on deck next to the wheel at (3, 2).
+    */
+    fn crewed_world(role: Role) -> World {
+        let mut test_world = quiet_world();
+        cover_map(&mut test_world, 40, TileType::Air);
+        test_world.ship.change_tile(1, 2, Tile::new(TileType::Lookout));
+        test_world.ship.change_tile(2, 2, Tile::new(TileType::Workbench));
+        test_world.ship.change_tile(4, 2, Tile::new(TileType::Cannon));
+
+        let mut member = CrewMember::new("Abel", role, test_world.player.x, test_world.player.y);
+        member.recruited = true;
+        member.on_ship = true;
+        test_world.crew.push(member);
+        test_world
+    }
+
+    /*
+        Gives a crew member a station by cycling through them.
+    */
+    fn station_at(test_world: &mut World, member: usize, station: (usize, usize)) {
+        for _ in 0..10 {
+            if test_world.crew[member].station == Some(station) {
+                return;
+            }
+            test_world.execute(Command::CycleStation(member));
+        }
+        panic!("station not reached");
+    }
+
+    /*
+        Runs the world and the crew's tasks for some seconds.
+    */
+    fn run(test_world: &mut World, seconds: f64) {
+        for _ in 0..(seconds / FIXED_DT).ceil() as usize {
+            test_world.update(FIXED_DT);
+            test_world.run_crew_tasks(FIXED_DT);
+        }
+    }
+
+    #[test]
+    fn stations_are_shared_out() {
+        let mut test_world = crewed_world(Role::Deckhand);
+        assert_eq!(test_world.ship.stations().len(), 4);
+
+        // Cycling goes through every station and back off duty.
+        let mut seen = vec![];
+        for _ in 0..5 {
+            test_world.execute(Command::CycleStation(0));
+            seen.push(test_world.crew[0].station);
+        }
+        assert_eq!(seen[4], None);
+        assert!(seen[..4].iter().all(|station| station.is_some()));
+
+        // A second crew member skips the taken station.
+        let mut other = test_world.crew[0].clone();
+        other.station = None;
+        test_world.crew.push(other);
+        station_at(&mut test_world, 0, (3, 2));
+        for _ in 0..4 {
+            test_world.execute(Command::CycleStation(1));
+            assert_ne!(test_world.crew[1].station, Some((3, 2)));
+        }
+
+        // Stationed crew stand on their station, and leave it if it's built over.
+        run(&mut test_world, FIXED_DT);
+        assert_eq!(test_world.crew[0].creature.x, test_world.ship.x + 3.0 * IMAGE_SIZE_SCALED);
+        test_world.ship.change_tile(3, 2, Tile::new(TileType::WoodFloor));
+        run(&mut test_world, FIXED_DT);
+        assert_eq!(test_world.crew[0].station, None);
+    }
+
+    #[test]
+    fn stations_stay_put_when_the_ship_grows() {
+        use misc::*;
+        use ship::*;
+
+        let mut test_world = crewed_world(Role::Lookout);
+        station_at(&mut test_world, 0, (1, 2));
+
+        // Build floor off the ship's left edge, below the lookout.
+        test_world.player.x = test_world.ship.x + 3.0 * IMAGE_SIZE_SCALED;
+        test_world.player.y = test_world.ship.y + 2.0 * IMAGE_SIZE_SCALED;
+        test_world.execute(Command::ToggleBuildMode);
+        for dir in &[Direction::W, Direction::W, Direction::W, Direction::W, Direction::S] {
+            test_world.execute(Command::StartMoving(*dir));
+            test_world.execute(Command::StopMoving(*dir));
+        }
+        assert_eq!(test_world.build_cursor, Some((-1, 3)));
+        let logs = test_world.item_prototypes["logs"].clone();
+        test_world.player.pickup_item(logs);
+        test_world.execute(Command::Build(BuildAction::AddFloor));
+        assert_eq!(test_world.build_error, None);
+
+        // The new column pushes the lookout one column right, and the crew member with it.
+        assert_eq!(test_world.crew[0].station, Some((2, 2)));
+        assert_eq!(test_world.ship.tile_type_at(2, 2), TileType::Lookout);
+        run(&mut test_world, 1.0);
+        assert_eq!(test_world.crew[0].station, Some((2, 2)));
+    }
+
+    #[test]
+    fn lookout_spots_islands() {
+        let mut test_world = crewed_world(Role::Lookout);
+        station_at(&mut test_world, 0, (1, 2));
+        assert!(test_world.map.discovered_islands().is_empty());
+
+        run(&mut test_world, LOOKOUT_INTERVAL / SKILLED_WORK_RATE + 0.1);
+        assert!(!test_world.map.discovered_islands().is_empty());
+        assert!(test_world.crew_reports[0].starts_with("Abel spotted"));
+    }
+
+    #[test]
+    fn workbench_crafts_queue() {
+        let mut test_world = crewed_world(Role::Gunner);
+        station_at(&mut test_world, 0, (2, 2));
+        let logs = test_world.item_prototypes["logs"].clone();
+        test_world.player.pickup_item(logs);
+
+        test_world.execute(Command::QueueCraft(0));
+        test_world.execute(Command::QueueCraft(1));
+        test_world.execute(Command::QueueCraft(9));
+        assert_eq!(test_world.craft_queue, vec![0, 1]);
+        assert!(test_world.craft_error.is_some());
+
+        // Unskilled crew take the full time.
+        run(&mut test_world, CRAFT_TIME - 0.5);
+        assert_eq!(test_world.craft_queue.len(), 2);
+        run(&mut test_world, 1.0);
+        assert_eq!(test_world.craft_queue, vec![1]);
+        assert_eq!(test_world.crew_reports, vec!["Abel crafted planks".to_string()]);
+
+        // The sword needs materials nobody has.
+        run(&mut test_world, CRAFT_TIME);
+        assert!(test_world.craft_queue.is_empty());
+        assert!(test_world.crew_reports[1].starts_with("Abel couldn't craft sword"));
+    }
+
+    #[test]
+    fn wheel_steers_to_waypoint() {
+        let mut test_world = crewed_world(Role::Deckhand);
+        station_at(&mut test_world, 0, (3, 2));
+        let (x, y) = test_world.ship_center();
+        test_world.waypoint = Some((x + 20.0 * IMAGE_SIZE_SCALED, y));
+
+        for _ in 0..20 {
+            run(&mut test_world, 1.0);
+            if test_world.waypoint.is_none() {
+                break;
+            }
+        }
+        assert_eq!(test_world.waypoint, None);
+        assert!(test_world.ship.x > x);
+        assert_eq!(
+            test_world.crew_reports,
+            vec!["Abel reached the waypoint".to_string()]
+        );
+    }
+
+    #[test]
+    fn cannon_fires_at_enemies() {
+        let mut test_world = crewed_world(Role::Gunner);
+        station_at(&mut test_world, 0, (4, 2));
+        let mut enemy = test_world.enemy_prototypes["wisp"].clone();
+        enemy.ai = None;
+        enemy.x = test_world.ship.x + 6.0 * IMAGE_SIZE_SCALED;
+        enemy.y = test_world.ship.y + 2.0 * IMAGE_SIZE_SCALED;
+        test_world.npcs = vec![enemy];
+
+        // The cannon waits loaded for a target, then fires.
+        run(&mut test_world, CANNON_INTERVAL / SKILLED_WORK_RATE + 0.1);
+        assert_eq!(test_world.npcs[0].health, 10 - CANNON_DAMAGE);
+        run(&mut test_world, CANNON_INTERVAL / SKILLED_WORK_RATE);
+        assert!(test_world.npcs.is_empty());
+        assert_eq!(
+            test_world.crew_reports,
+            vec!["Abel shot down an enemy".to_string()]
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! Testing of dialogue trees and conversations, walked without a window.
+
+#[cfg(test)]
+
+mod tests {
+    use constants::*;
+    use crew::*;
+    use dialogue::*;
+    use item::*;
+    use misc::*;
+    use test::fixtures::*;
+    use world::*;
+
+    const TEST_DIALOGUE: &str = "\
+start = hello
+
+node hello
+speaker = $name
+line = Hello.
+line = Need anything?
+choice = [flag helped] Thanks again. -> end
+choice = [has logs 2] Take some logs. -> logs
+choice = Bye. -> end
+
+node logs
+speaker = $name
+line = Here, have a sword for them.
+effect = take logs 2
+effect = give sword 1
+effect = set helped
+next = hello
+";
+
+    /*
+        Creates a world with an islander next to the player, ready to talk to.
+    */
+    fn talking_world() -> World {
+        let mut test_world = quiet_world();
+        test_world.player_location = PlayerLocation::InWorld;
+        let islander = CrewMember::new(
+            "Brin",
+            Role::Gunner,
+            test_world.player.x + IMAGE_SIZE_SCALED,
+            test_world.player.y,
+        );
+        test_world.crew.push(islander);
+        test_world.dialogues.insert(
+            "test".to_string(),
+            Dialogue::parse("test", TEST_DIALOGUE).unwrap(),
+        );
+        test_world
+    }
+
+    /*
+        Walks the conversation being had until it ends, picking the given choices in
+        turn whenever choices are offered.
+
+        @return Vec<String> The lines shown, with names filled in.
+    */
+    fn talk_through(test_world: &mut World, picks: &[usize]) -> Vec<String> {
+        let mut picks = picks.iter();
+        let mut shown = vec![];
+        for _ in 0..100 {
+            let line = match (test_world.conversation.as_ref(), test_world.conversation_node()) {
+                (Some(conversation), Some(node)) => {
+                    let last = conversation.line + 1 == node.lines.len();
+                    (conversation.fill_in(&node.lines[conversation.line]), last)
+                }
+                _ => return shown,
+            };
+            shown.push(line.0);
+            if line.1 && !test_world.conversation_choices().is_empty() {
+                let pick = *picks.next().expect("ran out of choices");
+                for _ in 0..pick {
+                    test_world.execute(Command::StartMoving(Direction::S));
+                    test_world.execute(Command::StopMoving(Direction::S));
+                }
+            }
+            test_world.execute(Command::Interact);
+        }
+        panic!("conversation never ended");
+    }
+
+    #[test]
+    fn dialogue_parses() {
+        let dialogue = Dialogue::parse("test", TEST_DIALOGUE).expect("dialogue did not parse");
+        assert_eq!(dialogue.start, "hello");
+        let hello = &dialogue.nodes["hello"];
+        assert_eq!(hello.lines.len(), 2);
+        assert_eq!(hello.choices[1].text, "Take some logs.");
+        assert_eq!(
+            hello.choices[1].conditions,
+            vec![Condition::HasItem(ItemType::Resource(ResourceType::Logs), 2)]
+        );
+        assert_eq!(dialogue.nodes["logs"].effects.len(), 3);
+        assert_eq!(dialogue.nodes["logs"].next, Some("hello".to_string()));
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let line_of = |text: &str| {
+            error_position(Dialogue::parse("bad", text), |e| match *e {
+                DialogueError::Malformed(_, line, _) => Some(line),
+                _ => None,
+            })
+        };
+        assert_eq!(line_of("start = a\nnode a\nline = Hi.\nchoice = Go. -> b\n"), 4);
+        assert_eq!(line_of("start = a\nnode a\nline = Hi.\neffect = dance\n"), 4);
+        assert_eq!(line_of("start = a\n\nnode a\nline = Hi.\nchoice = [has gold 1] Go. -> end\n"), 5);
+        assert_eq!(line_of("start = b\nnode a\nline = Hi.\n"), 1);
+        assert_eq!(line_of("start = a\nnode a\nspeaker = Nobody\n"), 2);
+
+        // Nodes nobody can get to are probably mistakes.
+        assert_eq!(line_of("start = a\nnode a\nline = Hi.\nnode b\nline = Bye.\n"), 4);
+    }
+
+    #[test]
+    fn asset_dialogues_are_complete() {
+        let dialogues = match Dialogue::load_all() {
+            Ok(dialogues) => dialogues,
+            Err(DialogueError::Malformed(name, line, reason)) => {
+                panic!("{}.dlg line {}: {}", name, line, reason)
+            }
+            Err(DialogueError::Io(_)) => panic!("dialogue folder could not be read"),
+        };
+        assert!(dialogues.contains_key("islander"));
+
+        // The world loads them without complaint.
+        let test_world = quiet_world();
+        assert!(test_world.load_errors.is_empty());
+        assert_eq!(test_world.dialogues.len(), dialogues.len());
+    }
+
+    #[test]
+    fn choices_follow_conditions_and_effects() {
+        let mut test_world = talking_world();
+        assert!(test_world.start_conversation("test", Some(0)));
+        assert!(!test_world.start_conversation("missing", None));
+
+        // Without logs, only goodbye is offered.
+        let shown = talk_through(&mut test_world, &[0]);
+        assert_eq!(shown, vec!["Hello.", "Need anything?"]);
+
+        // With logs, they're swapped for a sword, and the conversation comes back round.
+        let logs = test_world.item_prototypes["logs"].clone();
+        test_world.player.pickup_item(logs.clone());
+        test_world.player.pickup_item(logs);
+        test_world.start_conversation("test", Some(0));
+        let shown = talk_through(&mut test_world, &[0, 0]);
+        assert_eq!(shown[2], "Here, have a sword for them.");
+        assert_eq!(shown.len(), 5);
+        assert_eq!(test_world.player.inventory.count(&ItemType::Resource(ResourceType::Logs)), 0);
+        assert_eq!(
+            test_world.player.inventory.count(&ItemType::Interactable(InteractableType::Sword)),
+            1
+        );
+        assert!(test_world.flags.contains("helped"));
+
+        // The player doesn't move while talking.
+        test_world.start_conversation("test", Some(0));
+        let x = test_world.player.x;
+        test_world.execute(Command::StartMoving(Direction::E));
+        test_world.update(1.0);
+        assert_eq!(test_world.player.x, x);
+    }
+
+    #[test]
+    fn islanders_join_through_dialogue() {
+        let mut test_world = talking_world();
+        let bisket = test_world.item_prototypes["bisket"].clone();
+        test_world.player.pickup_item(bisket);
+
+        // E talks to the islander in reach. Once fed, the bisket offer is gone.
+        test_world.execute(Command::Interact);
+        assert!(test_world.conversation.is_some());
+        let shown = talk_through(&mut test_world, &[1, 2]);
+        assert_eq!(shown[0], "Ahoy! A ship, out here?");
+        assert!(test_world.flags.contains("fed_islander"));
+        assert_eq!(
+            test_world.player.inventory.count(&ItemType::Resource(ResourceType::Grune)),
+            1
+        );
+        assert!(!test_world.crew[0].recruited);
+
+        // Fed, they're asked how the bisket was, then asked to join.
+        test_world.execute(Command::Interact);
+        let shown = talk_through(&mut test_world, &[1, 0]);
+        assert_eq!(shown[2], "Stale, but better than grune every day.");
+        assert!(test_world.crew[0].recruited);
+        assert_eq!(test_world.crew[0].name, "Brin");
+    }
+}
@@ -0,0 +1,177 @@
+//! Testing of input actions and key bindings.
+
+#[cfg(test)]
+
+mod tests {
+    use input::*;
+    use misc::*;
+    use piston_window::Key;
+
+    #[test]
+    fn defaults_bind_every_action() {
+        let bindings = Bindings::new();
+        for action in ACTIONS.iter() {
+            assert!(!bindings.keys(*action).is_empty(), "{} is unbound", action.name());
+            for &key in bindings.keys(*action) {
+                assert_eq!(bindings.action_for(key), Some(*action));
+                assert_eq!(bindings.check(*action, key), Ok(()));
+            }
+            assert_eq!(Action::from_name(action.name()), Some(*action));
+        }
+        assert_eq!(bindings.describe(Action::OpenMenu), "Tab/Return");
+        assert_eq!(key_from_name("LeftBracket"), Some(Key::LeftBracket));
+        assert_eq!(key_from_name("Up"), Some(Key::Up));
+        assert_eq!(key_from_name("Banana"), None);
+    }
+
+    #[test]
+    fn bindings_round_trip() {
+        let mut bindings = Bindings::new();
+        bindings.rebind(Action::Interact, Key::F).unwrap();
+        bindings.rebind(Action::MoveNorth, Key::Up).unwrap();
+        let parsed = Bindings::parse(&bindings.write()).expect("bindings did not parse");
+        assert_eq!(parsed, bindings);
+
+        // Actions left out keep their defaults, unless another action took them.
+        let parsed = Bindings::parse("; Mine\ninteract = W\n").unwrap();
+        assert_eq!(parsed.action_for(Key::W), Some(Action::Interact));
+        assert!(parsed.keys(Action::MoveNorth).is_empty());
+        assert_eq!(parsed.keys(Action::Throw), &[Key::T]);
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        use test::fixtures::*;
+
+        let line_of = |text: &str| {
+            error_position(Bindings::parse(text), |e| match *e {
+                BindingsError::Malformed(line, _) => Some(line),
+                _ => None,
+            })
+        };
+        assert_eq!(line_of("interact = E\n\ndance = X\n"), 3);
+        assert_eq!(line_of("interact = Banana\n"), 1);
+        assert_eq!(line_of("interact = E\nthrow = E\n"), 2);
+        assert_eq!(line_of("throw = T\nthrow = Y\n"), 2);
+        assert_eq!(line_of("throw T\n"), 1);
+        assert_eq!(line_of("interact = D1\n"), 1);
+    }
+
+    #[test]
+    fn rebinding_detects_conflicts() {
+        let mut bindings = Bindings::new();
+        assert_eq!(
+            bindings.rebind(Action::Throw, Key::E),
+            Err(BindError::Taken(Key::E, Action::Interact))
+        );
+        assert_eq!(bindings.rebind(Action::Throw, Key::D3), Err(BindError::Reserved(Key::D3)));
+        assert_eq!(
+            bindings.rebind(Action::Throw, Key::P),
+            Err(BindError::Taken(Key::P, Action::PlotCourse))
+        );
+        assert_eq!(bindings.keys(Action::Throw), &[Key::T]);
+
+        // Menu shortcuts are only free for actions used in game.
+        assert_eq!(bindings.rebind(Action::OpenMenu, Key::I), Err(BindError::Reserved(Key::I)));
+        assert_eq!(bindings.rebind(Action::Throw, Key::I), Ok(()));
+        assert_eq!(bindings.action_for(Key::T), None);
+
+        // Rebinding replaces the keys an action had.
+        assert_eq!(bindings.rebind(Action::OpenMenu, Key::Tab), Ok(()));
+        assert_eq!(bindings.action_for(Key::Return), None);
+    }
+
+    #[test]
+    fn creature_and_ship_move_by_action() {
+        use blueprint::*;
+        use creature::*;
+        use ship::*;
+
+        let mut creature = Creature::new();
+        creature.handle_input(Action::MoveEast, true);
+        creature.handle_input(Action::Interact, true);
+        assert_eq!(creature.directions, vec![Direction::E]);
+        creature.handle_input(Action::MoveEast, false);
+        assert!(creature.directions.is_empty());
+
+        // Only movement steers the ship.
+        let blueprint = Blueprint::load(DEFAULT_SHIP).unwrap();
+        let mut ship = Ship::from_blueprint(&blueprint);
+        ship.handle_input(Action::Throw, true);
+        ship.update_self_velocity();
+        ship.update_velocity(1.0 / 60.0);
+        assert_eq!((ship.self_vel_x, ship.self_vel_y), (0.0, 0.0));
+        ship.handle_input(Action::moving(Direction::N), true);
+        ship.update_self_velocity();
+        ship.update_velocity(1.0 / 60.0);
+        assert!(ship.self_vel_y < 0.0);
+    }
+
+    #[test]
+    fn gamepad_buttons_give_actions() {
+        assert_eq!(gamepad_action(0), Some(Action::Interact));
+        assert_eq!(gamepad_action(1), Some(Action::ToggleHands));
+        assert_eq!(gamepad_action(6), Some(Action::OpenMenu));
+        assert_eq!(gamepad_action(11), Some(Action::MoveNorth));
+        assert_eq!(gamepad_action(14), Some(Action::MoveEast));
+        assert_eq!(gamepad_action(7), None);
+    }
+
+    #[test]
+    fn stick_has_a_dead_zone() {
+        use piston_window::ControllerAxisArgs;
+
+        let mut gamepad = Gamepad::new();
+        assert_eq!(gamepad.move_axis(ControllerAxisArgs::new(0, 0, 0.1)), None);
+        assert_eq!(gamepad.move_axis(ControllerAxisArgs::new(0, 0, 1.0)), Some((1.0, 0.0)));
+        assert_eq!(gamepad.move_axis(ControllerAxisArgs::new(0, 1, 0.0)), None);
+
+        // Pushing part way moves part of the way, never more than all of it.
+        let (x, y) = gamepad.move_axis(ControllerAxisArgs::new(0, 0, -0.6)).unwrap();
+        assert!((x + 0.5).abs() < 1e-9 && y == 0.0);
+        let (x, y) = apply_dead_zone((1.0, 1.0));
+        assert!((x.hypot(y) - 1.0).abs() < 1e-9);
+
+        // Other axes, like the right stick, are left alone.
+        assert_eq!(gamepad.move_axis(ControllerAxisArgs::new(0, 2, 1.0)), None);
+        assert_eq!(gamepad.move_axis(ControllerAxisArgs::new(0, 0, 0.0)), Some((0.0, 0.0)));
+    }
+
+    #[test]
+    fn stick_moves_by_how_far_it_is_pushed() {
+        use blueprint::*;
+        use map::*;
+        use piston_window::ControllerAxisArgs;
+        use ship::*;
+        use world::*;
+
+        // Half a push walks at half speed, and letting go stops.
+        let mut test_world = World::new(Map::with_seed(0));
+        let mut gamepad = Gamepad::new();
+        for &(axis, position) in &[(0, 0.6), (1, 0.0)] {
+            if let Some((x, y)) = gamepad.move_axis(ControllerAxisArgs::new(0, axis, position)) {
+                test_world.execute(Command::MoveAnalog(x, y));
+            }
+        }
+        let speed = test_world.player.speed;
+        assert!((test_world.player.self_vel_x - speed / 2.0).abs() < 1e-9);
+        assert_eq!(test_world.player.self_vel_y, 0.0);
+        test_world.execute(Command::MoveAnalog(0.0, 0.0));
+        assert_eq!(test_world.player.self_vel_x, 0.0);
+
+        // The ship's top speed follows the push too.
+        let blueprint = Blueprint::load(DEFAULT_SHIP).unwrap();
+        let mut ship = Ship::from_blueprint(&blueprint);
+        ship.handle_analog(0.0, -0.5);
+        ship.update_self_velocity();
+        for _ in 0..600 {
+            ship.update_velocity(1.0 / 60.0);
+        }
+        assert!(ship.self_vel_y < 0.0);
+        assert!(-ship.self_vel_y <= ship.speed / 2.0 + 1e-9);
+        ship.handle_analog(0.0, 0.0);
+        ship.handle_input(Action::MoveNorth, true);
+        ship.update_self_velocity();
+        assert_eq!(ship.throttle, 1.0);
+    }
+}
@@ -0,0 +1,96 @@
+//! Testing of the Inventory object.
+
+#[cfg(test)]
+
+mod tests {
+
+    #[test]
+    fn items_stack_up_to_limit() {
+        use inventory::*;
+        use item::*;
+
+        let mut test_inventory = Inventory::new(3, 1000.0);
+        let bisket = Item::new(ItemType::Food(FoodType::Bisket), 1, true, 1.0);
+        let sword = Item::new(ItemType::Interactable(InteractableType::Sword), 10, true, 5.0);
+
+        for _ in 0..12 {
+            assert!(test_inventory.add(bisket.clone()));
+        }
+        assert_eq!(test_inventory.count(&bisket.item_type), 12);
+        assert_eq!(test_inventory.slots[0].as_ref().unwrap().count, 10);
+        assert_eq!(test_inventory.slots[1].as_ref().unwrap().count, 2);
+
+        // Swords don't stack, so the last slot fills up.
+        assert!(test_inventory.add(sword.clone()));
+        assert!(!test_inventory.can_add(&sword));
+        assert!(!test_inventory.add(sword));
+
+        drop(test_inventory);
+    }
+
+    #[test]
+    fn weight_is_limited() {
+        use inventory::*;
+        use item::*;
+
+        let mut test_inventory = Inventory::new(INVENTORY_SLOTS, 20.0);
+        let logs = Item::new(ItemType::Resource(ResourceType::Logs), 5, true, 8.0);
+
+        assert!(test_inventory.add(logs.clone()));
+        assert!(test_inventory.add(logs.clone()));
+        assert!(!test_inventory.add(logs.clone()));
+        assert_eq!(test_inventory.weight(), 16.0);
+
+        drop(test_inventory);
+    }
+
+    #[test]
+    fn hand_slot_is_used() {
+        use creature::*;
+        use item::*;
+
+        let mut test_creature = Creature::new();
+        let bisket = Item::new(ItemType::Food(FoodType::Bisket), 1, true, 1.0);
+        let logs = Item::new(ItemType::Resource(ResourceType::Logs), 5, true, 8.0);
+        test_creature.pickup_item(logs.clone());
+        test_creature.pickup_item(bisket.clone());
+        test_creature.pickup_item(bisket.clone());
+        test_creature.health = 1;
+
+        // Logs are in hand, so nothing is eaten.
+        test_creature.use_item();
+        assert_eq!(test_creature.health, 1);
+
+        test_creature.inventory.select(1);
+        test_creature.use_item();
+        assert_eq!(test_creature.health, 2);
+        assert_eq!(test_creature.inventory.count(&bisket.item_type), 1);
+
+        // Dropped items land where the creature stands.
+        test_creature.x = 30.0;
+        let dropped = test_creature.drop_item().expect("nothing was dropped");
+        assert_eq!(dropped.x, 30.0);
+        assert!(test_creature.inventory.held().is_none());
+        assert!(test_creature.drop_item().is_none());
+    }
+
+    #[test]
+    fn removing_needs_enough() {
+        use inventory::*;
+        use item::*;
+
+        let mut test_inventory = Inventory::new(INVENTORY_SLOTS, MAX_CARRY_WEIGHT);
+        let grune = Item::new(ItemType::Resource(ResourceType::Grune), 1, true, 0.5);
+        for _ in 0..25 {
+            test_inventory.add(grune.clone());
+        }
+
+        assert!(!test_inventory.remove(&grune.item_type, 26));
+        assert_eq!(test_inventory.count(&grune.item_type), 25);
+        assert!(test_inventory.remove(&grune.item_type, 22));
+        assert_eq!(test_inventory.count(&grune.item_type), 3);
+        assert!(test_inventory.slots[0].is_none());
+
+        drop(test_inventory);
+    }
+}
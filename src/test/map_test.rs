@@ -0,0 +1,222 @@
+//! Testing of the Map object.
+
+#[cfg(test)]
+
+mod tests {
+
+    #[test]
+    fn same_seed_same_tiles() {
+        use map::*;
+
+        let mut first_map = Map::with_seed(1234);
+        let mut second_map = Map::with_seed(1234);
+        first_map.load_around(0.0, 0.0);
+        second_map.load_around(0.0, 0.0);
+
+        // Every noise layer and texture roll should be reproduced.
+        assert_eq!(first_map.seed, 1234);
+        for i in -128..256 {
+            for j in -128..256 {
+                assert_eq!(first_map.tile(i, j), second_map.tile(i, j));
+            }
+        }
+
+        drop(first_map);
+        drop(second_map);
+    }
+
+    #[test]
+    fn different_seed_different_tiles() {
+        use map::*;
+
+        let mut first_map = Map::with_seed(1);
+        let mut second_map = Map::with_seed(2);
+        first_map.load_around(0.0, 0.0);
+        second_map.load_around(0.0, 0.0);
+
+        let mut differences = 0;
+        for i in -128..256 {
+            for j in -128..256 {
+                if first_map.tile(i, j) != second_map.tile(i, j) {
+                    differences += 1;
+                }
+            }
+        }
+        assert!(differences > 0);
+
+        drop(first_map);
+        drop(second_map);
+    }
+
+    #[test]
+    fn chunks_load_and_evict() {
+        use map::*;
+
+        let mut test_map = Map::with_seed(99);
+        assert!(test_map.tile(0, 0).is_none());
+
+        test_map.load_around(0.0, 0.0);
+        let original = test_map.tile(-1, 5).cloned();
+        assert!(original.is_some());
+        assert!(test_map.tile(2 * CHUNK_SIZE as i32, 0).is_none());
+
+        // Travelling far away drops the old chunks.
+        let far = (10 * CHUNK_SIZE) as f64;
+        test_map.load_around(far, far);
+        assert!(test_map.tile(-1, 5).is_none());
+        assert!(test_map.tile(10 * CHUNK_SIZE as i32, 10 * CHUNK_SIZE as i32).is_some());
+
+        // Coming back regenerates them as they were.
+        test_map.load_around(0.0, 0.0);
+        assert_eq!(test_map.tile(-1, 5).cloned(), original);
+
+        drop(test_map);
+    }
+
+    #[test]
+    fn changed_chunks_survive_eviction() {
+        use map::*;
+        use tile::*;
+
+        let mut test_map = Map::with_seed(99);
+        test_map.load_around(0.0, 0.0);
+        test_map.set_tile(-3, -3, Tile::new(TileType::Portal));
+
+        // Changed chunks are dropped like any other, but their changes are kept.
+        let far = (10 * CHUNK_SIZE) as f64;
+        test_map.load_around(far, far);
+        assert!(test_map.tile(-3, -3).is_none());
+        let changed = test_map.changed_tiles();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].0, (-3, -3));
+
+        // Coming back regenerates the chunk with the change applied.
+        test_map.load_around(0.0, 0.0);
+        assert_eq!(
+            test_map.tile(-3, -3).map(|tile| tile.tile_type.clone()),
+            Some(TileType::Portal)
+        );
+
+        drop(test_map);
+    }
+
+    #[test]
+    fn islands_stay_in_chunks() {
+        use map::*;
+
+        let mut test_map = Map::with_seed(7);
+        test_map.load_around(0.0, 0.0);
+
+        let size = CHUNK_SIZE as i32;
+        let mut islands = 0;
+        for chunk_x in -1..2 {
+            for chunk_y in -1..2 {
+                for i in chunk_x * size..(chunk_x + 1) * size {
+                    for j in chunk_y * size..(chunk_y + 1) * size {
+                        if let Some(island) = test_map.island_at(i, j) {
+                            // The island never touches its chunk's edge.
+                            assert!(island.x > chunk_x * size);
+                            assert!(island.y > chunk_y * size);
+                            assert!(island.x + (island.width as i32) < (chunk_x + 1) * size);
+                            assert!(island.y + (island.height as i32) < (chunk_y + 1) * size);
+                            islands += 1;
+                        }
+                    }
+                }
+            }
+        }
+        assert!(islands > 0);
+
+        drop(test_map);
+    }
+
+    #[test]
+    fn sky_outside_islands() {
+        use map::*;
+        use tile::TileType;
+
+        let mut test_map = Map::with_seed(7);
+        test_map.load_around(0.0, 0.0);
+
+        for i in -128..256 {
+            for j in -128..256 {
+                if test_map.island_at(i, j).is_none() {
+                    assert_eq!(test_map.tile(i, j).unwrap().tile_type, TileType::Air);
+                }
+            }
+        }
+
+        drop(test_map);
+    }
+
+    #[test]
+    fn finds_island_by_position() {
+        use map::*;
+
+        let mut test_map = Map::with_seed(7);
+        test_map.load_around(0.0, 0.0);
+        let island = test_map
+            .nearest_island(0.0, 0.0)
+            .expect("no islands near the origin")
+            .clone();
+        let (center_x, center_y) = island.center();
+
+        assert_eq!(
+            test_map.island_at(center_x as i32, center_y as i32).map(|i| i.id),
+            Some(island.id)
+        );
+        assert_eq!(
+            test_map.nearest_island(center_x, center_y).map(|i| i.id),
+            Some(island.id)
+        );
+
+        drop(test_map);
+    }
+
+    #[test]
+    fn summarizes_terrain_by_most_common_tile() {
+        use map::*;
+        use tile::*;
+
+        let mut test_map = Map::with_seed(7);
+        test_map.load_around(0.0, 0.0);
+        let island = test_map
+            .nearest_island(0.0, 0.0)
+            .expect("no islands near the origin")
+            .clone();
+
+        // Three of a cell's four tiles are made walls, so the cell shows as wall.
+        let (left, top) = (island.x - 4, island.y - 4);
+        for &(x, y) in &[(left, top), (left + 1, top), (left, top + 1)] {
+            test_map.set_tile(x, y, Tile::new(TileType::StoneWall));
+        }
+        let summary = test_map.summarize(left, top, 2, 1, 2);
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0], vec![Some(TileType::StoneWall)]);
+        assert_eq!(summary[1], vec![Some(TileType::Air)]);
+
+        // Unloaded chunks have nothing to show.
+        let far = 10 * CHUNK_SIZE as i32;
+        assert_eq!(test_map.summarize(far, far, 1, 1, 4), vec![vec![None]]);
+
+        drop(test_map);
+    }
+
+    #[test]
+    fn markers_are_placed_and_removed() {
+        use map::*;
+
+        let mut test_map = Map::with_seed(7);
+        assert!(test_map.add_marker(10, 20));
+        assert!(test_map.add_marker(14, 20));
+        assert!(!test_map.add_marker(10, 20));
+        assert_eq!(test_map.markers(), &[(10, 20), (14, 20)]);
+
+        // Only the closest marker within reach is removed.
+        assert!(!test_map.remove_marker_near(30.0, 20.5, 3.0));
+        assert!(test_map.remove_marker_near(13.0, 20.5, 3.0));
+        assert_eq!(test_map.markers(), &[(10, 20)]);
+
+        drop(test_map);
+    }
+}
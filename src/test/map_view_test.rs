@@ -0,0 +1,67 @@
+//! Testing of the MapView object.
+
+#[cfg(test)]
+
+mod tests {
+    use map::*;
+    use map_view::*;
+    use misc::*;
+    use world::*;
+
+    #[test]
+    fn screen_and_tiles_convert_both_ways() {
+        let mut view = MapView::new();
+        view.center_on(100.0, -50.0);
+
+        // The centre of the window shows the centre of the view.
+        assert_eq!(view.to_screen(100.0, -50.0, 800.0, 600.0), (400.0, 300.0));
+        let (screen_x, screen_y) = view.to_screen(112.0, -47.0, 800.0, 600.0);
+        assert_eq!(view.to_tiles(screen_x, screen_y, 800.0, 600.0), (112.0, -47.0));
+    }
+
+    #[test]
+    fn zoom_stays_within_levels() {
+        let mut view = MapView::new();
+        while view.zoom_in() {}
+        assert_eq!(view.scale(), ZOOM_LEVELS[ZOOM_LEVELS.len() - 1]);
+        assert_eq!(view.cell_size(), 1);
+
+        while view.zoom_out() {}
+        assert_eq!(view.scale(), ZOOM_LEVELS[0]);
+        assert!(!view.zoom_out());
+
+        // Cells stay a few pixels wide, so zooming out summarizes more tiles per cell.
+        assert_eq!(view.cell_size() as f64 * view.scale(), 4.0);
+    }
+
+    #[test]
+    fn panning_moves_a_quarter_of_the_window() {
+        let mut view = MapView::new();
+        let scale = view.scale();
+        view.pan(Direction::E, 800.0, 600.0);
+        view.pan(Direction::S, 800.0, 600.0);
+        assert_eq!((view.center_x, view.center_y), (200.0 / scale, 150.0 / scale));
+
+        // Further out, the same step covers more of the world.
+        view.zoom_out();
+        view.pan(Direction::W, 800.0, 600.0);
+        assert!(view.center_x < 0.0);
+    }
+
+    #[test]
+    fn maps_focus_on_the_ship_or_the_player() {
+        use constants::*;
+
+        let mut test_world = World::new(Map::with_seed(0));
+        let (ship_x, ship_y) = test_world.ship_center();
+        assert_eq!(
+            focus(&test_world),
+            (ship_x / IMAGE_SIZE_SCALED, ship_y / IMAGE_SIZE_SCALED)
+        );
+
+        test_world.player_location = PlayerLocation::InWorld;
+        test_world.player.x = 10.0 * IMAGE_SIZE_SCALED;
+        test_world.player.y = -4.0 * IMAGE_SIZE_SCALED;
+        assert_eq!(focus(&test_world), (10.5, -3.5));
+    }
+}
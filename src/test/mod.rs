@@ -1,4 +1,86 @@
-//! Test library.
+//! Test library, along with the worlds and checks the tests share.
 
+mod achievement_test;
+mod ai_test;
+mod blueprint_test;
+mod crafting_test;
 mod creature_test;
+mod crew_test;
+mod dialogue_test;
+mod input_test;
+mod inventory_test;
+mod map_test;
+mod map_view_test;
+mod quest_test;
+mod save_test;
 mod ship_test;
+mod timestep_test;
+mod world_test;
+
+#[cfg(test)]
+
+pub mod fixtures {
+    use map::*;
+    use std::fmt::Debug;
+    use tile::*;
+    use world::*;
+
+    /*
+        Creates a world to test in, from a fixed seed and without enemies.
+
+        @return World The world.
+    */
+    pub fn quiet_world() -> World {
+        let mut test_world = World::new(Map::with_seed(0));
+        test_world.peaceful = true;
+        test_world
+    }
+
+    /*
+        Creates a quiet world over flat grass, so the player can always step off the ship.
+
+        @return World The world.
+    */
+    pub fn grass_world() -> World {
+        let mut test_world = quiet_world();
+        cover_map(&mut test_world, 20, TileType::GrassFloor);
+        test_world
+    }
+
+    /*
+        Covers the map around the player with one type of tile.
+
+        @param test_world The world.
+        @param radius How many tiles to cover on each side of the player.
+        @param tile_type The tile type.
+    */
+    pub fn cover_map(test_world: &mut World, radius: i32, tile_type: TileType) {
+        let (x, y) = test_world.player_tile();
+        for i in x - radius..x + radius {
+            for j in y - radius..y + radius {
+                test_world.map.set_tile(i, j, Tile::new(tile_type.clone()));
+            }
+        }
+    }
+
+    /*
+        Finds where a parser says some text is wrong, failing the test unless it's rejected.
+
+        @param result What the parser returned.
+        @param position Picks the position out of the error, if it's the kind expected.
+        @return P The position.
+    */
+    pub fn error_position<T, E, P, F>(result: Result<T, E>, position: F) -> P
+    where
+        E: Debug,
+        F: Fn(&E) -> Option<P>,
+    {
+        match result {
+            Ok(_) => panic!("expected a malformed error"),
+            Err(e) => match position(&e) {
+                Some(p) => p,
+                None => panic!("expected a malformed error, got {:?}", e),
+            },
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Testing of quests, driven by game events.
+
+#[cfg(test)]
+
+mod tests {
+    use event::*;
+    use item::*;
+    use quest::*;
+
+    /*
+        Creates a quest log with a two stage quest, and a quest that follows it.
+    */
+    fn test_log() -> QuestLog {
+        QuestLog::new(vec![
+            Quest::new(
+                "first",
+                "First",
+                None,
+                vec![
+                    Stage::new(
+                        "Gather logs",
+                        Objective::Collect(ItemType::Resource(ResourceType::Logs), 2),
+                    ),
+                    Stage::new("Land somewhere new", Objective::ReachIsland),
+                ],
+                vec![(ItemType::Food(FoodType::Bisket), 1)],
+            ),
+            Quest::new(
+                "second",
+                "Second",
+                Some("first"),
+                vec![Stage::new("Defeat enemies", Objective::Defeat(2))],
+                vec![],
+            ),
+        ])
+    }
+
+    #[test]
+    fn stages_follow_events() {
+        let mut log = test_log();
+        assert_eq!(log.active().len(), 1);
+        let first = log.quests[0].clone();
+        assert_eq!(log.describe(&first), "Gather logs (0/2)");
+
+        // Only the right items count.
+        let logs = GameEvent::Collected(ItemType::Resource(ResourceType::Logs));
+        log.handle(&GameEvent::Collected(ItemType::Resource(ResourceType::Grune)));
+        log.handle(&GameEvent::EnemyDefeated);
+        log.handle(&logs);
+        assert_eq!(log.describe(&first), "Gather logs (1/2)");
+        assert!(log.handle(&logs).is_empty());
+        assert_eq!(log.describe(&first), "Land somewhere new");
+
+        // Any island not reached before counts, not only the one pointed out.
+        assert_eq!(log.needing_target(), vec!["first".to_string()]);
+        log.set_target("first", 9);
+        assert!(log.needing_target().is_empty());
+        log.reached.insert(8);
+        log.handle(&GameEvent::IslandReached(8));
+        assert_eq!(log.progress["first"].stage, 1);
+        assert_eq!(log.handle(&GameEvent::IslandReached(7)), vec![0]);
+        assert!(log.reached.contains(&7) && log.reached.contains(&8));
+
+        // Finishing a quest starts the next.
+        assert!(log.is_finished("first"));
+        assert_eq!(log.finished(), vec![&first]);
+        assert_eq!(log.active()[0].name, "second");
+        log.handle(&GameEvent::EnemyDefeated);
+        assert_eq!(log.handle(&GameEvent::EnemyDefeated), vec![1]);
+        assert!(log.active().is_empty());
+        assert_eq!(log.handle(&GameEvent::EnemyDefeated), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn quests_are_well_formed() {
+        let log = QuestLog::new(generate_quests());
+        assert!(!log.active().is_empty());
+        for quest in &log.quests {
+            assert!(!quest.stages.is_empty(), "{} has no stages", quest.name);
+            assert!(!quest.name.contains(' '), "{} can't be saved", quest.name);
+            if let Some(ref required) = quest.requires {
+                assert!(log.quest(required).is_some(), "{} requires nothing real", quest.name);
+            }
+        }
+    }
+
+    #[test]
+    fn world_plays_through_quests() {
+        use constants::*;
+        use crew::*;
+        use test::fixtures::*;
+        use timestep::*;
+        use world::*;
+
+        let mut test_world = grass_world();
+
+        // The first island to reach is the one below.
+        test_world.update(FIXED_DT);
+        let below = test_world.island_under_player().unwrap().id;
+        assert_eq!(test_world.quests.progress["landfall"].target, Some(below));
+        test_world.player.x = test_world.ship.x + 3.0 * IMAGE_SIZE_SCALED;
+        test_world.player.y = test_world.ship.y;
+        test_world.execute(Command::Interact);
+        test_world.update(FIXED_DT);
+        assert_eq!(test_world.quests.progress["landfall"].stage, 1);
+        assert!(test_world.quests.reached.contains(&below));
+
+        // Picking up logs finishes it, with a reward. A dropped log doesn't count again.
+        let logs = test_world.item_prototypes["logs"].clone();
+        let log = logs.generate_clone(test_world.player.x, test_world.player.y);
+        test_world.items_in_game.push(log);
+        test_world.execute(Command::ToggleHands);
+        for _ in 0..3 {
+            test_world.execute(Command::DropItem);
+            test_world.execute(Command::ToggleHands);
+        }
+        test_world.update(FIXED_DT);
+        assert_eq!(test_world.quests.progress["landfall"].count, 1);
+        for _ in 0..3 {
+            let log = logs.generate_clone(test_world.player.x, test_world.player.y);
+            test_world.items_in_game.push(log);
+            test_world.execute(Command::ToggleHands);
+        }
+        test_world.update(FIXED_DT);
+        assert!(test_world.quests.is_finished("landfall"));
+        let bisket = ItemType::Food(FoodType::Bisket);
+        assert_eq!(test_world.player.inventory.count(&bisket), 2);
+
+        // Recruiting finishes the next, and the one after sends the player somewhere new.
+        test_world.player.x += IMAGE_SIZE_SCALED;
+        let islander = CrewMember::new(
+            "Gil",
+            Role::Deckhand,
+            test_world.player.x,
+            test_world.player.y + IMAGE_SIZE_SCALED,
+        );
+        test_world.crew.push(islander);
+        test_world.execute(Command::Interact);
+        while test_world.conversation.is_some() {
+            test_world.execute(Command::Interact);
+        }
+        test_world.update(FIXED_DT);
+        assert!(test_world.quests.is_finished("all_hands"));
+        let sword = ItemType::Interactable(InteractableType::Sword);
+        assert_eq!(test_world.player.inventory.count(&sword), 1);
+        let target = test_world.quests.progress["far_shores"].target;
+        assert!(target.is_some() && target != Some(below));
+    }
+}
@@ -0,0 +1,260 @@
+//! Testing of saving and loading.
+
+#[cfg(test)]
+
+mod tests {
+
+    #[test]
+    fn game_round_trips() {
+        use blueprint::*;
+        use creature::*;
+        use crew::*;
+        use item::*;
+        use map::*;
+        use save::*;
+        use ship::*;
+        use tile::*;
+        use world::*;
+
+        let mut test_world = World::new(Map::with_seed(42));
+
+        test_world.player.x = 120.5;
+        test_world.player.y = -3.25;
+        test_world.player.health = 2;
+        test_world.player.creature_state = CreatureState::ControllingShip;
+        test_world
+            .player
+            .pickup_item(Item::new(ItemType::Food(FoodType::Bisket), 1, true, 1.0));
+        for _ in 0..3 {
+            test_world
+                .player
+                .pickup_item(Item::new(ItemType::Resource(ResourceType::Logs), 5, true, 8.0));
+        }
+        test_world.player.inventory.select(1);
+
+        test_world.ship = Ship::from_blueprint(
+            &Blueprint::parse("legend:\n. = air\n# = wood_floor\nW = wheel\nP = portal\n\nlayout:\n.P.\n#W#\n").unwrap(),
+        );
+        test_world.ship.x = 64.0;
+        test_world.ship.y = 96.0;
+        test_world.ship.speed = 500.0;
+
+        let mut test_item = Item::new(ItemType::Resource(ResourceType::Logs), 5, true, 8.0);
+        test_item.x = 10.0;
+        test_item.y = 20.0;
+        test_item.x_vel = 1.5;
+        test_item.collected = true;
+        test_item.aboard = true;
+        test_world.items_in_game = vec![test_item];
+
+        // Only recruited crew are saved.
+        let mut member = CrewMember::new("Abel", Role::Lookout, 70.0, 100.0);
+        member.recruited = true;
+        member.on_ship = true;
+        member.station = Some((3, 2));
+        test_world.crew = vec![member, CrewMember::new("Brin", Role::Gunner, 0.0, 0.0)];
+
+        test_world.player_location = PlayerLocation::InWorld;
+        test_world.map.under_portal = Tile::new(TileType::GrassFloor);
+        test_world.map.set_tile(-3, 4, Tile::new(TileType::Portal));
+        let mut picked = Tile::new(TileType::Grune);
+        picked.regrow = 12.5;
+        test_world.map.set_tile(-3, 5, picked);
+
+        let island = test_world.map.islands()[0].clone();
+        test_world.map.discover(&island);
+        test_world.map.add_marker(300, -12);
+        test_world.map.add_marker(-5, 40);
+        test_world.flags.insert("fed_islander".to_string());
+        test_world.cleared_islands.insert(11);
+        test_world.quests.reached.insert(island.id);
+        test_world.quests.set_target("landfall", 77);
+
+        let text = write_save(&test_world);
+        let mut loaded_world = World::new(Map::with_seed(0));
+        loaded_world.load(read_save(&text).expect("save did not load"));
+
+        // Player's position, health, state and inventory should be restored.
+        assert_eq!(loaded_world.player.x, 120.5);
+        assert_eq!(loaded_world.player.y, -3.25);
+        assert_eq!(loaded_world.player.health, 2);
+        assert_eq!(
+            loaded_world.player.creature_state,
+            CreatureState::ControllingShip
+        );
+        assert_eq!(loaded_world.player.inventory, test_world.player.inventory);
+
+        // Ship's position and layout should be restored.
+        assert_eq!(loaded_world.ship.x, 64.0);
+        assert_eq!(loaded_world.ship.y, 96.0);
+        assert_eq!(loaded_world.ship.tiles, test_world.ship.tiles);
+        assert_eq!(loaded_world.ship.speed, 500.0);
+
+        assert_eq!(loaded_world.player_location, PlayerLocation::InWorld);
+        assert_eq!(loaded_world.items_in_game, test_world.items_in_game);
+
+        assert_eq!(loaded_world.crew.len(), 1);
+        assert_eq!(loaded_world.crew[0].describe(), "Abel the lookout");
+        assert!(loaded_world.crew[0].recruited && loaded_world.crew[0].on_ship);
+        assert_eq!(loaded_world.crew[0].creature.x, 70.0);
+        assert_eq!(loaded_world.crew[0].station, Some((3, 2)));
+        assert_eq!(loaded_world.flags, test_world.flags);
+        assert_eq!(loaded_world.cleared_islands, test_world.cleared_islands);
+        assert_eq!(loaded_world.quests.progress, test_world.quests.progress);
+        assert_eq!(loaded_world.quests.reached, test_world.quests.reached);
+
+        // The map, including the placed portal, should be restored.
+        assert_eq!(loaded_world.map.seed, 42);
+        assert_eq!(
+            loaded_world.map.tile(-3, 4).map(|tile| tile.tile_type.clone()),
+            Some(TileType::Portal)
+        );
+        assert_eq!(loaded_world.map.changed_tiles(), test_world.map.changed_tiles());
+        assert_eq!(loaded_world.map.tile(-3, 5).unwrap().regrow, 12.5);
+        assert_eq!(loaded_world.map.under_portal, test_world.map.under_portal);
+        assert_eq!(loaded_world.map.discovered_islands(), vec![&island]);
+        assert_eq!(loaded_world.map.markers(), &[(300, -12), (-5, 40)]);
+
+        // Saving the loaded game again should give the same save.
+        assert_eq!(text, write_save(&loaded_world));
+
+        // Picked plants keep growing back once loaded.
+        loaded_world.map.update(12.5);
+        assert!(loaded_world.map.tile(-3, 5).unwrap().is_ripe());
+    }
+
+    #[test]
+    fn loads_older_versions() {
+        use crew::*;
+        use map::*;
+        use save::*;
+        use tile::*;
+        use world::*;
+
+        // Strips the sections added since an old version, and sets its header.
+        fn downgrade(text: &str, version: u32, sections: &[&str]) -> String {
+            text.lines()
+                .filter(|line| !sections.iter().any(|section| line.starts_with(section)))
+                .map(|line| {
+                    // Crew had no home before version 11, and no station before version 7.
+                    // Items didn't record being collected before version 12, or aboard before 14.
+                    let mut fields: Vec<&str> = line.split(' ').collect();
+                    if line.starts_with("member ") {
+                        fields.truncate(if version < 7 { 4 } else { 5 });
+                    }
+                    if line.starts_with("item ") || line.starts_with("stack ") {
+                        fields.truncate(9);
+                    }
+                    format!("{}\n", fields.join(" "))
+                })
+                .collect::<String>()
+                .replacen(
+                    &format!("AOE_SAVE {}", SAVE_VERSION),
+                    &format!("AOE_SAVE {}", version),
+                    1,
+                )
+        }
+        let mut later = vec![
+            "flags ", "flag ", "cleared ", "clear ", "quests ", "quest ", "reached ", "landed ", "discovered ",
+            "island ", "markers ", "marker ",
+        ];
+
+        let mut test_world = World::new(Map::with_seed(42));
+        test_world.crew.clear();
+        test_world.map.add_marker(3, 4);
+        let mut member = CrewMember::new("Abel", Role::Lookout, 70.0, 20.0);
+        member.recruited = true;
+        member.on_ship = true;
+        test_world.crew.push(member);
+
+        // Version 6 had crew without stations, and none of the later sections.
+        let old_text = downgrade(&write_save(&test_world), 6, &later);
+        let data = read_save(&old_text).expect("version 6 save did not load");
+        assert_eq!(data.map.seed, 42);
+        assert_eq!(data.crew.len(), 1);
+        assert_eq!(data.crew[0].describe(), "Abel the lookout");
+        assert_eq!(data.crew[0].station, None);
+        assert!(data.flags.is_empty() && data.quests.is_empty());
+        assert!(data.cleared_islands.is_empty());
+        assert!(data.map.markers().is_empty());
+
+        // Before version 15 whole chunks were saved, with the plants growing back after.
+        let mut rows = vec!["a".repeat(CHUNK_SIZE); CHUNK_SIZE];
+        rows[2] = format!("an{}", "a".repeat(CHUNK_SIZE - 2));
+        let chunk = format!("chunk 0 0\n{}\nregrowing 1\nplant 1 2 3\n", rows.join("\n"));
+        let old_text = old_text.replacen("map 42 0", "map 42 1", 1) + &chunk;
+        let mut data = read_save(&old_text).expect("version 6 chunk did not load");
+        assert_eq!(data.map.tile(1, 2).unwrap().tile_type, TileType::Grune);
+        assert_eq!(data.map.tile(1, 2).unwrap().regrow, 3.0);
+        assert_eq!(data.map.tile(64, 64).unwrap().tile_type, TileType::Air);
+        data.map.load_around(0.0, (10 * CHUNK_SIZE) as f64);
+        data.map.load_around(0.0, 0.0);
+        assert_eq!(data.map.tile(64, 64).unwrap().tile_type, TileType::Air);
+
+        // Version 5 had no crew either.
+        test_world.crew.clear();
+        later.push("crew ");
+        let old_text = downgrade(&write_save(&test_world), 5, &later);
+        let data = read_save(&old_text).expect("version 5 save did not load");
+        assert!(data.crew.is_empty());
+        assert_eq!(data.player.x, test_world.player.x);
+
+        // Versions before 5 are too old to read.
+        match read_save(&format!("AOE_SAVE {}\n", OLDEST_SAVE_VERSION - 1)) {
+            Err(SaveError::UnsupportedVersion(4)) => {}
+            _ => panic!("expected an unsupported version error"),
+        }
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        use save::*;
+
+        match read_save("AOE_SAVE 999\n") {
+            Err(SaveError::UnsupportedVersion(999)) => {}
+            _ => panic!("expected an unsupported version error"),
+        }
+    }
+
+    #[test]
+    fn reports_malformed_line() {
+        use save::*;
+
+        let text = format!("AOE_SAVE {}\ncreature 0 0 three Normal\n", SAVE_VERSION);
+        match read_save(&text) {
+            Err(SaveError::Malformed(line, _)) => assert_eq!(line, 2),
+            _ => panic!("expected a malformed save error"),
+        }
+    }
+
+    #[test]
+    fn rejects_broken_ships() {
+        use map::*;
+        use save::*;
+        use test::fixtures::*;
+        use world::*;
+
+        let text = write_save(&World::new(Map::with_seed(0)));
+        let lines: Vec<&str> = text.lines().collect();
+        let start = lines.iter().position(|line| line.starts_with("ship ")).unwrap();
+        let height: usize = lines[start].split(' ').nth(4).unwrap().parse().unwrap();
+
+        // Swaps the saved ship for another, finding the line the save is rejected on.
+        let error_line = |ship: &str| {
+            let mut edited = lines[..start].join("\n");
+            edited.push('\n');
+            edited.push_str(ship);
+            edited.push_str(&lines[start + 1 + height..].join("\n"));
+            error_position(read_save(&edited), |e| match *e {
+                SaveError::Malformed(line, _) => Some(line),
+                _ => None,
+            })
+        };
+        let ship_line = start + 1;
+        assert_eq!(error_line("ship 0 0 0 3 1\n"), ship_line);
+        assert_eq!(error_line("ship 0 0 3 0 1\n"), ship_line);
+        assert_eq!(error_line("ship 0 0 2 1 1\nww\n"), ship_line + 1);
+        assert_eq!(error_line("ship 0 0 3 1 1\nhaw\n"), ship_line + 1);
+        assert!(read_save(&lines.join("\n")).is_ok());
+    }
+}
@@ -6,17 +6,9 @@ mod tests {
 
     #[test]
     fn ship_constructs() {
-        use ship::*;
         use tile::*;
 
-        let test_tiles = vec![
-            vec![0, 0, 1, 0, 0],
-            vec![0, 1, 1, 1, 0],
-            vec![0, 1, 2, 1, 0],
-            vec![1, 1, 1, 1, 1],
-        ];
-
-        let test_ship = Ship::new(test_tiles);
+        let test_ship = ship_from("..#..\n.###.\n.#W#.\n##P##");
 
         // Check ship's width, height, and layout.
         assert_eq!(test_ship.width, 5.0);
@@ -28,4 +20,172 @@ mod tests {
 
         drop(test_ship);
     }
+
+    /*
+        Builds a ship from blueprint layout rows, using the default legend.
+    */
+    fn ship_from(layout: &str) -> ::ship::Ship {
+        use blueprint::*;
+        use ship::*;
+
+        let text = format!(
+            "legend:\n. = air\n# = wood_floor\nW = wheel\nP = portal\n\nlayout:\n{}\n",
+            layout
+        );
+        Ship::from_blueprint(&Blueprint::parse(&text).unwrap())
+    }
+
+    /*
+        Flies a ship for some seconds with the given directions held.
+    */
+    fn fly(test_ship: &mut ::ship::Ship, directions: &[::misc::Direction], seconds: f64) {
+        use input::*;
+        use misc::*;
+        use timestep::*;
+
+        for dir in directions {
+            test_ship.handle_input(Action::moving(*dir), true);
+        }
+        test_ship.update_self_velocity();
+        let mut time = 0.0;
+        while time < seconds {
+            test_ship.update_velocity(FIXED_DT);
+            test_ship.update_position(FIXED_DT);
+            time += FIXED_DT;
+        }
+    }
+
+    #[test]
+    fn ship_speeds_up_and_coasts() {
+        use input::*;
+        use misc::*;
+
+        let mut test_ship = ship_from("#W#\n#P#");
+
+        // Ship should build up speed rather than jump to it.
+        fly(&mut test_ship, &[Direction::N], 1.0 / 60.0);
+        assert!(test_ship.self_vel_y < 0.0);
+        assert!(test_ship.self_vel_y > -test_ship.speed / 2.0);
+
+        fly(&mut test_ship, &[], 3.0);
+        assert!((test_ship.self_vel_y + test_ship.speed).abs() < 1e-6);
+
+        // Letting go should glide to a stop.
+        test_ship.handle_input(Action::MoveNorth, false);
+        fly(&mut test_ship, &[], 1.0 / 60.0);
+        assert!(test_ship.self_vel_y < -test_ship.speed / 2.0);
+
+        fly(&mut test_ship, &[], 10.0);
+        assert_eq!(test_ship.self_vel_y, 0.0);
+    }
+
+    #[test]
+    fn diagonals_are_not_faster() {
+        use misc::*;
+
+        let mut straight = ship_from("#W#\n#P#");
+        let mut diagonal = ship_from("#W#\n#P#");
+        fly(&mut straight, &[Direction::N], 5.0);
+        fly(&mut diagonal, &[Direction::N, Direction::E], 5.0);
+
+        let straight_speed = straight.self_vel_x.hypot(straight.self_vel_y);
+        let diagonal_speed = diagonal.self_vel_x.hypot(diagonal.self_vel_y);
+        assert!((straight_speed - diagonal_speed).abs() < 1e-6);
+        assert!(diagonal.self_vel_x > 0.0 && diagonal.self_vel_y < 0.0);
+    }
+
+    #[test]
+    fn ship_turns_gradually() {
+        use input::*;
+        use misc::*;
+
+        let mut test_ship = ship_from("#W#\n#P#");
+        fly(&mut test_ship, &[Direction::E], 5.0);
+        assert!(test_ship.self_vel_x > 0.0);
+
+        // Reversing takes time, the ship keeps drifting east at first.
+        test_ship.handle_input(Action::MoveEast, false);
+        fly(&mut test_ship, &[Direction::W], 0.25);
+        assert!(test_ship.self_vel_x > 0.0);
+        assert!(test_ship.self_vel_y != 0.0);
+
+        fly(&mut test_ship, &[], 5.0);
+        assert!(test_ship.self_vel_x < 0.0);
+    }
+
+    #[test]
+    fn bigger_ships_are_sluggish() {
+        use misc::*;
+
+        let mut small = ship_from("#W#\n#P#");
+        let mut big = ship_from(&format!("WP{}\n{}", "#".repeat(10), vec!["#".repeat(12); 11].join("\n")));
+        assert_eq!(small.tile_count(), 6);
+        assert_eq!(big.tile_count(), 144);
+        assert!(big.mass > small.mass && big.thrust > small.thrust && big.drag > small.drag);
+
+        fly(&mut small, &[Direction::E], 0.5);
+        fly(&mut big, &[Direction::E], 0.5);
+        assert!(big.self_vel_x < small.self_vel_x);
+        assert!(big.x < small.x);
+    }
+
+    #[test]
+    fn ship_grows_on_its_edge() {
+        use constants::*;
+        use ship::*;
+        use tile::*;
+
+        let mut test_ship = ship_from("#W#\n#P#");
+        let mass = test_ship.mass;
+
+        assert_eq!(
+            test_ship.build(BuildAction::AddFloor, 5, 0),
+            Err(BuildError::NotOnEdge)
+        );
+        assert_eq!(
+            test_ship.build(BuildAction::AddFloor, 1, 0),
+            Err(BuildError::Occupied)
+        );
+
+        // Growing to the left moves the ship, so the old tiles stay in place.
+        assert_eq!(test_ship.build(BuildAction::AddFloor, -1, 0), Ok(()));
+        assert_eq!(test_ship.width, 4.0);
+        assert_eq!(test_ship.x, -IMAGE_SIZE_SCALED);
+        assert_eq!(test_ship.tile_type_at(0, 0), TileType::WoodFloor);
+        assert_eq!(test_ship.tile_type_at(0, 1), TileType::Air);
+        assert_eq!(test_ship.tile_type_at(2, 0), TileType::Wheel);
+        assert!(test_ship.mass > mass);
+    }
+
+    #[test]
+    fn ship_stays_in_one_piece() {
+        use ship::*;
+        use tile::*;
+
+        let mut test_ship = ship_from("#W#P#");
+
+        assert_eq!(
+            test_ship.build(BuildAction::RemoveFloor, 2, 0),
+            Err(BuildError::WouldSplit)
+        );
+        assert_eq!(
+            test_ship.build(BuildAction::RemoveFloor, 1, 0),
+            Err(BuildError::NotFloor)
+        );
+
+        // Removing an end tile shrinks the ship.
+        assert_eq!(test_ship.build(BuildAction::RemoveFloor, 4, 0), Ok(()));
+        assert_eq!(test_ship.width, 4.0);
+
+        // The wheel and portal move, leaving floor behind.
+        assert_eq!(test_ship.build(BuildAction::MoveWheel, 0, 0), Ok(()));
+        assert_eq!(test_ship.tile_type_at(0, 0), TileType::Wheel);
+        assert_eq!(test_ship.tile_type_at(1, 0), TileType::WoodFloor);
+        assert_eq!(
+            test_ship.build(BuildAction::MovePortal, 0, 0),
+            Err(BuildError::NotFloor)
+        );
+        assert_eq!(test_ship.build(BuildAction::MovePortal, 2, 0), Ok(()));
+        assert_eq!(test_ship.tile_type_at(3, 0), TileType::WoodFloor);
+    }
 }
@@ -0,0 +1,112 @@
+//! Testing of the Timestep object.
+
+#[cfg(test)]
+
+mod tests {
+    use constants::*;
+    use map::*;
+    use misc::*;
+    use timestep::*;
+    use world::*;
+
+    /*
+        Runs a world for the given frame times, returning the player and ship positions.
+    */
+    fn run_frames(frame_times: &[f64]) -> (f64, f64, f64, f64, u32) {
+        let mut test_world = World::new(Map::with_seed(0));
+        let mut timestep = Timestep::new(FIXED_DT, MAX_STEPS_PER_FRAME);
+
+        // Steer the ship north-east from the wheel.
+        test_world.player.x = test_world.ship.x + 3.0 * IMAGE_SIZE_SCALED;
+        test_world.player.y = test_world.ship.y + 2.0 * IMAGE_SIZE_SCALED;
+        test_world.execute(Command::Interact);
+        test_world.execute(Command::StartMoving(Direction::N));
+        test_world.execute(Command::StartMoving(Direction::E));
+
+        let mut total_steps = 0;
+        for frame_time in frame_times {
+            for _ in 0..timestep.advance(*frame_time) {
+                test_world.update(timestep.dt);
+                total_steps += 1;
+            }
+        }
+        (
+            test_world.player.x,
+            test_world.player.y,
+            test_world.ship.x,
+            test_world.ship.y,
+            total_steps,
+        )
+    }
+
+    #[test]
+    fn uneven_frames_match_even_frames() {
+        // Both add up to 30.5 updates' worth of time, without hitting the catch up cap.
+        let even = vec![FIXED_DT; 30]
+            .into_iter()
+            .chain(vec![FIXED_DT / 2.0])
+            .collect::<Vec<f64>>();
+        let uneven = vec![
+            FIXED_DT * 0.3,
+            FIXED_DT * 2.9,
+            FIXED_DT * 0.1,
+            FIXED_DT * 4.0,
+            FIXED_DT * 1.7,
+            FIXED_DT * 4.5,
+            FIXED_DT * 4.5,
+            FIXED_DT * 2.0,
+            FIXED_DT * 0.25,
+            FIXED_DT * 4.25,
+            FIXED_DT * 3.0,
+            FIXED_DT * 3.0,
+        ];
+
+        let even_result = run_frames(&even);
+        let uneven_result = run_frames(&uneven);
+
+        assert_eq!(even_result.4, 30);
+        assert_eq!(even_result, uneven_result);
+    }
+
+    #[test]
+    fn catch_up_is_capped() {
+        let mut timestep = Timestep::new(FIXED_DT, MAX_STEPS_PER_FRAME);
+
+        // A long stall should only run the maximum number of updates.
+        assert_eq!(timestep.advance(FIXED_DT * 100.5), MAX_STEPS_PER_FRAME);
+
+        // The dropped lag should not be made up on the next frame.
+        assert_eq!(timestep.advance(FIXED_DT), 1);
+    }
+
+    #[test]
+    fn alpha_tracks_leftover_time() {
+        let mut timestep = Timestep::new(0.25, MAX_STEPS_PER_FRAME);
+
+        assert_eq!(timestep.advance(0.125), 0);
+        assert_eq!(timestep.alpha(), 0.5);
+
+        assert_eq!(timestep.advance(0.1875), 1);
+        assert_eq!(timestep.alpha(), 0.25);
+    }
+
+    #[test]
+    fn movement_is_per_second() {
+        use creature::*;
+        let mut test_player = Creature::new();
+        test_player.self_vel_x = 120.0;
+
+        // Many small steps should cover the same distance as one big one.
+        for _ in 0..4 {
+            test_player.update_position_self(0.25);
+        }
+        assert_eq!(test_player.x, 120.0);
+
+        let (render_x, _) = {
+            test_player.store_last_position();
+            test_player.update_position_self(0.5);
+            test_player.render_position(0.5)
+        };
+        assert_eq!(render_x, 150.0);
+    }
+}
@@ -0,0 +1,802 @@
+//! Testing of the World object, driving play sessions without a window.
+
+#[cfg(test)]
+
+mod tests {
+    use constants::*;
+    use map::*;
+    use test::fixtures::*;
+    use timestep::*;
+    use tile::*;
+    use world::*;
+
+    /*
+        Creates a world in open sky with the ship lined up on the tile grid,
+        and the player steering it.
+    */
+    fn sky_world() -> World {
+        let mut test_world = quiet_world();
+        cover_map(&mut test_world, 30, TileType::Air);
+        let (x, y) = test_world.player_tile();
+        test_world.ship.x = (x as f64).floor() * IMAGE_SIZE_SCALED;
+        test_world.ship.y = (y as f64).floor() * IMAGE_SIZE_SCALED;
+
+        // Stand on the wheel.
+        test_world.player.x = test_world.ship.x + 3.0 * IMAGE_SIZE_SCALED;
+        test_world.player.y = test_world.ship.y + 2.0 * IMAGE_SIZE_SCALED;
+        test_world.execute(Command::Interact);
+        test_world
+    }
+
+    #[test]
+    fn player_walks_on_ship() {
+        use misc::*;
+        let mut test_world = grass_world();
+        let start_x = test_world.player.x;
+
+        test_world.execute(Command::StartMoving(Direction::E));
+        test_world.update(FIXED_DT);
+
+        // Player should move while on the ship.
+        assert!(test_world.player.x > start_x);
+
+        for _ in 0..100 {
+            test_world.update(FIXED_DT);
+        }
+
+        // Player should stop at the edge of the ship.
+        let ship_right = test_world.ship.x + test_world.ship.width * IMAGE_SIZE_SCALED;
+        assert!(test_world.player.x + IMAGE_SIZE_SCALED <= ship_right);
+
+        test_world.execute(Command::StopMoving(Direction::E));
+        let stopped_x = test_world.player.x;
+        test_world.update(FIXED_DT);
+        assert_eq!(test_world.player.x, stopped_x);
+    }
+
+    #[test]
+    fn player_steers_ship() {
+        use creature::*;
+        use misc::*;
+        let mut test_world = grass_world();
+
+        // Stand on the wheel.
+        test_world.player.x = test_world.ship.x + 3.0 * IMAGE_SIZE_SCALED;
+        test_world.player.y = test_world.ship.y + 2.0 * IMAGE_SIZE_SCALED;
+        test_world.execute(Command::Interact);
+        assert_eq!(
+            test_world.player.creature_state,
+            CreatureState::ControllingShip
+        );
+
+        let ship_start = test_world.ship.y;
+        let player_start = test_world.player.y;
+        test_world.execute(Command::StartMoving(Direction::N));
+        for _ in 0..10 {
+            test_world.update(FIXED_DT);
+        }
+
+        // Ship should move, carrying the player with it.
+        assert!(test_world.ship.y < ship_start);
+        assert_eq!(
+            test_world.player.y - player_start,
+            test_world.ship.y - ship_start
+        );
+    }
+
+    #[test]
+    fn player_uses_portal() {
+        let mut test_world = grass_world();
+
+        // Stand on the portal.
+        test_world.player.x = test_world.ship.x + 3.0 * IMAGE_SIZE_SCALED;
+        test_world.player.y = test_world.ship.y;
+        test_world.execute(Command::Interact);
+
+        // Player should be in the world, with a portal placed under them.
+        assert_eq!(test_world.player_location, PlayerLocation::InWorld);
+        assert_eq!(
+            test_world
+                .tile_under_player(PlayerLocation::InWorld)
+                .unwrap()
+                .tile_type,
+            TileType::Portal
+        );
+        assert_eq!(test_world.map.under_portal.tile_type, TileType::GrassFloor);
+
+        let (x, y) = test_world.player_tile();
+        test_world.execute(Command::Interact);
+
+        // Player should be back on the ship, and the ground restored.
+        assert_eq!(test_world.player_location, PlayerLocation::OnShip);
+        assert_eq!(test_world.map.under_portal.tile_type, TileType::Air);
+        assert_eq!(
+            test_world.map.tile(x, y).unwrap().tile_type,
+            TileType::GrassFloor
+        );
+    }
+
+    #[test]
+    fn player_arrives_on_moved_portal() {
+        use ship::*;
+        let mut test_world = grass_world();
+        test_world
+            .ship
+            .build(BuildAction::MovePortal, 3, 1)
+            .expect("portal did not move");
+
+        // Go down through the moved portal, and back up onto it.
+        test_world.player.x = test_world.ship.x + 3.0 * IMAGE_SIZE_SCALED;
+        test_world.player.y = test_world.ship.y + IMAGE_SIZE_SCALED;
+        test_world.execute(Command::Interact);
+        assert_eq!(test_world.player_location, PlayerLocation::InWorld);
+        test_world.execute(Command::Interact);
+        assert_eq!(test_world.player_location, PlayerLocation::OnShip);
+        assert_eq!(
+            (test_world.player.x, test_world.player.y),
+            (
+                test_world.ship.x + 3.0 * IMAGE_SIZE_SCALED,
+                test_world.ship.y + IMAGE_SIZE_SCALED
+            )
+        );
+        assert_eq!(
+            test_world
+                .tile_under_player(PlayerLocation::OnShip)
+                .unwrap()
+                .tile_type,
+            TileType::Portal
+        );
+
+        // Without a portal aboard, there's nowhere to arrive.
+        test_world.execute(Command::Interact);
+        test_world.ship.change_tile(3, 1, Tile::new(TileType::WoodFloor));
+        test_world.execute(Command::Interact);
+        assert_eq!(test_world.player_location, PlayerLocation::InWorld);
+    }
+
+    #[test]
+    fn player_picks_up_and_drops_item() {
+        let mut test_world = grass_world();
+        let mut bisket = test_world.items_in_game[0].clone();
+        test_world.player.x = bisket.x;
+        test_world.player.y = bisket.y;
+
+        test_world.execute(Command::ToggleHands);
+        assert!(test_world.items_in_game.is_empty());
+        bisket.collected = true;
+        assert_eq!(test_world.player.inventory.held(), Some(&bisket));
+
+        // Items too heavy to carry are left, and the held item is dropped instead.
+        let mut anvil = bisket.clone();
+        anvil.weight = test_world.player.inventory.max_weight;
+        test_world.items_in_game.push(anvil);
+        test_world.execute(Command::ToggleHands);
+        assert_eq!(test_world.items_in_game.len(), 2);
+        assert!(test_world.player.inventory.held().is_none());
+
+        test_world.items_in_game.remove(0);
+        test_world.execute(Command::ToggleHands);
+        assert!(test_world.items_in_game.is_empty());
+
+        // Dropping acts on the selected slot only.
+        test_world.execute(Command::SelectSlot(1));
+        test_world.execute(Command::DropItem);
+        assert!(test_world.items_in_game.is_empty());
+        test_world.execute(Command::SelectSlot(0));
+        test_world.execute(Command::DropItem);
+        assert_eq!(test_world.items_in_game.len(), 1);
+        assert_eq!(test_world.player.inventory.held(), None);
+    }
+
+    #[test]
+    fn player_chops_trees_and_picks_grune() {
+        let mut test_world = grass_world();
+        test_world.player_location = PlayerLocation::InWorld;
+        let (x, y) = test_world.player_tile();
+
+        // Trees need a tool.
+        test_world.map.set_tile(x, y, Tile::new(TileType::Tree));
+        test_world.execute(Command::Interact);
+        assert_eq!(test_world.map.tile(x, y).unwrap().tile_type, TileType::Tree);
+
+        let sword = test_world.item_prototypes["sword"].clone();
+        test_world.player.pickup_item(sword);
+        test_world.execute(Command::Interact);
+        assert_eq!(
+            test_world.map.tile(x, y).unwrap().tile_type,
+            TileType::GrassFloor
+        );
+        let logs = test_world
+            .items_in_game
+            .iter()
+            .filter(|item| item.item_type.name() == "logs")
+            .count();
+        assert_eq!(logs, 2);
+
+        // Grune can be picked once, then grows back.
+        test_world.map.set_tile(x, y, Tile::new(TileType::Grune));
+        test_world.execute(Command::Interact);
+        test_world.execute(Command::Interact);
+        let grune = test_world.item_prototypes["grune"].item_type.clone();
+        assert_eq!(test_world.player.inventory.count(&grune), 1);
+        assert!(!test_world.map.tile(x, y).unwrap().is_ripe());
+
+        test_world.update(1.0);
+        assert!(test_world.map.tile(x, y).unwrap().regrow > 0.0);
+        test_world.map.update(60.0);
+        assert!(test_world.map.tile(x, y).unwrap().is_ripe());
+    }
+
+    #[test]
+    fn thrown_items_slide_and_hit() {
+        use creature::*;
+        use misc::*;
+
+        let mut test_world = grass_world();
+        test_world.items_in_game.clear();
+        test_world.player_location = PlayerLocation::InWorld;
+        let (x, y) = test_world.player_tile();
+        let throw = |test_world: &mut World, name: &str| {
+            let item = test_world.item_prototypes[name].clone();
+            test_world.player.pickup_item(item);
+            test_world.execute(Command::Throw);
+            for _ in 0..300 {
+                test_world.update(FIXED_DT);
+            }
+        };
+        assert_eq!(test_world.player.facing(), Direction::S);
+
+        // Items slide to a stop, heavier ones sooner.
+        throw(&mut test_world, "bisket");
+        throw(&mut test_world, "logs");
+        let bisket = &test_world.items_in_game[0];
+        let logs = &test_world.items_in_game[1];
+        assert!(!bisket.is_moving() && !logs.is_moving());
+        assert!(bisket.y > logs.y && logs.y > test_world.player.y);
+        assert_eq!(bisket.x, test_world.player.x);
+
+        // Walls stop them.
+        test_world.items_in_game.clear();
+        test_world.map.set_tile(x, y + 4, Tile::new(TileType::StoneWall));
+        throw(&mut test_world, "bisket");
+        assert!(test_world.items_in_game[0].y < (y + 3) as f64 * IMAGE_SIZE_SCALED + 1.0);
+
+        // Creatures that are hit take damage, and are removed when killed.
+        test_world.items_in_game.clear();
+        let mut npc = Creature::new();
+        npc.x = test_world.player.x;
+        npc.y = test_world.player.y + 2.0 * IMAGE_SIZE_SCALED;
+        test_world.npcs.push(npc);
+        throw(&mut test_world, "bisket");
+        assert_eq!(test_world.npcs[0].health, 2);
+        assert!(test_world.items_in_game[0].y < test_world.npcs[0].y);
+
+        throw(&mut test_world, "sword");
+        assert!(test_world.npcs.is_empty());
+
+        // Items thrown aboard stay on the ship, even once the player has left it.
+        test_world.items_in_game.clear();
+        test_world.player_location = PlayerLocation::OnShip;
+        let item = test_world.item_prototypes["bisket"].clone();
+        test_world.player.pickup_item(item);
+        test_world.execute(Command::Throw);
+        test_world.player_location = PlayerLocation::InWorld;
+        for _ in 0..300 {
+            test_world.update(FIXED_DT);
+        }
+        let bisket = &test_world.items_in_game[0];
+        assert!(bisket.aboard && test_world.is_on_ship(bisket.x, bisket.y));
+    }
+
+    #[test]
+    fn sword_hits_in_front() {
+        use creature::*;
+
+        let mut test_world = grass_world();
+        test_world.items_in_game.clear();
+        test_world.player_location = PlayerLocation::InWorld;
+        let sword = test_world.item_prototypes["sword"].clone();
+        test_world.player.pickup_item(sword);
+
+        // One creature in front of the player, facing south, and one behind.
+        let mut front = Creature::new();
+        front.health = 20;
+        front.x = test_world.player.x;
+        front.y = test_world.player.y + IMAGE_SIZE_SCALED;
+        let mut behind = Creature::new();
+        behind.x = test_world.player.x;
+        behind.y = test_world.player.y - IMAGE_SIZE_SCALED;
+        test_world.npcs = vec![front, behind];
+
+        test_world.execute(Command::Interact);
+        assert_eq!(test_world.npcs[0].health, 10);
+        assert_eq!(test_world.npcs[1].health, 3);
+        assert!(test_world.player.swing_timer > 0.0);
+
+        // Attacks have a cooldown.
+        test_world.execute(Command::Interact);
+        assert_eq!(test_world.npcs[0].health, 10);
+
+        // The hit creature is knocked back, then stops.
+        let y = test_world.npcs[0].y;
+        for _ in 0..60 {
+            test_world.update(FIXED_DT);
+        }
+        assert!(test_world.npcs[0].y > y);
+        assert_eq!(test_world.npcs[0].knockback_y, 0.0);
+        assert_eq!(test_world.player.swing_timer, 0.0);
+
+        // It's now out of reach.
+        test_world.execute(Command::Interact);
+        assert_eq!(test_world.npcs[0].health, 10);
+
+        test_world.npcs[0].y = test_world.player.y + IMAGE_SIZE_SCALED;
+        for _ in 0..60 {
+            test_world.update(FIXED_DT);
+        }
+        test_world.execute(Command::Interact);
+        assert_eq!(test_world.npcs.len(), 1);
+    }
+
+    #[test]
+    fn walls_block_walking() {
+        let mut test_world = grass_world();
+        let (x, y) = test_world.player_tile();
+        test_world.map.set_tile(x + 5, y + 5, Tile::new(TileType::StoneWall));
+        let position = |i: f64, j: f64| {
+            (
+                (x as f64 + i) * IMAGE_SIZE_SCALED,
+                (y as f64 + j) * IMAGE_SIZE_SCALED,
+            )
+        };
+
+        let (open_x, open_y) = position(2.0, 2.0);
+        assert!(test_world.can_go_to(open_x, open_y));
+        let (wall_x, wall_y) = position(5.0, 5.0);
+        assert!(!test_world.can_go_to(wall_x, wall_y));
+        let (beside_x, beside_y) = position(4.5, 5.0);
+        assert!(!test_world.can_go_to(beside_x, beside_y));
+
+        // Chunks that aren't loaded can't be walked on either.
+        let (far_x, far_y) = position(10.0 * CHUNK_SIZE as f64, 0.0);
+        assert!(!test_world.can_go_to(far_x, far_y));
+    }
+
+    #[test]
+    fn map_streams_with_ship() {
+        let mut test_world = grass_world();
+        let (start_x, start_y) = test_world.player_tile();
+        let start = chunk_key(start_x, start_y);
+        let size = CHUNK_SIZE as i32;
+
+        // Pick a loaded chunk behind the ship.
+        let behind = (start.0 - 1, start.1 - 1);
+        assert!(test_world.map.tile(behind.0 * size, behind.1 * size).is_some());
+
+        // Carry the ship and player a few chunks east.
+        let distance = ((EVICT_RADIUS + 1) * size) as f64 * IMAGE_SIZE_SCALED;
+        test_world.ship.x += distance;
+        test_world.player.x += distance;
+        test_world.update(FIXED_DT);
+
+        // Chunks ahead should be loaded, and the ones behind dropped, edited or not.
+        let (x, y) = test_world.player_tile();
+        assert!(test_world.map.tile(x + size, y).is_some());
+        assert!(test_world.map.tile(behind.0 * size, behind.1 * size).is_none());
+        assert!(test_world.map.tile(start_x, start_y).is_none());
+
+        // Coming back brings the grass back with its chunk.
+        test_world.ship.x -= distance;
+        test_world.player.x -= distance;
+        test_world.update(FIXED_DT);
+        assert_eq!(
+            test_world.map.tile(start_x, start_y).unwrap().tile_type,
+            TileType::GrassFloor
+        );
+    }
+
+    #[test]
+    fn ship_slides_along_wall() {
+        use misc::*;
+        let mut test_world = sky_world();
+        let ship_x = (test_world.ship.x / IMAGE_SIZE_SCALED) as i32;
+        let ship_y = (test_world.ship.y / IMAGE_SIZE_SCALED) as i32;
+
+        // A wall two tiles to the right of the ship, running north.
+        let wall_x = ship_x + test_world.ship.width as i32 + 2;
+        for j in ship_y - 25..ship_y + 10 {
+            test_world.map.set_tile(wall_x, j, Tile::new(TileType::StoneWall));
+        }
+
+        // Approach diagonally.
+        let start_y = test_world.ship.y;
+        test_world.execute(Command::StartMoving(Direction::N));
+        test_world.execute(Command::StartMoving(Direction::E));
+        for _ in 0..180 {
+            test_world.update(FIXED_DT);
+        }
+
+        // Ship should stop flush with the wall, but keep sliding north.
+        let ship_right = test_world.ship.x + test_world.ship.width * IMAGE_SIZE_SCALED;
+        assert!((ship_right - wall_x as f64 * IMAGE_SIZE_SCALED).abs() < 0.1);
+        assert!(test_world.ship.y < start_y - 5.0 * IMAGE_SIZE_SCALED);
+
+        // The player should still be at the wheel.
+        assert_eq!(
+            test_world
+                .tile_under_player(PlayerLocation::OnShip)
+                .unwrap()
+                .tile_type,
+            TileType::Wheel
+        );
+    }
+
+    #[test]
+    fn ship_shape_collides() {
+        use misc::*;
+        let mut test_world = sky_world();
+        let ship_x = (test_world.ship.x / IMAGE_SIZE_SCALED) as i32;
+        let ship_y = (test_world.ship.y / IMAGE_SIZE_SCALED) as i32;
+
+        // A tree above the ship's left column, which is empty for its first three rows.
+        test_world.map.set_tile(ship_x, ship_y - 2, Tile::new(TileType::Tree));
+        assert!(test_world.ship_fits(test_world.ship.x, test_world.ship.y));
+
+        test_world.execute(Command::StartMoving(Direction::N));
+        for _ in 0..60 {
+            test_world.update(FIXED_DT);
+        }
+
+        // The ship's hull overlaps the tree's row, only its first wooden tile stops it.
+        let stop_y = (ship_y - 1 - 3) as f64 * IMAGE_SIZE_SCALED;
+        assert!((test_world.ship.y - stop_y).abs() < 0.1);
+        assert_eq!(
+            test_world.ship.x,
+            ship_x as f64 * IMAGE_SIZE_SCALED
+        );
+    }
+
+    #[test]
+    fn player_builds_at_wheel() {
+        use item::*;
+        use misc::*;
+        use ship::*;
+        let mut test_world = grass_world();
+
+        // Build mode only opens at a station.
+        test_world.execute(Command::ToggleBuildMode);
+        assert_eq!(test_world.build_cursor, None);
+
+        test_world.player.x = test_world.ship.x + 3.0 * IMAGE_SIZE_SCALED;
+        test_world.player.y = test_world.ship.y + 2.0 * IMAGE_SIZE_SCALED;
+        test_world.execute(Command::ToggleBuildMode);
+        assert_eq!(test_world.build_cursor, Some((3, 2)));
+
+        // Movement steers the cursor instead of the player.
+        let player_x = test_world.player.x;
+        for dir in &[Direction::W, Direction::W, Direction::N, Direction::N] {
+            test_world.execute(Command::StartMoving(*dir));
+            test_world.execute(Command::StopMoving(*dir));
+        }
+        test_world.update(FIXED_DT);
+        assert_eq!(test_world.player.x, player_x);
+        assert_eq!(test_world.build_cursor, Some((1, 0)));
+
+        // Floor costs logs.
+        test_world.execute(Command::Build(BuildAction::AddFloor));
+        assert_eq!(test_world.build_error, Some(BuildError::NoMaterials(ResourceType::Logs)));
+        let logs = test_world.item_prototypes["logs"].clone();
+        let logs_type = logs.item_type.clone();
+        test_world.player.pickup_item(logs);
+        test_world.execute(Command::Build(BuildAction::AddFloor));
+        assert_eq!(test_world.build_error, None);
+        assert_eq!(test_world.ship.tile_type_at(1, 0), TileType::WoodFloor);
+        assert_eq!(test_world.player.inventory.count(&logs_type), 0);
+
+        // Removing it gives the logs back.
+        test_world.execute(Command::Build(BuildAction::RemoveFloor));
+        assert_eq!(test_world.build_error, None);
+        assert_eq!(test_world.ship.tile_type_at(1, 0), TileType::Air);
+        assert_eq!(test_world.player.inventory.count(&logs_type), 1);
+
+        // Floor can't be built into something solid below the ship.
+        let map_x = (test_world.ship.x / IMAGE_SIZE_SCALED).floor() as i32 + 1;
+        let map_y = (test_world.ship.y / IMAGE_SIZE_SCALED).floor() as i32;
+        test_world.map.set_tile(map_x, map_y, Tile::new(TileType::Tree));
+        test_world.execute(Command::Build(BuildAction::AddFloor));
+        assert_eq!(test_world.build_error, Some(BuildError::Blocked));
+        assert_eq!(test_world.ship.tile_type_at(1, 0), TileType::Air);
+        assert_eq!(test_world.player.inventory.count(&logs_type), 1);
+        test_world.map.set_tile(map_x, map_y, Tile::new(TileType::GrassFloor));
+
+        // Cannons cost planks.
+        test_world.execute(Command::StartMoving(Direction::S));
+        test_world.execute(Command::StopMoving(Direction::S));
+        test_world.execute(Command::Build(BuildAction::PlaceCannon));
+        assert_eq!(
+            test_world.build_error,
+            Some(BuildError::NoMaterials(ResourceType::Planks))
+        );
+        let planks = test_world.item_prototypes["planks"].clone();
+        let planks_type = planks.item_type.clone();
+        for _ in 0..4 {
+            test_world.player.pickup_item(planks.clone());
+        }
+        test_world.execute(Command::Build(BuildAction::PlaceCannon));
+        assert_eq!(test_world.build_error, None);
+        assert_eq!(test_world.ship.tile_type_at(1, 1), TileType::Cannon);
+        assert_eq!(test_world.player.inventory.count(&planks_type), 0);
+
+        test_world.execute(Command::ToggleBuildMode);
+        assert_eq!(test_world.build_cursor, None);
+    }
+
+    #[test]
+    fn enemies_chase_attack_and_flee() {
+        let mut test_world = grass_world();
+        test_world.items_in_game.clear();
+        test_world.player_location = PlayerLocation::InWorld;
+        test_world.player.health = 10;
+
+        let mut bandit = test_world.enemy_prototypes["bandit"].clone();
+        bandit.x = test_world.player.x + 4.0 * IMAGE_SIZE_SCALED;
+        bandit.y = test_world.player.y;
+        test_world.npcs = vec![bandit];
+
+        // The bandit closes in, then hits the player.
+        let start_x = test_world.npcs[0].x;
+        test_world.update(FIXED_DT);
+        assert!(test_world.npcs[0].x < start_x);
+        for _ in 0..120 {
+            test_world.update(FIXED_DT);
+        }
+        assert!(test_world.player.health < 10);
+
+        // Badly hurt, it runs away.
+        test_world.npcs[0].health = 5;
+        let x = test_world.npcs[0].x;
+        for _ in 0..30 {
+            test_world.update(FIXED_DT);
+        }
+        assert!(test_world.npcs[0].x > x);
+
+        // Players on the ship are out of reach.
+        test_world.player_location = PlayerLocation::OnShip;
+        test_world.npcs[0].health = 20;
+        let health = test_world.player.health;
+        test_world.npcs[0].x = test_world.player.x;
+        for _ in 0..60 {
+            test_world.update(FIXED_DT);
+        }
+        assert_eq!(test_world.player.health, health);
+    }
+
+    #[test]
+    fn islands_spawn_enemies_once() {
+        let mut test_world = World::new(Map::with_seed(0));
+        test_world.update(FIXED_DT);
+        assert!(!test_world.npcs.is_empty());
+        assert_eq!(test_world.spawned_islands.len(), test_world.map.islands().len());
+        for npc in &test_world.npcs {
+            assert!(npc.ai.is_some());
+            assert!(test_world.can_go_to(npc.x, npc.y));
+        }
+
+        // Islands aren't populated twice, and the same seed spawns the same enemies.
+        let count = test_world.npcs.len();
+        test_world.update(FIXED_DT);
+        assert_eq!(test_world.npcs.len(), count);
+
+        let mut other_world = World::new(Map::with_seed(0));
+        other_world.update(FIXED_DT);
+        assert_eq!(other_world.npcs.len(), count);
+
+        let mut peaceful_world = quiet_world();
+        peaceful_world.update(FIXED_DT);
+        assert!(peaceful_world.npcs.is_empty());
+    }
+
+    #[test]
+    fn enemies_leave_with_their_islands() {
+        use save::*;
+
+        let mut test_world = World::new(Map::with_seed(0));
+        test_world.update(FIXED_DT);
+        let island_of = |test_world: &World, i: usize| {
+            let npc = &test_world.npcs[i];
+            let (x, y) = (npc.x / IMAGE_SIZE_SCALED, npc.y / IMAGE_SIZE_SCALED);
+            test_world.map.island_at(x.floor() as i32, y.floor() as i32).unwrap().id
+        };
+        let island = island_of(&test_world, 0);
+        let on_island = |test_world: &World| {
+            (0..test_world.npcs.len())
+                .filter(|&i| island_of(test_world, i) == island)
+                .count()
+        };
+        let count = on_island(&test_world);
+
+        // Sailing away drops the enemies with their chunks, and coming back brings them again.
+        let distance = ((EVICT_RADIUS + 1) * CHUNK_SIZE as i32) as f64 * IMAGE_SIZE_SCALED;
+        for &step in &[distance, -distance] {
+            test_world.ship.x += step;
+            test_world.player.x += step;
+            test_world.update(FIXED_DT);
+            for npc in &test_world.npcs {
+                let (x, y) = (npc.x / IMAGE_SIZE_SCALED, npc.y / IMAGE_SIZE_SCALED);
+                assert!(test_world.map.tile(x.floor() as i32, y.floor() as i32).is_some());
+            }
+        }
+        assert_eq!(on_island(&test_world), count);
+
+        // Defeating every enemy on an island clears it for good, even after loading.
+        for i in 0..test_world.npcs.len() {
+            if island_of(&test_world, i) == island {
+                test_world.npcs[i].health = 0;
+            }
+        }
+        test_world.update(FIXED_DT);
+        assert!(test_world.cleared_islands.contains(&island));
+        let text = write_save(&test_world);
+        test_world.load(read_save(&text).expect("save did not load"));
+        test_world.update(FIXED_DT);
+        assert!(!test_world.npcs.is_empty());
+        assert_eq!(on_island(&test_world), 0);
+    }
+
+    #[test]
+    fn recruits_are_not_found_again_after_loading() {
+        use save::*;
+
+        let mut test_world = quiet_world();
+        test_world.update(FIXED_DT);
+        let home = test_world.crew[0].home;
+        assert!(home.is_some());
+        test_world.crew[0].recruited = true;
+
+        // Loading repopulates the islands, but the recruit's island has no islander left.
+        let text = write_save(&test_world);
+        test_world.load(read_save(&text).expect("save did not load"));
+        test_world.update(FIXED_DT);
+        let found = test_world
+            .crew
+            .iter()
+            .filter(|member| member.home == home)
+            .count();
+        assert_eq!(found, 1);
+        assert!(test_world.crew.iter().any(|member| !member.recruited));
+    }
+
+    #[test]
+    fn crew_follow_and_board() {
+        use crew::*;
+        use misc::*;
+        use ship::*;
+
+        let mut test_world = grass_world();
+        test_world.crew.clear();
+
+        // Step off the ship and away from the portal.
+        test_world.player.x = test_world.ship.x + 3.0 * IMAGE_SIZE_SCALED;
+        test_world.player.y = test_world.ship.y;
+        test_world.execute(Command::Interact);
+        let (portal_x, portal_y) = (test_world.player.x, test_world.player.y);
+        test_world.player.x -= IMAGE_SIZE_SCALED;
+
+        let islander = CrewMember::new(
+            "Abel",
+            Role::Lookout,
+            test_world.player.x,
+            test_world.player.y + IMAGE_SIZE_SCALED,
+        );
+        test_world.crew.push(islander);
+
+        // Talking to them, the first answer asks them to join.
+        test_world.execute(Command::Interact);
+        assert!(test_world.conversation.is_some());
+        while test_world.conversation.is_some() {
+            test_world.execute(Command::Interact);
+        }
+        assert!(test_world.crew[0].recruited);
+
+        // Recruits follow the player around.
+        let x = test_world.crew[0].creature.x;
+        test_world.execute(Command::StartMoving(Direction::W));
+        for _ in 0..30 {
+            test_world.update(FIXED_DT);
+        }
+        test_world.execute(Command::StopMoving(Direction::W));
+        assert!(test_world.crew[0].creature.x < x);
+
+        // They board through the portal with the player, arriving on the ship's portal
+        // wherever it has been moved to.
+        test_world
+            .ship
+            .build(BuildAction::MovePortal, 3, 1)
+            .expect("portal did not move");
+        test_world.player.x = portal_x;
+        test_world.player.y = portal_y;
+        test_world.execute(Command::Interact);
+        assert_eq!(test_world.player_location, PlayerLocation::OnShip);
+        assert!(test_world.crew[0].on_ship);
+        let arrival = test_world.ship_portal_position();
+        assert_eq!(
+            Some((test_world.crew[0].creature.x, test_world.crew[0].creature.y)),
+            arrival
+        );
+
+        // And are carried along by the ship.
+        test_world.player.x = test_world.ship.x + 3.0 * IMAGE_SIZE_SCALED;
+        test_world.player.y = test_world.ship.y + 2.0 * IMAGE_SIZE_SCALED;
+        test_world.execute(Command::Interact);
+        let ship_start = test_world.ship.y;
+        let crew_start = test_world.crew[0].creature.y;
+        test_world.execute(Command::StartMoving(Direction::N));
+        for _ in 0..10 {
+            test_world.update(FIXED_DT);
+        }
+        assert!(test_world.ship.y < ship_start);
+        assert_eq!(
+            test_world.crew[0].creature.y - crew_start,
+            test_world.ship.y - ship_start
+        );
+    }
+
+    #[test]
+    fn mouse_clicks_and_aims() {
+        use creature::*;
+        use misc::*;
+
+        let mut test_world = grass_world();
+        test_world.items_in_game.clear();
+
+        // Clicks land on ship tiles while aboard, and nothing off the ship.
+        let (trans_x, trans_y) = (400.0 - test_world.player.x, 320.0 - test_world.player.y);
+        let (x, y) = screen_to_world(400.0, 320.0, trans_x, trans_y);
+        assert_eq!((x, y), (test_world.player.x, test_world.player.y));
+        let (ship_x, ship_y) = (test_world.ship.x, test_world.ship.y);
+        let half = IMAGE_SIZE_SCALED / 2.0;
+        assert_eq!(
+            test_world.tile_at(ship_x + IMAGE_SIZE_SCALED + half, ship_y + half),
+            Some((1, 0))
+        );
+        assert_eq!(test_world.tile_at(ship_x - half, ship_y), None);
+
+        // Clicking the wheel underfoot takes it.
+        test_world.player.x = ship_x + 3.0 * IMAGE_SIZE_SCALED;
+        test_world.player.y = ship_y + 2.0 * IMAGE_SIZE_SCALED;
+        let (center_x, center_y) = test_world.player_center();
+        test_world.execute(Command::InteractAt(center_x, center_y));
+        assert_eq!(test_world.player.creature_state, CreatureState::ControllingShip);
+        test_world.execute(Command::InteractAt(center_x, center_y));
+
+        // Clicking an item in reach picks it up, one out of reach stays put.
+        test_world.player_location = PlayerLocation::InWorld;
+        let (center_x, center_y) = test_world.player_center();
+        let bisket = test_world.item_prototypes["bisket"].clone();
+        let near = bisket.generate_clone(test_world.player.x + IMAGE_SIZE_SCALED, test_world.player.y);
+        let far = bisket.generate_clone(test_world.player.x + 4.0 * IMAGE_SIZE_SCALED, test_world.player.y);
+        test_world.items_in_game.push(near);
+        test_world.items_in_game.push(far);
+        test_world.execute(Command::InteractAt(center_x + 4.0 * IMAGE_SIZE_SCALED, center_y));
+        test_world.execute(Command::InteractAt(center_x + IMAGE_SIZE_SCALED, center_y));
+        assert_eq!(test_world.items_in_game.len(), 1);
+        assert_eq!(test_world.player.inventory.count(&bisket.item_type), 1);
+
+        // Clicking a tree next to the player turns to chop it.
+        let (x, y) = test_world.player_tile();
+        test_world.map.set_tile(x - 1, y, Tile::new(TileType::Tree));
+        let sword = test_world.item_prototypes["sword"].clone();
+        test_world.player.pickup_item(sword);
+        test_world.player.inventory.hand = 1;
+        test_world.execute(Command::InteractAt(center_x - IMAGE_SIZE_SCALED, center_y));
+        assert_eq!(test_world.player.facing(), Direction::W);
+        assert_eq!(test_world.map.tile(x - 1, y).unwrap().tile_type, TileType::GrassFloor);
+
+        // Throws fly towards the cursor, at the same speed whichever way.
+        test_world.items_in_game.clear();
+        test_world.execute(Command::ThrowAt(center_x + 100.0, center_y - 100.0));
+        let thrown = &test_world.items_in_game[0];
+        assert!(thrown.x_vel > 0.0 && (thrown.x_vel + thrown.y_vel).abs() < 1e-9);
+        assert_eq!(test_world.player.facing(), Direction::N);
+    }
+}
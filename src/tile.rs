@@ -1,6 +1,10 @@
 //! The tile object specifies properties of different tiles in the game.
 //! Floor tiles can be walked on.
 //! Wall tiles cannot be walked on.
+//! Solid tiles, like walls and trees, block the ship.
+//! Plant tiles, like grune, can be picked and grow back over time.
+//! Station tiles, like the wheel, are where crew work aboard the ship.
+//! Every tile has a colour for summarizing the terrain on the map.
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum TileType {
@@ -13,6 +17,46 @@ pub enum TileType {
     Water,
     Wheel,
     Portal,
+    Workbench,
+    Grune,
+    Lookout,
+    Cannon,
+}
+
+impl TileType {
+    /*
+        Determines if crew can be stationed on the tile.
+
+        @return bool Whether the tile is a crew station.
+    */
+    pub fn is_station(&self) -> bool {
+        match *self {
+            TileType::Wheel | TileType::Lookout | TileType::Workbench | TileType::Cannon => true,
+            _ => false,
+        }
+    }
+
+    /*
+        Determines the colour the tile is shown with on the map.
+
+        @return [f32; 4] The colour.
+    */
+    pub fn map_color(&self) -> [f32; 4] {
+        match *self {
+            TileType::WoodFloor => [0.55, 0.35, 0.2, 1.0],
+            TileType::StoneWall => [0.5, 0.5, 0.5, 1.0],
+            TileType::GrassFloor => [0.3, 0.65, 0.25, 1.0],
+            TileType::DirtFloor => [0.55, 0.4, 0.25, 1.0],
+            TileType::Tree => [0.1, 0.4, 0.15, 1.0],
+            TileType::Air => [0.45, 0.65, 0.9, 1.0],
+            TileType::Water => [0.2, 0.35, 0.8, 1.0],
+            TileType::Portal => [0.7, 0.3, 0.9, 1.0],
+            TileType::Grune => [0.55, 0.8, 0.3, 1.0],
+            TileType::Wheel | TileType::Workbench | TileType::Lookout | TileType::Cannon => {
+                [0.7, 0.5, 0.3, 1.0]
+            }
+        }
+    }
 }
 
 /* 
@@ -20,14 +64,18 @@ pub enum TileType {
 
     @field tile_type The tile's type.
     @field passable Whether the tile can be walked on.
+    @field solid Whether the tile blocks the ship.
     @field texture Whether the tile will have extra texture (for graphics).
+    @field regrow Seconds until a picked plant has grown back, 0 when grown.
 */
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Tile {
     pub tile_type: TileType,
     pub passable: bool,
+    pub solid: bool,
     pub texture: bool,
+    pub regrow: f64,
 }
 
 impl Tile {
@@ -42,10 +90,25 @@ impl Tile {
             TileType::Water | TileType::StoneWall | TileType::Air => false,
             _ => true,
         };
+        let is_solid = match tile_type {
+            TileType::StoneWall | TileType::Tree => true,
+            _ => false,
+        };
         Tile {
             tile_type: tile_type,
             passable: can_pass,
+            solid: is_solid,
             texture: false,
+            regrow: 0.0,
         }
     }
+
+    /*
+        Determines if the tile is a plant that is ready to be picked.
+
+        @return bool Whether the tile is a grown plant.
+    */
+    pub fn is_ripe(&self) -> bool {
+        self.tile_type == TileType::Grune && self.regrow <= 0.0
+    }
 }
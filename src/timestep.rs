@@ -0,0 +1,67 @@
+//! The Timestep object turns uneven frame times into a steady number of fixed updates,
+//! so gameplay runs at the same speed no matter how often the window draws.
+
+pub const FIXED_DT: f64 = 1.0 / 60.0;
+pub const MAX_STEPS_PER_FRAME: u32 = 5;
+
+/**
+    Implementation of the Timestep object.
+
+    @field dt The length of one update in seconds.
+    @field max_steps The most updates allowed in one frame before lag is dropped.
+    @field accumulator Time that has passed but not yet been simulated.
+*/
+pub struct Timestep {
+    pub dt: f64,
+    pub max_steps: u32,
+    accumulator: f64,
+}
+
+impl Timestep {
+    /*
+        Timestep constructor.
+
+        @param dt The length of one update in seconds.
+        @param max_steps The most updates allowed in one frame.
+        @return Timestep Returns itself.
+    */
+    pub fn new(dt: f64, max_steps: u32) -> Self {
+        Timestep {
+            dt: dt,
+            max_steps: max_steps,
+            accumulator: 0.0,
+        }
+    }
+
+    /*
+        Adds a frame's worth of time and determines how many updates to run.
+        If the game has fallen too far behind, the extra time is dropped
+        instead of running an ever growing number of catch up updates.
+
+        @param frame_time Time since the last frame in seconds.
+        @return u32 The number of fixed updates to run.
+    */
+    pub fn advance(&mut self, frame_time: f64) -> u32 {
+        self.accumulator += frame_time.max(0.0);
+        let mut steps = 0;
+        while self.accumulator >= self.dt && steps < self.max_steps {
+            self.accumulator -= self.dt;
+            steps += 1;
+        }
+        if steps == self.max_steps && self.accumulator >= self.dt {
+            // Lag handling: drop whole updates that can't be caught up on.
+            self.accumulator %= self.dt;
+        }
+        steps
+    }
+
+    /*
+        Determines how far the game is between the last update and the next,
+        used to interpolate drawing.
+
+        @return f64 A value from 0 to 1.
+    */
+    pub fn alpha(&self) -> f64 {
+        self.accumulator / self.dt
+    }
+}
@@ -0,0 +1,2090 @@
+//! The World holds all of the game's simulation state and steps it forward.
+//! It does not need a window, so whole play sessions can be driven from tests
+//! by sending Commands and calling update.
+
+use achievement::{generate_achievements, Achievements};
+use ai::*;
+use blueprint::{Blueprint, DEFAULT_SHIP};
+use crafting::{generate_recipes, CraftError, Recipe};
+use creature::{Creature, CreatureState};
+use crew::*;
+use dialogue::*;
+use constants::*;
+use event::{EventBus, GameEvent};
+use input::Action;
+use item::*;
+use map::{Island, Map};
+use misc::*;
+use quest::{generate_quests, QuestLog};
+use rand::*;
+use rand::distributions::Sample;
+use save::SaveData;
+use ship::{BuildAction, BuildError, Ship};
+use std::collections::{HashMap, HashSet};
+use tile::*;
+
+const ITEM_GEN_INTERVAL: f64 = 1000.0 / 60.0;
+const SHIP_COLLISION_STEPS: u32 = 10;
+const LOGS_PER_TREE: u32 = 2;
+const SWORD_SPAWN_CHANCE: f64 = 0.1;
+const GRUNE_REGROW_TIME: f64 = 60.0;
+const ENEMIES_PER_ISLAND: (usize, usize) = (1, 4);
+const SPAWN_ATTEMPTS: usize = 20;
+const SPAWN_MIN_DISTANCE: f64 = 8.0 * IMAGE_SIZE_SCALED;
+const ISLANDER_CHANCE: f64 = 0.5;
+const CREW_REPORTS_KEPT: usize = 5;
+const CLICK_REACH: f64 = 1.5 * IMAGE_SIZE_SCALED;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlayerLocation {
+    OnShip,
+    InWorld,
+}
+
+/*
+    Abstract input for the World, independent of keyboard or window.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    StartMoving(Direction),
+    StopMoving(Direction),
+    MoveAnalog(f64, f64),
+    Interact,
+    InteractAt(f64, f64),
+    ToggleHands,
+    DropItem,
+    Throw,
+    ThrowAt(f64, f64),
+    SelectSlot(usize),
+    TakeDamage,
+    ToggleBuildMode,
+    Build(BuildAction),
+    Craft(usize),
+    QueueCraft(usize),
+    CycleStation(usize),
+    PlotCourse,
+}
+
+/**
+    Implementation of the World object.
+
+    @field player The main player.
+    @field npcs Other creatures in the world, such as enemies spawned on islands.
+    @field crew Islanders who can be recruited, and the crew already recruited.
+    @field ship The player's airship.
+    @field player_location Player's worldly position (see above).
+    @field item_prototypes Prototyping pattern for cloning items.
+    @field enemy_prototypes Prototyping pattern for cloning enemies.
+    @field recipes Registry of crafting recipes.
+    @field items_in_game Set of all items in the game.
+    @field map The world map.
+    @field time_since_last_gen Seconds since an item was last generated.
+    @field view_width Width of the visible area, used for placing items.
+    @field view_height Height of the visible area, used for placing items.
+    @field build_cursor The ship tile being edited in build mode, or None outside of it.
+    @field build_error Why the last change in build mode was refused, if it was.
+    @field craft_error Why the last crafting attempt failed, if it did.
+    @field waypoint Where crew at the wheel steer the ship's centre to, in world coordinates.
+    @field craft_queue Recipes for crew at a workbench to craft, in order.
+    @field crew_reports What the crew did lately, oldest first.
+    @field dialogues Registry of dialogue trees, keyed by name.
+    @field conversation The conversation being had, if any. It takes over the player's input.
+    @field flags Story flags set by dialogue.
+    @field quests The quests and how far the player has got with them.
+    @field achievements The achievements and the player's progress towards them.
+    @field events Events that happened since the last update, waiting to be dispatched.
+    @field spawned_islands Ids of the loaded islands that have been populated.
+    @field cleared_islands Ids of the islands whose enemies have all been defeated.
+    @field peaceful Whether enemy spawning is turned off.
+    @field load_errors Why assets couldn't be loaded, if any couldn't, for telling the player.
+*/
+pub struct World {
+    pub player: Creature,
+    pub npcs: Vec<Creature>,
+    pub crew: Vec<CrewMember>,
+    pub ship: Ship,
+    pub player_location: PlayerLocation,
+    pub item_prototypes: HashMap<String, Item>,
+    pub enemy_prototypes: HashMap<String, Creature>,
+    pub recipes: Vec<Recipe>,
+    pub items_in_game: Vec<Item>,
+    pub map: Map,
+    pub time_since_last_gen: f64,
+    pub view_width: f64,
+    pub view_height: f64,
+    pub build_cursor: Option<(i32, i32)>,
+    pub build_error: Option<BuildError>,
+    pub craft_error: Option<CraftError>,
+    pub waypoint: Option<(f64, f64)>,
+    pub craft_queue: Vec<usize>,
+    pub crew_reports: Vec<String>,
+    pub dialogues: HashMap<String, Dialogue>,
+    pub conversation: Option<Conversation>,
+    pub flags: HashSet<String>,
+    pub quests: QuestLog,
+    pub achievements: Achievements,
+    pub events: EventBus,
+    pub spawned_islands: HashSet<u64>,
+    pub cleared_islands: HashSet<u64>,
+    pub peaceful: bool,
+    pub load_errors: Vec<String>,
+}
+
+impl World {
+    /*
+        World constructor. Places the default ship above the island closest
+        to the origin, with the player on board at its spawn point.
+        Assets that can't be loaded are noted in load_errors, falling back to the built-in
+        ship and no dialogue.
+
+        @param map The world map.
+        @return World Returns itself.
+    */
+    pub fn new(map: Map) -> Self {
+        let mut load_errors = vec![];
+        let blueprint = match Blueprint::load(DEFAULT_SHIP) {
+            Ok(blueprint) => blueprint,
+            Err(e) => {
+                load_errors.push(format!("Could not load ship: {}", e));
+                Blueprint::built_in()
+            }
+        };
+        let dialogues = match Dialogue::load_all() {
+            Ok(dialogues) => dialogues,
+            Err(e) => {
+                load_errors.push(format!("Could not load dialogue: {}", e));
+                HashMap::new()
+            }
+        };
+
+        let mut world = World {
+            player: Creature::new(),
+            npcs: vec![],
+            crew: vec![],
+            ship: Ship::from_blueprint(&blueprint),
+            player_location: PlayerLocation::OnShip,
+            item_prototypes: generate_item_prototypes(),
+            enemy_prototypes: generate_enemy_prototypes(),
+            recipes: generate_recipes(),
+            items_in_game: vec![],
+            map: map,
+            time_since_last_gen: 0.0,
+            view_width: 800.0,
+            view_height: 640.0,
+            build_cursor: None,
+            build_error: None,
+            craft_error: None,
+            waypoint: None,
+            craft_queue: vec![],
+            crew_reports: vec![],
+            dialogues: dialogues,
+            conversation: None,
+            flags: HashSet::new(),
+            quests: QuestLog::new(generate_quests()),
+            achievements: Achievements::new(generate_achievements()),
+            events: EventBus::new(),
+            spawned_islands: HashSet::new(),
+            cleared_islands: HashSet::new(),
+            peaceful: false,
+            load_errors: load_errors,
+        };
+
+        // Initial ship position, above the island closest to the origin.
+        world.map.load_around(0.0, 0.0);
+        let (start_x, start_y) = match world.map.nearest_island(0.0, 0.0) {
+            Some(island) => island.center(),
+            None => (0.0, 0.0),
+        };
+        world.ship.x = start_x * IMAGE_SIZE_SCALED;
+        world.ship.y = start_y * IMAGE_SIZE_SCALED;
+        world.player.x = world.ship.x + blueprint.spawn.0 as f64 * IMAGE_SIZE_SCALED;
+        world.player.y = world.ship.y + blueprint.spawn.1 as f64 * IMAGE_SIZE_SCALED;
+        world.player.store_last_position();
+        world.ship.store_last_position();
+        world.load_nearby_chunks();
+
+        // Temporary item generation.
+        let bisket = world.item_prototypes
+            .get("bisket")
+            .unwrap()
+            .generate_clone(world.ship.x + 128.0, world.ship.y + 128.0);
+        world.items_in_game.push(bisket);
+
+        world
+    }
+
+    /*
+        Replaces the world's state with saved data.
+
+        @param data The state recovered from a save.
+    */
+    pub fn load(&mut self, data: SaveData) {
+        self.player = data.player;
+        self.ship = data.ship;
+        self.player_location = data.player_location;
+        self.items_in_game = data.items_in_game;
+        self.map = data.map;
+        // Only the crew are saved; enemies and islanders on islands are populated afresh.
+        self.npcs.clear();
+        self.crew = data.crew;
+        self.flags = data.flags;
+        self.cleared_islands = data.cleared_islands;
+        self.quests.progress = data.quests;
+        self.quests.reached = data.reached;
+        self.quests.start_available();
+        self.events.pending.clear();
+        self.conversation = None;
+        self.waypoint = None;
+        self.craft_queue.clear();
+        self.crew_reports.clear();
+        self.spawned_islands.clear();
+        self.player.store_last_position();
+        self.ship.store_last_position();
+        self.load_nearby_chunks();
+    }
+
+    /*
+        Streams in the map around the player, wherever the ship has taken them.
+    */
+    pub fn load_nearby_chunks(&mut self) {
+        let (x, y) = self.player_tile();
+        self.map.load_around(x as f64, y as f64);
+    }
+
+    /*
+        Finds the middle of the player.
+
+        @return (f64, f64) The x and y position, in world coordinates.
+    */
+    pub fn player_center(&self) -> (f64, f64) {
+        (
+            self.player.x + IMAGE_SIZE_SCALED / 2.0,
+            self.player.y + IMAGE_SIZE_SCALED / 2.0,
+        )
+    }
+
+    /*
+        Finds the tile at a point, like the one under the mouse cursor. On board, that's
+        a tile of the ship, otherwise a tile of the map.
+
+        @param x The point's x, in world coordinates.
+        @param y The point's y, in world coordinates.
+        @return Option<(i32, i32)> The tile's column and row, relative to the ship when
+        on board, or None if the point is off the ship.
+    */
+    pub fn tile_at(&self, x: f64, y: f64) -> Option<(i32, i32)> {
+        match self.player_location {
+            PlayerLocation::OnShip => {
+                let column = ((x - self.ship.x) / IMAGE_SIZE_SCALED).floor();
+                let row = ((y - self.ship.y) / IMAGE_SIZE_SCALED).floor();
+                if column < 0.0 || row < 0.0 || column >= self.ship.width || row >= self.ship.height
+                {
+                    return None;
+                }
+                Some((column as i32, row as i32))
+            }
+            PlayerLocation::InWorld => Some(tile_position(x, y)),
+        }
+    }
+
+    /*
+        Determines whether a point is close enough to the player to click on.
+
+        @param x The point's x, in world coordinates.
+        @param y The point's y, in world coordinates.
+        @return bool Whether it's in reach.
+    */
+    pub fn in_reach(&self, x: f64, y: f64) -> bool {
+        let (center_x, center_y) = self.player_center();
+        (x - center_x).hypot(y - center_y) <= CLICK_REACH
+    }
+
+    /*
+        Determines which map tile the middle of the player is over.
+
+        @return (i32, i32) The x and y position of the tile.
+    */
+    pub fn player_tile(&self) -> (i32, i32) {
+        tile_position(
+            self.player.x + IMAGE_SIZE_SCALED / 2.0,
+            self.player.y + IMAGE_SIZE_SCALED / 2.0,
+        )
+    }
+
+    /*
+        Handles updating of the World, most importantly player position.
+
+        @param dt The time step in seconds.
+    */
+    pub fn update(&mut self, dt: f64) {
+        self.player.store_last_position();
+        self.ship.store_last_position();
+        for npc in self.npcs.iter_mut() {
+            npc.store_last_position();
+        }
+        for member in self.crew.iter_mut() {
+            member.creature.store_last_position();
+        }
+
+        if self.time_since_last_gen > ITEM_GEN_INTERVAL {
+            self.time_since_last_gen = 0.0;
+
+            let mut rng = thread_rng();
+            let mut range_x =
+                distributions::Range::new(-1.0 * self.view_width / 2.0, self.view_width / 2.0);
+            let mut range_y =
+                distributions::Range::new(-1.0 * self.view_height / 2.0, self.view_height / 2.0);
+
+            let item_x = self.player.x + range_x.sample(&mut rng);
+            let item_y = self.player.y + range_y.sample(&mut rng);
+
+            // Mostly food, with the occasional sword.
+            let name = if rng.gen::<f64>() < SWORD_SPAWN_CHANCE {
+                "sword"
+            } else {
+                "bisket"
+            };
+            self.items_in_game.push(
+                self.item_prototypes
+                    .get(name)
+                    .unwrap()
+                    .generate_clone(item_x, item_y),
+            );
+        }
+        self.time_since_last_gen += dt;
+        self.map.update(dt);
+        self.update_items(dt);
+        self.player.update_timers(dt);
+        self.update_npcs(dt);
+
+        match self.player_location {
+            PlayerLocation::OnShip => {
+                self.ship.update_velocity(dt);
+                self.move_ship(dt);
+                self.player.other_vel_x = self.ship.self_vel_x;
+                self.player.other_vel_y = self.ship.self_vel_y;
+                self.player.update_position_other(dt);
+                let x = self.player.x_to_be_location(dt);
+                let y = self.player.y_to_be_location(dt);
+                if self.is_on_ship(x, y) {
+                    self.player.update_position_self(dt);
+                    self.player.update_direction();
+                }
+            }
+            PlayerLocation::InWorld => {
+                let x = self.player.x_to_be_location(dt);
+                let y = self.player.y_to_be_location(dt);
+                if self.can_go_to(x, y) {
+                    self.player.update_position_self(dt);
+                    self.player.update_direction();
+                }
+            }
+        }
+        self.update_crew(dt);
+        self.publish_tile_entered();
+        self.load_nearby_chunks();
+        self.unload_distant_npcs();
+        if let Some(island) = self.island_under_player().cloned() {
+            if self.map.discover(&island) {
+                self.events.publish(GameEvent::IslandDiscovered(island.id));
+            }
+            let landed = self.player_location == PlayerLocation::InWorld;
+            if landed && !self.quests.reached.contains(&island.id) {
+                self.events.publish(GameEvent::IslandReached(island.id));
+            }
+        }
+        self.populate_islands();
+        self.dispatch_events();
+    }
+
+    /*
+        Tells observers when the player has stepped onto another tile during the update.
+        On the ship, tiles are counted relative to it, so flying along doesn't count.
+    */
+    fn publish_tile_entered(&mut self) {
+        let half = IMAGE_SIZE_SCALED / 2.0;
+        let (before, now) = match self.player_location {
+            PlayerLocation::OnShip => (
+                tile_position(
+                    self.player.last_x - self.ship.last_x + half,
+                    self.player.last_y - self.ship.last_y + half,
+                ),
+                self.player_ship_tile(),
+            ),
+            PlayerLocation::InWorld => (
+                tile_position(self.player.last_x + half, self.player.last_y + half),
+                self.player_tile(),
+            ),
+        };
+        if before != now {
+            self.publish_tile_under_player();
+        }
+    }
+
+    /*
+        Tells observers the player is on the tile under them.
+    */
+    fn publish_tile_under_player(&mut self) {
+        let location = self.player_location.clone();
+        if let Some(tile) = self.tile_under_player(location) {
+            self.events.publish(GameEvent::TileEntered(tile.tile_type));
+        }
+    }
+
+    /*
+        Dispatches the events since the last update to the quests and achievements,
+        granting the rewards of quests finished, then chooses islands for quests waiting
+        to be sent to one.
+    */
+    fn dispatch_events(&mut self) {
+        self.events
+            .dispatch(&mut [&mut self.quests, &mut self.achievements]);
+        let finished: Vec<usize> = self.quests.unrewarded.drain(..).collect();
+        for i in finished {
+            for (item_type, count) in self.quests.quests[i].reward.clone() {
+                self.give(&item_type, count);
+            }
+        }
+        for name in self.quests.needing_target() {
+            if let Some(island) = self.nearest_unreached_island() {
+                self.quests.set_target(&name, island);
+            }
+        }
+    }
+
+    /*
+        Finds the loaded island closest to the player that they haven't set foot on.
+
+        @return Option<u64> The island's id, if one is loaded.
+    */
+    pub fn nearest_unreached_island(&self) -> Option<u64> {
+        let (x, y) = self.player_tile();
+        let distance = |island: &Island| {
+            let (center_x, center_y) = island.center();
+            (center_x - x as f64).hypot(center_y - y as f64)
+        };
+        self.map
+            .islands()
+            .into_iter()
+            .filter(|island| !self.quests.reached.contains(&island.id))
+            .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap())
+            .map(|island| island.id)
+    }
+
+    /*
+        Removes the enemies on islands whose chunks have been dropped. Their islands are
+        populated again once loaded.
+    */
+    fn unload_distant_npcs(&mut self) {
+        let map = &self.map;
+        self.npcs.retain(|npc| {
+            let (x, y) = tile_position(npc.x, npc.y);
+            map.tile(x, y).is_some()
+        });
+        let loaded: HashSet<u64> = map.islands().iter().map(|island| island.id).collect();
+        self.spawned_islands.retain(|id| loaded.contains(id));
+    }
+
+    /*
+        Populates newly loaded islands with enemies, unless they've been cleared, and
+        sometimes an islander who can be recruited. Each island's inhabitants are chosen
+        from the map seed, so a world always spawns the same ones.
+    */
+    pub fn populate_islands(&mut self) {
+        let islands: Vec<Island> = self.map
+            .islands()
+            .into_iter()
+            .filter(|island| !self.spawned_islands.contains(&island.id))
+            .cloned()
+            .collect();
+        let mut names: Vec<String> = self.enemy_prototypes.keys().cloned().collect();
+        names.sort();
+
+        for island in islands {
+            self.spawned_islands.insert(island.id);
+            let mut rng = StdRng::from_seed(&[self.map.seed as usize, island.id as usize][..]);
+
+            if rng.gen::<f64>() < ISLANDER_CHANCE {
+                let name = CREW_NAMES[rng.gen_range(0, CREW_NAMES.len())];
+                let role = Role::all()[rng.gen_range(0, Role::all().len())];
+                // Islanders already recruited, e.g. before a load, aren't found again.
+                // Crew saved before they knew their home are matched by name and role.
+                let recruited = self.crew.iter().any(|member| match member.home {
+                    Some(home) => home == island.id,
+                    None => member.name == name && member.role == role,
+                });
+                if let (Some((x, y)), false) = (self.spawn_spot(&island, &mut rng), recruited) {
+                    let mut islander = CrewMember::new(name, role, x, y);
+                    islander.home = Some(island.id);
+                    self.crew.push(islander);
+                }
+            }
+
+            if self.peaceful || self.cleared_islands.contains(&island.id) {
+                continue;
+            }
+            let count = rng.gen_range(ENEMIES_PER_ISLAND.0, ENEMIES_PER_ISLAND.1);
+            for _ in 0..count {
+                let name = &names[rng.gen_range(0, names.len())];
+                let (x, y) = match self.spawn_spot(&island, &mut rng) {
+                    Some(spot) => spot,
+                    None => break,
+                };
+                let mut enemy = self.enemy_prototypes[name].clone();
+                enemy.x = x;
+                enemy.y = y;
+                enemy.store_last_position();
+                self.npcs.push(enemy);
+            }
+        }
+    }
+
+    /*
+        Picks a random place on an island to spawn a creature, on a passable tile
+        out of the player's sight.
+
+        @param island The island.
+        @param rng Random number generator seeded for the island.
+        @return Option<(f64, f64)> The position, if one was found.
+    */
+    fn spawn_spot<R: Rng>(&self, island: &Island, rng: &mut R) -> Option<(f64, f64)> {
+        for _ in 0..SPAWN_ATTEMPTS {
+            let x = (island.x + rng.gen_range(0, island.width as i32)) as f64 * IMAGE_SIZE_SCALED;
+            let y = (island.y + rng.gen_range(0, island.height as i32)) as f64 * IMAGE_SIZE_SCALED;
+            let far_enough = (x - self.player.x).hypot(y - self.player.y) >= SPAWN_MIN_DISTANCE;
+            if far_enough && self.can_go_to(x, y) {
+                return Some((x, y));
+            }
+        }
+        None
+    }
+
+    /*
+        Carries out a command from the player.
+
+        @param command The command.
+    */
+    pub fn execute(&mut self, command: Command) {
+        if self.conversation.is_some() {
+            self.execute_conversation(command);
+            return;
+        }
+        match command {
+            Command::StartMoving(dir) if self.build_cursor.is_some() => self.move_build_cursor(dir),
+            Command::StartMoving(dir) => self.execute_move(dir, true),
+            Command::StopMoving(dir) => self.execute_move(dir, false),
+            Command::MoveAnalog(..) if self.build_cursor.is_some() => {}
+            Command::MoveAnalog(x, y) => self.execute_analog(x, y),
+            Command::Interact
+            | Command::InteractAt(..)
+            | Command::ToggleHands
+            | Command::DropItem
+            | Command::Throw
+            | Command::ThrowAt(..) if self.build_cursor.is_some() => {}
+            Command::Interact => self.execute_action(),
+            Command::ToggleHands => self.execute_player_hands(),
+            Command::DropItem => self.drop_held_item(),
+            Command::Throw => self.throw_held_item(),
+            Command::InteractAt(x, y) => self.interact_at(x, y),
+            Command::ThrowAt(x, y) => self.throw_at(x, y),
+            Command::SelectSlot(slot) => self.player.inventory.select(slot),
+            Command::TakeDamage => self.hurt_player(1),
+            Command::ToggleBuildMode => self.toggle_build_mode(),
+            Command::Build(action) => self.build_error = self.build(action).err(),
+            Command::Craft(recipe) => self.craft_error = self.craft(recipe).err(),
+            Command::QueueCraft(recipe) => self.craft_error = self.queue_craft(recipe).err(),
+            Command::CycleStation(member) => self.cycle_station(member),
+            Command::PlotCourse => {
+                self.plot_course();
+            }
+        }
+    }
+
+    /*
+        Enters build mode when standing at the wheel or a workbench, or leaves it.
+    */
+    fn toggle_build_mode(&mut self) {
+        if self.build_cursor.is_some() {
+            self.build_cursor = None;
+            return;
+        }
+        let at_station = match self.tile_under_player(PlayerLocation::OnShip) {
+            Some(tile) => match tile.tile_type {
+                TileType::Wheel | TileType::Workbench => true,
+                _ => false,
+            },
+            None => false,
+        };
+        if self.player_location != PlayerLocation::OnShip || !at_station {
+            return;
+        }
+
+        // Let go of the wheel and stand still while building.
+        if self.player.creature_state == CreatureState::ControllingShip {
+            self.player.change_control_state();
+            self.ship.reset_dir();
+        }
+        for dir in &[Direction::N, Direction::E, Direction::S, Direction::W] {
+            self.player.handle_input(Action::moving(*dir), false);
+        }
+        self.player.handle_analog(0.0, 0.0);
+        self.player.update_self_velocity();
+
+        self.build_cursor = Some(self.player_ship_tile());
+        self.build_error = None;
+    }
+
+    /*
+        Moves the build cursor a tile, up to one tile outside the ship.
+
+        @param dir The direction to move in.
+    */
+    fn move_build_cursor(&mut self, dir: Direction) {
+        if let Some((x, y)) = self.build_cursor {
+            let (x, y) = match dir {
+                Direction::N => (x, y - 1),
+                Direction::S => (x, y + 1),
+                Direction::W => (x - 1, y),
+                Direction::E => (x + 1, y),
+            };
+            let x = x.max(-1).min(self.ship.width as i32);
+            let y = y.max(-1).min(self.ship.height as i32);
+            self.build_cursor = Some((x, y));
+        }
+    }
+
+    /*
+        Changes the ship tile under the build cursor, paying for it with carried logs or planks.
+        Removed floor is refunded into the inventory, or at the player's feet if it doesn't fit.
+
+        @param action The change to make.
+        @return Result<(), BuildError> Why the change was refused, if it was.
+    */
+    pub fn build(&mut self, action: BuildAction) -> Result<(), BuildError> {
+        let (x, y) = match self.build_cursor {
+            Some(cursor) => cursor,
+            None => return Err(BuildError::NotBuilding),
+        };
+        let material = ItemType::Resource(action.material());
+        if self.player.inventory.count(&material) < action.cost() {
+            return Err(BuildError::NoMaterials(action.material()));
+        }
+        if action == BuildAction::RemoveFloor && self.player_ship_tile() == (x, y) {
+            return Err(BuildError::StandingThere);
+        }
+        if action == BuildAction::AddFloor
+            && !self.ship_tile_fits(self.ship.x, self.ship.y, x, y)
+        {
+            return Err(BuildError::Blocked);
+        }
+
+        let (ship_x, ship_y) = (self.ship.x, self.ship.y);
+        self.ship.build(action, x, y)?;
+        self.events.publish(GameEvent::ShipBuilt(action));
+
+        // The ship may have grown or shrunk at its top or left, keep the cursor and the
+        // crew's stations in place.
+        let shift_x = ((ship_x - self.ship.x) / IMAGE_SIZE_SCALED).round() as i32;
+        let shift_y = ((ship_y - self.ship.y) / IMAGE_SIZE_SCALED).round() as i32;
+        self.build_cursor = Some((x + shift_x, y + shift_y));
+        for member in self.crew.iter_mut() {
+            if let Some((station_x, station_y)) = member.station {
+                let (station_x, station_y) = (station_x as i32 + shift_x, station_y as i32 + shift_y);
+                member.station = if station_x >= 0 && station_y >= 0 {
+                    Some((station_x as usize, station_y as usize))
+                } else {
+                    None
+                };
+            }
+        }
+
+        if action.cost() > 0 {
+            self.player.inventory.remove(&material, action.cost());
+        } else if action == BuildAction::RemoveFloor {
+            let mut logs = self.item_prototypes
+                .get("logs")
+                .unwrap()
+                .generate_clone(self.player.x, self.player.y);
+            logs.collected = true;
+            if !self.player.inventory.can_add(&logs) {
+                self.release(logs);
+            } else {
+                self.player.pickup_item(logs);
+            }
+        }
+        Ok(())
+    }
+
+    /*
+        Crafts a recipe from the player's inventory, using the tile they stand on as the station.
+
+        @param recipe The recipe's index in the registry.
+        @return Result<(), CraftError> Why crafting failed, if it did.
+    */
+    pub fn craft(&mut self, recipe: usize) -> Result<(), CraftError> {
+        let location = self.player_location.clone();
+        let station = self.tile_under_player(location).map(|tile| tile.tile_type);
+        match self.recipes.get(recipe) {
+            Some(recipe) => recipe.craft(
+                &mut self.player.inventory,
+                station.as_ref(),
+                &self.item_prototypes,
+            ),
+            None => Err(CraftError::UnknownRecipe),
+        }
+    }
+
+    /*
+        Creates a blueprint of the ship, spawning on the player's tile if they're on board,
+        otherwise on the wheel.
+
+        @param name The blueprint's name.
+        @return Blueprint The blueprint.
+    */
+    pub fn ship_blueprint(&self, name: &str) -> Blueprint {
+        let (x, y) = self.player_ship_tile();
+        let on_board = self.player_location == PlayerLocation::OnShip
+            && self.ship.tile_type_at(x, y) != TileType::Air;
+        let mut spawn = (x as usize, y as usize);
+        if !on_board {
+            for (i, column) in self.ship.tiles.iter().enumerate() {
+                for (j, tile) in column.iter().enumerate() {
+                    if tile.tile_type == TileType::Wheel {
+                        spawn = (i, j);
+                    }
+                }
+            }
+        }
+        Blueprint::from_ship(&self.ship, name, spawn)
+    }
+
+    /*
+        Determines which ship tile the middle of the player is over.
+
+        @return (i32, i32) The tile's column and row, relative to the ship.
+    */
+    pub fn player_ship_tile(&self) -> (i32, i32) {
+        (
+            ((self.player.x + IMAGE_SIZE_SCALED / 2.0 - self.ship.x) / IMAGE_SIZE_SCALED).floor()
+                as i32,
+            ((self.player.y + IMAGE_SIZE_SCALED / 2.0 - self.ship.y) / IMAGE_SIZE_SCALED).floor()
+                as i32,
+        )
+    }
+
+    /*
+        Determines the tile the player is standing on.
+
+        @param location Whether to look at the ship or the world below.
+        @return Option<TileType> Some tile.
+    */
+    pub fn tile_under_player(&self, location: PlayerLocation) -> Option<Tile> {
+        let x = self.player.x + IMAGE_SIZE_SCALED as f64 / 2.0;
+        let y = self.player.y + IMAGE_SIZE_SCALED as f64 / 2.0;
+        let iss = IMAGE_SIZE_SCALED as f64;
+        match location {
+            PlayerLocation::OnShip => {
+                let is_in_x = x >= self.ship.x && x + iss <= self.ship.x + self.ship.width * iss;
+                let is_in_y = y >= self.ship.y && y + iss <= self.ship.y + self.ship.height * iss;
+                if is_in_x && is_in_y {
+                    return Some(
+                        self.ship.tiles[((x - self.ship.x) / IMAGE_SIZE_SCALED).floor() as usize]
+                            [((y - self.ship.y) / IMAGE_SIZE_SCALED).floor() as usize]
+                            .clone(),
+                    );
+                }
+            }
+            PlayerLocation::InWorld => {
+                let (tile_x, tile_y) = tile_position(x, y);
+                return self.map.tile(tile_x, tile_y).cloned();
+            }
+        }
+        None
+    }
+
+    /*
+        Finds the island the player is over, if any.
+
+        @return Option<&Island> The island.
+    */
+    pub fn island_under_player(&self) -> Option<&Island> {
+        let (x, y) = self.player_tile();
+        self.map.island_at(x, y)
+    }
+
+    /*
+        Determines whether some x and y can be stepped on.
+
+        @param x Some x coordinate.
+        @param y Some y coordinate.
+        @return bool Whether the respective tile is passable.
+    */
+    pub fn can_go_to(&self, x: f64, y: f64) -> bool {
+        let x = x / IMAGE_SIZE_SCALED;
+        let y = y / IMAGE_SIZE_SCALED;
+        // Every tile the player would overlap must be loaded and passable.
+        let passable = |tile_x: f64, tile_y: f64| match self.map.tile(tile_x as i32, tile_y as i32) {
+            Some(tile) => tile.passable,
+            None => false,
+        };
+        passable(x.floor(), y.floor())
+            && passable(x.floor(), y.ceil())
+            && passable(x.ceil(), y.floor())
+            && passable(x.ceil(), y.ceil())
+    }
+
+    /*
+        Moves the ship by its velocity, stopping it flush against solid tiles.
+        Each axis is checked separately, so the ship slides along walls it hits at an angle.
+        Velocity into a wall is lost.
+
+        @param dt The time step in seconds.
+    */
+    pub fn move_ship(&mut self, dt: f64) {
+        let (x, y) = (self.ship.x, self.ship.y);
+        self.ship.self_vel_x *= self.ship_move_fraction(x, y, self.ship.self_vel_x, 0.0, dt);
+        let x = x + self.ship.self_vel_x * dt;
+        self.ship.self_vel_y *= self.ship_move_fraction(x, y, 0.0, self.ship.self_vel_y, dt);
+        self.ship.update_position(dt);
+    }
+
+    /*
+        Determines how much of a move the ship can make before hitting something.
+
+        @param x The ship's x coordinate before the move.
+        @param y The ship's y coordinate before the move.
+        @param vel_x The horizontal velocity.
+        @param vel_y The vertical velocity.
+        @param dt The time step in seconds.
+        @return f64 The fraction of the move, from 0 to 1.
+    */
+    fn ship_move_fraction(&self, x: f64, y: f64, vel_x: f64, vel_y: f64, dt: f64) -> f64 {
+        let fits = |fraction: f64| {
+            self.ship_fits(x + vel_x * fraction * dt, y + vel_y * fraction * dt)
+        };
+        if fits(1.0) || !fits(0.0) {
+            // A ship already stuck in terrain may fly out of it.
+            return 1.0;
+        }
+        // Binary search for the furthest free position.
+        let mut free = 0.0;
+        let mut blocked = 1.0;
+        for _ in 0..SHIP_COLLISION_STEPS {
+            let middle = (free + blocked) / 2.0;
+            if fits(middle) {
+                free = middle;
+            } else {
+                blocked = middle;
+            }
+        }
+        free
+    }
+
+    /*
+        Determines whether the ship's footprint, its non-Air tiles, would be clear
+        of solid map tiles at some position.
+
+        @param x Some x coordinate for the ship.
+        @param y Some y coordinate for the ship.
+        @return bool Whether the ship fits there.
+    */
+    pub fn ship_fits(&self, x: f64, y: f64) -> bool {
+        for (i, column) in self.ship.tiles.iter().enumerate() {
+            for (j, tile) in column.iter().enumerate() {
+                let solid = tile.tile_type != TileType::Air;
+                if solid && !self.ship_tile_fits(x, y, i as i32, j as i32) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /*
+        Determines whether one tile of the ship would be clear of solid map tiles.
+
+        @param x Some x coordinate for the ship.
+        @param y Some y coordinate for the ship.
+        @param column The tile's column, which may be just outside the ship.
+        @param row The tile's row, which may be just outside the ship.
+        @return bool Whether the tile fits there.
+    */
+    fn ship_tile_fits(&self, x: f64, y: f64, column: i32, row: i32) -> bool {
+        let left = (x / IMAGE_SIZE_SCALED + column as f64).floor() as i32;
+        let top = (y / IMAGE_SIZE_SCALED + row as f64).floor() as i32;
+        let right = (x / IMAGE_SIZE_SCALED + column as f64 + 1.0).ceil() as i32 - 1;
+        let bottom = (y / IMAGE_SIZE_SCALED + row as f64 + 1.0).ceil() as i32 - 1;
+        for map_x in left..right + 1 {
+            for map_y in top..bottom + 1 {
+                // Chunks that aren't loaded are open sky.
+                if let Some(map_tile) = self.map.tile(map_x, map_y) {
+                    if map_tile.solid {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /*
+        Checks whether a specific x,y position is on the ship.
+        Used for collision detection.
+
+        @param x Some x coordinate.
+        @param y Some y coordinate.
+        @return bool Whether the x,y coordinate is within the ship.
+    */
+    pub fn is_on_ship(&self, x: f64, y: f64) -> bool {
+        let ship_x = self.ship.x;
+        let ship_y = self.ship.y;
+
+        // Check edges.
+        let is_in_x =
+            x >= ship_x && x + IMAGE_SIZE_SCALED <= ship_x + self.ship.width * IMAGE_SIZE_SCALED;
+        let is_in_y =
+            y >= ship_y && y + IMAGE_SIZE_SCALED <= ship_y + self.ship.height * IMAGE_SIZE_SCALED;
+        if is_in_x && is_in_y {
+            // Check surrounding tiles.
+            let ship_tile_x = (x - ship_x) / IMAGE_SIZE_SCALED;
+            let ship_tile_y = (y - ship_y) / IMAGE_SIZE_SCALED;
+            if self.ship.tiles[ship_tile_x.floor() as usize][ship_tile_y.floor() as usize].passable
+                && self.ship.tiles[ship_tile_x.floor() as usize][ship_tile_y.ceil() as usize]
+                    .passable
+                && self.ship.tiles[ship_tile_x.ceil() as usize][ship_tile_y.floor() as usize]
+                    .passable
+                && self.ship.tiles[ship_tile_x.ceil() as usize][ship_tile_y.ceil() as usize]
+                    .passable
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    /*
+        Changes player position in the world.
+    */
+    fn change_player_location(&mut self) {
+        self.player_location = match self.player_location {
+            PlayerLocation::OnShip => PlayerLocation::InWorld,
+            PlayerLocation::InWorld => PlayerLocation::OnShip,
+        };
+        self.player.creature_state = CreatureState::Normal;
+    }
+
+    /*
+        Handles "pickup" and "drop" for the player. Picks up a nearby item that fits in the
+        inventory, otherwise drops the held item.
+    */
+    fn execute_player_hands(&mut self) {
+        let mut place = -1;
+        for i in 0..self.items_in_game.len() {
+            // Determines if player is in range of item.
+            let diff_x = self.items_in_game[i].x - self.player.x;
+            let diff_y = self.items_in_game[i].y - self.player.y;
+            if diff_x < IMAGE_SIZE_SCALED && diff_x > -IMAGE_SIZE_SCALED
+                && diff_y < IMAGE_SIZE_SCALED
+                && diff_y > -IMAGE_SIZE_SCALED
+                && self.player.inventory.can_add(&self.items_in_game[i])
+            {
+                place = i as i32;
+                break;
+            }
+        }
+        if place != -1 {
+            self.pick_up(place as usize);
+        } else {
+            self.drop_held_item();
+        }
+    }
+
+    /*
+        Moves an item from the world into the player's inventory. Only the first time the
+        player gets an item counts as collecting it.
+
+        @param item The item's index in items_in_game.
+    */
+    fn pick_up(&mut self, item: usize) {
+        let item = self.items_in_game.remove(item);
+        if !item.collected {
+            self.events.publish(GameEvent::Collected(item.item_type.clone()));
+        }
+        self.player.pickup_item(item);
+    }
+
+    /*
+        Handles a click in reach of the player. Clicking an item picks it up, clicking the
+        player's own tile acts on it like Interact, and clicking a tile around them turns
+        to face it, then uses or harvests it.
+
+        @param x The point clicked, in world coordinates.
+        @param y The point clicked, in world coordinates.
+    */
+    fn interact_at(&mut self, x: f64, y: f64) {
+        if !self.in_reach(x, y) {
+            return;
+        }
+        let clicked = self.items_in_game.iter().position(|item| {
+            !item.is_moving()
+                && overlaps([item.x, item.y, IMAGE_SIZE_SCALED, IMAGE_SIZE_SCALED], [x, y, 0.0, 0.0])
+                && self.player.inventory.can_add(item)
+        });
+        if let Some(item) = clicked {
+            self.pick_up(item);
+            return;
+        }
+        let (center_x, center_y) = self.player_center();
+        if self.tile_at(x, y) == self.tile_at(center_x, center_y) {
+            self.execute_action();
+        } else {
+            self.player.face_towards(x - center_x, y - center_y);
+            self.use_or_harvest();
+        }
+    }
+
+    /*
+        Throws one of the held item towards a point, like the mouse cursor.
+
+        @param x The point aimed at, in world coordinates.
+        @param y The point aimed at, in world coordinates.
+    */
+    fn throw_at(&mut self, x: f64, y: f64) {
+        let (center_x, center_y) = self.player_center();
+        let (dx, dy) = (x - center_x, y - center_y);
+        if dx == 0.0 && dy == 0.0 {
+            self.throw_held_item();
+            return;
+        }
+        self.player.face_towards(dx, dy);
+        if let Some(mut item) = self.player.drop_item() {
+            item.throw_towards(dx, dy);
+            self.release(item);
+        }
+    }
+
+    /*
+        Throws one of the held item in the direction the player is facing.
+    */
+    fn throw_held_item(&mut self) {
+        if let Some(mut item) = self.player.drop_item() {
+            item.throw(self.player.facing());
+            self.release(item);
+        }
+    }
+
+    /*
+        Moves thrown items, stopping them at tiles they can't pass and at creatures they hit.
+        Items on the ship are kept to its floor, wherever the player has gone since.
+        Creatures that are hit take the item's damage, and are removed if it kills them.
+
+        @param dt The time step in seconds.
+    */
+    fn update_items(&mut self, dt: f64) {
+        for i in 0..self.items_in_game.len() {
+            if !self.items_in_game[i].is_moving() {
+                continue;
+            }
+            self.items_in_game[i].update_velocity(dt);
+            let (x, y) = self.items_in_game[i].to_be_location(dt);
+            let clear = if self.items_in_game[i].aboard {
+                self.is_on_ship(x, y)
+            } else {
+                self.can_go_to(x, y)
+            };
+            let item = &mut self.items_in_game[i];
+            if !clear {
+                item.stop();
+                continue;
+            }
+            item.x = x;
+            item.y = y;
+
+            let item_box = [item.x, item.y, IMAGE_SIZE_SCALED, IMAGE_SIZE_SCALED];
+            let hit = self.npcs
+                .iter_mut()
+                .find(|npc| overlaps(npc.hitbox(), item_box));
+            if let Some(npc) = hit {
+                npc.take_damage(item.damage);
+                item.stop();
+            }
+        }
+        self.remove_dead_npcs();
+    }
+
+    /*
+        Removes the creatures that have been killed. An island is cleared once the last
+        enemy on it is.
+    */
+    fn remove_dead_npcs(&mut self) {
+        let emptied: Vec<u64> = self.npcs
+            .iter()
+            .filter(|npc| npc.is_dead())
+            .filter_map(|npc| self.npc_island(npc))
+            .collect();
+        let before = self.npcs.len();
+        self.npcs.retain(|npc| !npc.is_dead());
+        for _ in self.npcs.len()..before {
+            self.events.publish(GameEvent::EnemyDefeated);
+        }
+        for island in emptied {
+            if !self.npcs.iter().any(|npc| self.npc_island(npc) == Some(island)) {
+                self.cleared_islands.insert(island);
+            }
+        }
+    }
+
+    /*
+        Finds the island a creature stands on.
+
+        @param npc The creature.
+        @return Option<u64> The island's id, if it's on a loaded one.
+    */
+    fn npc_island(&self, npc: &Creature) -> Option<u64> {
+        let (x, y) = tile_position(
+            npc.x + IMAGE_SIZE_SCALED / 2.0,
+            npc.y + IMAGE_SIZE_SCALED / 2.0,
+        );
+        self.map.island_at(x, y).map(|island| island.id)
+    }
+
+    /*
+        Drops one of the held item at the player's feet.
+    */
+    fn drop_held_item(&mut self) {
+        if let Some(item) = self.player.drop_item() {
+            self.release(item);
+        }
+    }
+
+    /*
+        Leaves an item the player let go of in the world, on the ship if they're aboard.
+
+        @param item The item.
+    */
+    fn release(&mut self, mut item: Item) {
+        item.aboard = self.player_location == PlayerLocation::OnShip;
+        self.items_in_game.push(item);
+    }
+
+    /*
+        Moves the player / ship, depending on player control state.
+        Calls respective ship / player functionality to handle.
+
+        @param dir The direction of movement.
+        @param moving Whether movement in the direction starts or stops.
+    */
+    fn execute_move(&mut self, dir: Direction, moving: bool) {
+        match self.player.creature_state {
+            CreatureState::Normal => {
+                self.player.handle_input(Action::moving(dir), moving);
+                self.player.update_self_velocity();
+            }
+            CreatureState::ControllingShip => {
+                self.ship.handle_input(Action::moving(dir), moving);
+                self.ship.update_self_velocity();
+            }
+        }
+    }
+
+    /*
+        Moves the player / ship by how far a stick is pushed, depending on player control state.
+
+        @param x How far to move horizontally, from -1 to 1.
+        @param y How far to move vertically, from -1 to 1.
+    */
+    fn execute_analog(&mut self, x: f64, y: f64) {
+        match self.player.creature_state {
+            CreatureState::Normal => {
+                self.player.handle_analog(x, y);
+                self.player.update_self_velocity();
+            }
+            CreatureState::ControllingShip => {
+                self.ship.handle_analog(x, y);
+                self.ship.update_self_velocity();
+            }
+        }
+    }
+
+    /*
+        Handles player general "action" button, with varying results (a sort of catch all).
+    */
+    fn execute_action(&mut self) {
+        let current_location = self.player_location.clone();
+        match self.tile_under_player(current_location) {
+            Some(t) => match t.tile_type {
+                TileType::Portal => match self.player_location {
+                    PlayerLocation::OnShip => {
+                        if let Some(tile) = self.tile_under_player(PlayerLocation::InWorld) {
+                            if tile.passable {
+                                self.change_player_location();
+                                let x = (self.player.x + IMAGE_SIZE_SCALED as f64 / 2.0)
+                                    / IMAGE_SIZE_SCALED;
+                                let y = (self.player.y + IMAGE_SIZE_SCALED as f64 / 2.0)
+                                    / IMAGE_SIZE_SCALED;
+                                self.player.x = x.floor() * IMAGE_SIZE_SCALED;
+                                self.player.y = y.floor() * IMAGE_SIZE_SCALED;
+                                self.player.store_last_position();
+
+                                let (x, y) = self.player_tile();
+                                self.map.under_portal = tile;
+                                self.map.set_tile(x, y, Tile::new(TileType::Portal));
+                                self.publish_tile_under_player();
+                            }
+                        }
+                    }
+                    PlayerLocation::InWorld => {
+                        // The trip is refused if the ship has no portal to arrive on.
+                        let arrival = match self.ship_portal_position() {
+                            Some(arrival) => arrival,
+                            None => return,
+                        };
+                        if let Some(tile) = self.tile_under_player(PlayerLocation::OnShip) {
+                            if tile.passable {
+                                self.change_player_location();
+
+                                let (x, y) = self.player_tile();
+                                self.board_crew(x, y, arrival);
+                                self.player.x = arrival.0;
+                                self.player.y = arrival.1;
+                                self.player.store_last_position();
+
+                                let under_portal = self.map.under_portal.clone();
+                                self.map.set_tile(x, y, under_portal);
+                                self.map.under_portal = Tile::new(TileType::Air);
+                                self.publish_tile_under_player();
+                            }
+                        }
+                    }
+                },
+
+                TileType::Wheel => {
+                    self.player.change_control_state();
+                    self.ship.reset_dir();
+                }
+                _ => self.use_or_harvest(),
+            },
+            _ => {
+                self.use_or_harvest();
+            }
+        }
+    }
+
+    /*
+        Talks to a nearby islander, otherwise harvests the tile in front of or under the
+        player, or uses the held item if there's nothing to harvest.
+    */
+    fn use_or_harvest(&mut self) {
+        if self.talk() || self.harvest() {
+            return;
+        }
+        match self.player.weapon_damage() {
+            Some(damage) => self.attack(damage),
+            None => self.player.use_item(),
+        }
+    }
+
+    /*
+        Swings the player's weapon, damaging and knocking back creatures in front of them.
+
+        @param damage The weapon's damage.
+    */
+    fn attack(&mut self, damage: i32) {
+        if !self.player.start_attack() {
+            return;
+        }
+        let hitbox = self.player.attack_hitbox();
+        let facing = self.player.facing();
+        for npc in self.npcs.iter_mut() {
+            if overlaps(npc.hitbox(), hitbox) {
+                npc.take_damage(damage);
+                npc.knock_back(facing);
+            }
+        }
+        self.remove_dead_npcs();
+    }
+
+    /*
+        Runs the AI of other creatures, then moves them by their velocity, where they
+        are able to walk. Creatures can only reach the player off the ship.
+
+        @param dt The time step in seconds.
+    */
+    fn update_npcs(&mut self, dt: f64) {
+        let mut rng = thread_rng();
+        for i in 0..self.npcs.len() {
+            self.npcs[i].update_timers(dt);
+            self.run_ai(i, dt, &mut rng);
+            let x = self.npcs[i].x_to_be_location(dt);
+            let y = self.npcs[i].y_to_be_location(dt);
+            if self.can_go_to(x, y) {
+                self.npcs[i].update_position_self(dt);
+            } else {
+                self.npcs[i].knockback_x = 0.0;
+                self.npcs[i].knockback_y = 0.0;
+            }
+        }
+    }
+
+    /*
+        Finds the closest islander in reach of the player, who could be recruited.
+
+        @return Option<usize> The islander's index in crew, if there is one.
+    */
+    pub fn islander_in_reach(&self) -> Option<usize> {
+        if self.player_location != PlayerLocation::InWorld {
+            return None;
+        }
+        let (player_x, player_y) = (self.player.x, self.player.y);
+        let distance =
+            |member: &CrewMember| (member.creature.x - player_x).hypot(member.creature.y - player_y);
+        self.crew
+            .iter()
+            .enumerate()
+            .filter(|&(_, member)| !member.recruited && distance(member) <= RECRUIT_RANGE)
+            .min_by(|&(_, a), &(_, b)| distance(a).partial_cmp(&distance(b)).unwrap())
+            .map(|(i, _)| i)
+    }
+
+    /*
+        Starts a conversation with the closest islander in reach of the player.
+
+        @return bool Whether a conversation started.
+    */
+    pub fn talk(&mut self) -> bool {
+        match self.islander_in_reach() {
+            Some(i) => self.start_conversation("islander", Some(i)),
+            None => false,
+        }
+    }
+
+    /*
+        Starts a conversation, stopping the player where they are.
+
+        @param dialogue The dialogue's name.
+        @param speaker The index in crew of whoever is being talked to, if they're crew.
+        @return bool Whether the dialogue exists.
+    */
+    pub fn start_conversation(&mut self, dialogue: &str, speaker: Option<usize>) -> bool {
+        let start = match self.dialogues.get(dialogue) {
+            Some(dialogue) => dialogue.start.clone(),
+            None => return false,
+        };
+        self.player.directions = vec![];
+        self.player.analog = None;
+        self.player.update_self_velocity();
+        self.conversation = Some(Conversation {
+            dialogue: dialogue.to_string(),
+            node: String::new(),
+            line: 0,
+            selection: 0,
+            speaker_name: speaker.map_or(String::new(), |i| self.crew[i].name.clone()),
+            speaker_index: speaker,
+        });
+        self.enter_node(start);
+        true
+    }
+
+    /*
+        Finds the node of the conversation being had.
+
+        @return Option<&Node> The node, if a conversation is being had.
+    */
+    pub fn conversation_node(&self) -> Option<&Node> {
+        let conversation = self.conversation.as_ref()?;
+        self.dialogues
+            .get(&conversation.dialogue)?
+            .nodes
+            .get(&conversation.node)
+    }
+
+    /*
+        Finds the choices offered in the conversation, those whose conditions are met.
+
+        @return Vec<&Choice> The choices, empty if there are none.
+    */
+    pub fn conversation_choices(&self) -> Vec<&Choice> {
+        match self.conversation_node() {
+            Some(node) => node.choices
+                .iter()
+                .filter(|choice| {
+                    choice
+                        .conditions
+                        .iter()
+                        .all(|condition| condition.is_met(&self.player.inventory, &self.flags))
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /*
+        Handles commands while talking. Up and down pick a choice, interacting moves on.
+
+        @param command The command.
+    */
+    fn execute_conversation(&mut self, command: Command) {
+        let choice_count = self.conversation_choices().len().max(1);
+        match command {
+            Command::StartMoving(dir) => {
+                if let Some(ref mut conversation) = self.conversation {
+                    conversation.selection = match dir {
+                        Direction::N => (conversation.selection + choice_count - 1) % choice_count,
+                        Direction::S => (conversation.selection + 1) % choice_count,
+                        _ => conversation.selection,
+                    };
+                }
+            }
+            Command::StopMoving(dir) => self.execute_move(dir, false),
+            Command::Interact | Command::InteractAt(..) => self.advance_conversation(),
+            _ => {}
+        }
+    }
+
+    /*
+        Shows the next line, or follows the picked choice or the node's next node
+        after the last line.
+    */
+    fn advance_conversation(&mut self) {
+        let (line, selection) = match self.conversation {
+            Some(ref conversation) => (conversation.line, conversation.selection),
+            None => return,
+        };
+        let (line_count, next) = match self.conversation_node() {
+            Some(node) => (node.lines.len(), node.next.clone()),
+            None => (0, None),
+        };
+        if line + 1 < line_count {
+            if let Some(ref mut conversation) = self.conversation {
+                conversation.line += 1;
+            }
+            return;
+        }
+        let next = match self.conversation_choices().get(selection) {
+            Some(choice) => choice.next.clone(),
+            None => next.unwrap_or_else(|| END_NODE.to_string()),
+        };
+        self.enter_node(next);
+    }
+
+    /*
+        Moves the conversation to a node and carries out its effects, or ends it.
+
+        @param node_name The node's name.
+    */
+    fn enter_node(&mut self, node_name: String) {
+        if node_name == END_NODE {
+            self.conversation = None;
+            return;
+        }
+        if let Some(ref mut conversation) = self.conversation {
+            conversation.node = node_name;
+            conversation.line = 0;
+            conversation.selection = 0;
+        }
+        let effects = match self.conversation_node() {
+            Some(node) => node.effects.clone(),
+            None => vec![],
+        };
+        for effect in effects {
+            self.apply_effect(effect);
+        }
+    }
+
+    /*
+        Carries out an effect of dialogue.
+
+        @param effect The effect.
+    */
+    fn apply_effect(&mut self, effect: Effect) {
+        match effect {
+            Effect::Give(item_type, count) => self.give(&item_type, count),
+            Effect::Take(item_type, count) => {
+                self.player.inventory.remove(&item_type, count);
+            }
+            Effect::SetFlag(flag) => {
+                self.flags.insert(flag);
+            }
+            Effect::ClearFlag(flag) => {
+                self.flags.remove(&flag);
+            }
+            Effect::Recruit => {
+                let speaker = self.conversation.as_ref().and_then(|c| c.speaker_index);
+                let crew = &mut self.crew;
+                if let Some(member) = speaker.and_then(|i| crew.get_mut(i)) {
+                    member.recruited = true;
+                    self.events.publish(GameEvent::Recruited(member.name.clone()));
+                }
+            }
+        }
+    }
+
+    /*
+        Gives the player items, dropping those that don't fit at their feet.
+
+        @param item_type The item type.
+        @param count How many to give.
+    */
+    fn give(&mut self, item_type: &ItemType, count: u32) {
+        let mut prototype = self.item_prototypes[item_type.name()].clone();
+        prototype.collected = true;
+        for _ in 0..count {
+            let item = prototype.generate_clone(self.player.x, self.player.y);
+            if !self.player.inventory.can_add(&item) {
+                self.release(item);
+            } else {
+                self.player.pickup_item(item);
+            }
+        }
+    }
+
+    /*
+        Brings recruits close to the portal aboard with the player, who has just gone
+        through it. They stand where the player arrives.
+
+        @param portal_x The x position of the portal's tile.
+        @param portal_y The y position of the portal's tile.
+        @param arrival Where the ship's portal is, in world coordinates.
+    */
+    fn board_crew(&mut self, portal_x: i32, portal_y: i32, arrival: (f64, f64)) {
+        let portal_x = portal_x as f64 * IMAGE_SIZE_SCALED;
+        let portal_y = portal_y as f64 * IMAGE_SIZE_SCALED;
+        let (arrival_x, arrival_y) = arrival;
+        for member in self.crew.iter_mut() {
+            let creature = &mut member.creature;
+            let near = (creature.x - portal_x).hypot(creature.y - portal_y) <= BOARDING_RANGE;
+            if member.recruited && !member.on_ship && near {
+                member.on_ship = true;
+                creature.x = arrival_x;
+                creature.y = arrival_y;
+                creature.directions = vec![];
+                creature.update_self_velocity();
+                creature.store_last_position();
+            }
+        }
+    }
+
+    /*
+        Moves the crew. Those aboard are carried along by the ship, like the player,
+        and recruits on land follow the player around the island.
+
+        @param dt The time step in seconds.
+    */
+    fn update_crew(&mut self, dt: f64) {
+        let (ship_vel_x, ship_vel_y) = match self.player_location {
+            PlayerLocation::OnShip => (self.ship.self_vel_x, self.ship.self_vel_y),
+            PlayerLocation::InWorld => (0.0, 0.0),
+        };
+        let (player_x, player_y) = (self.player.x, self.player.y);
+        for i in 0..self.crew.len() {
+            // Crew leave stations that were built over.
+            if let Some((x, y)) = self.crew[i].station {
+                if !self.ship.tile_type_at(x as i32, y as i32).is_station() {
+                    self.crew[i].station = None;
+                }
+            }
+            if self.crew[i].on_ship {
+                let station = self.crew[i].station;
+                let creature = &mut self.crew[i].creature;
+                creature.other_vel_x = ship_vel_x;
+                creature.other_vel_y = ship_vel_y;
+                creature.update_position_other(dt);
+                if let Some((x, y)) = station {
+                    creature.x = self.ship.x + x as f64 * IMAGE_SIZE_SCALED;
+                    creature.y = self.ship.y + y as f64 * IMAGE_SIZE_SCALED;
+                }
+                continue;
+            }
+
+            // Recruits left behind wait for the player to come back.
+            let (x, y) = (self.crew[i].creature.x, self.crew[i].creature.y);
+            let follow = self.crew[i].recruited
+                && self.player_location == PlayerLocation::InWorld
+                && (player_x - x).hypot(player_y - y) > FOLLOW_DISTANCE;
+            let directions = if follow {
+                directions_towards(x, y, player_x, player_y)
+            } else {
+                vec![]
+            };
+            self.crew[i].creature.directions = directions;
+            self.crew[i].creature.update_self_velocity();
+            self.crew[i].creature.update_direction();
+            let to_x = self.crew[i].creature.x_to_be_location(dt);
+            let to_y = self.crew[i].creature.y_to_be_location(dt);
+            if self.can_go_to(to_x, to_y) {
+                self.crew[i].creature.update_position_self(dt);
+            }
+        }
+    }
+
+    /*
+        Lists the recruited crew, aboard or not.
+
+        @return Vec<usize> The crew members' indices in crew.
+    */
+    pub fn recruited_crew(&self) -> Vec<usize> {
+        (0..self.crew.len())
+            .filter(|&i| self.crew[i].recruited)
+            .collect()
+    }
+
+    /*
+        Moves a crew member aboard to the next free station, or off duty after the last one.
+
+        @param member The crew member's index in crew.
+    */
+    pub fn cycle_station(&mut self, member: usize) {
+        match self.crew.get(member) {
+            Some(m) if m.recruited && m.on_ship => {}
+            _ => return,
+        }
+        let taken: Vec<(usize, usize)> = self.crew
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != member)
+            .filter_map(|(_, m)| m.station)
+            .collect();
+        let mut options = vec![None];
+        for station in self.ship.stations() {
+            if !taken.contains(&station) {
+                options.push(Some(station));
+            }
+        }
+        let current = options
+            .iter()
+            .position(|option| *option == self.crew[member].station)
+            .unwrap_or(0);
+        self.crew[member].station = options[(current + 1) % options.len()];
+        self.crew[member].task_timer = 0.0;
+    }
+
+    /*
+        Sets a waypoint over the nearest discovered island the ship isn't already over,
+        for crew at the wheel to steer towards.
+
+        @return bool Whether a waypoint was set.
+    */
+    pub fn plot_course(&mut self) -> bool {
+        let (ship_x, ship_y) = self.ship_center();
+        let distance = |&(x, y): &(f64, f64)| (x - ship_x).hypot(y - ship_y);
+        self.waypoint = self.map
+            .discovered_islands()
+            .into_iter()
+            .map(|island| {
+                let (x, y) = island.center();
+                (x * IMAGE_SIZE_SCALED, y * IMAGE_SIZE_SCALED)
+            })
+            .filter(|point| distance(point) > ARRIVAL_DISTANCE)
+            .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap());
+        self.waypoint.is_some()
+    }
+
+    /*
+        Finds where the ship's portal is in the world, for arriving aboard.
+
+        @return Option<(f64, f64)> The portal tile's x and y position, if the ship has one.
+    */
+    pub fn ship_portal_position(&self) -> Option<(f64, f64)> {
+        self.ship.portal().map(|(x, y)| {
+            (
+                self.ship.x + x as f64 * IMAGE_SIZE_SCALED,
+                self.ship.y + y as f64 * IMAGE_SIZE_SCALED,
+            )
+        })
+    }
+
+    /*
+        Determines the middle of the ship.
+
+        @return (f64, f64) The x and y position, in world coordinates.
+    */
+    pub fn ship_center(&self) -> (f64, f64) {
+        (
+            self.ship.x + self.ship.width * IMAGE_SIZE_SCALED / 2.0,
+            self.ship.y + self.ship.height * IMAGE_SIZE_SCALED / 2.0,
+        )
+    }
+
+    /*
+        Adds a recipe to the queue crafted by crew at a workbench.
+
+        @param recipe The recipe's index in the registry.
+        @return Result<(), CraftError> Why the recipe couldn't be queued, if it couldn't.
+    */
+    pub fn queue_craft(&mut self, recipe: usize) -> Result<(), CraftError> {
+        if recipe >= self.recipes.len() {
+            return Err(CraftError::UnknownRecipe);
+        }
+        self.craft_queue.push(recipe);
+        Ok(())
+    }
+
+    /*
+        Has crew aboard work at their stations: steering towards the waypoint, spotting
+        islands, crafting queued recipes and firing at enemies.
+
+        @param dt The time step in seconds.
+    */
+    pub fn run_crew_tasks(&mut self, dt: f64) {
+        for i in 0..self.crew.len() {
+            let (x, y) = match self.crew[i].station {
+                Some(station) if self.crew[i].on_ship => station,
+                _ => continue,
+            };
+            let tile_type = self.ship.tile_type_at(x as i32, y as i32);
+            let interval = match tile_type {
+                TileType::Wheel => {
+                    self.steer_to_waypoint(i);
+                    continue;
+                }
+                TileType::Lookout => LOOKOUT_INTERVAL,
+                TileType::Workbench => CRAFT_TIME,
+                TileType::Cannon => CANNON_INTERVAL,
+                _ => continue,
+            };
+            let rate = self.crew[i].work_rate(&tile_type);
+            self.crew[i].task_timer += dt * rate;
+            if self.crew[i].task_timer < interval {
+                continue;
+            }
+            self.crew[i].task_timer = 0.0;
+            match tile_type {
+                TileType::Lookout => self.keep_lookout(i, rate),
+                TileType::Workbench => self.craft_queued(i),
+                _ => {
+                    // Keep the cannon loaded until something comes in range.
+                    if !self.fire_cannon(i, x, y) {
+                        self.crew[i].task_timer = interval;
+                    }
+                }
+            }
+        }
+    }
+
+    /*
+        Steers the ship towards the waypoint, unless the player has the wheel,
+        and stops once it's reached.
+
+        @param member The index in crew of the crew member at the wheel.
+    */
+    fn steer_to_waypoint(&mut self, member: usize) {
+        if self.player.creature_state == CreatureState::ControllingShip {
+            return;
+        }
+        let (to_x, to_y) = match self.waypoint {
+            Some(waypoint) => waypoint,
+            None => return,
+        };
+        let (x, y) = self.ship_center();
+        if (to_x - x).hypot(to_y - y) <= ARRIVAL_DISTANCE {
+            self.ship.reset_dir();
+            self.waypoint = None;
+            let report = format!("{} reached the waypoint", self.crew[member].name);
+            self.report(report);
+        } else {
+            self.ship.steer_towards((to_y - y).atan2(to_x - x));
+        }
+    }
+
+    /*
+        Discovers the loaded islands in sight of the ship.
+
+        @param member The index in crew of the crew member on lookout.
+        @param rate How well they keep watch, stretching their range.
+    */
+    fn keep_lookout(&mut self, member: usize, rate: f64) {
+        let (x, y) = self.ship_center();
+        let (x, y) = (x / IMAGE_SIZE_SCALED, y / IMAGE_SIZE_SCALED);
+        let in_sight: Vec<Island> = self.map
+            .islands()
+            .into_iter()
+            .filter(|island| {
+                let (island_x, island_y) = island.center();
+                (island_x - x).hypot(island_y - y) <= LOOKOUT_RANGE * rate
+            })
+            .cloned()
+            .collect();
+        let spotted: Vec<u64> = in_sight
+            .iter()
+            .filter(|island| self.map.discover(island))
+            .map(|island| island.id)
+            .collect();
+        for &island in &spotted {
+            self.events.publish(GameEvent::IslandDiscovered(island));
+        }
+        let spotted = spotted.len();
+        if spotted > 0 {
+            let report = format!("{} spotted {} new island(s)", self.crew[member].name, spotted);
+            self.report(report);
+        }
+    }
+
+    /*
+        Crafts the next queued recipe from the player's inventory.
+
+        @param member The index in crew of the crew member at the workbench.
+    */
+    fn craft_queued(&mut self, member: usize) {
+        if self.craft_queue.is_empty() {
+            return;
+        }
+        let recipe = &self.recipes[self.craft_queue.remove(0)];
+        let result = recipe.craft(
+            &mut self.player.inventory,
+            Some(&TileType::Workbench),
+            &self.item_prototypes,
+        );
+        let report = match result {
+            Ok(()) => format!("{} crafted {}", self.crew[member].name, recipe.name),
+            Err(error) => format!(
+                "{} couldn't craft {}: {}",
+                self.crew[member].name, recipe.name, error
+            ),
+        };
+        self.report(report);
+    }
+
+    /*
+        Fires a cannon at the nearest creature in range.
+
+        @param member The index in crew of the crew member at the cannon.
+        @param x The cannon's column, relative to the ship.
+        @param y The cannon's row, relative to the ship.
+        @return bool Whether there was something to fire at.
+    */
+    fn fire_cannon(&mut self, member: usize, x: usize, y: usize) -> bool {
+        let cannon_x = self.ship.x + x as f64 * IMAGE_SIZE_SCALED;
+        let cannon_y = self.ship.y + y as f64 * IMAGE_SIZE_SCALED;
+        let distance = |npc: &Creature| (npc.x - cannon_x).hypot(npc.y - cannon_y);
+        let target = self.npcs
+            .iter()
+            .enumerate()
+            .filter(|&(_, npc)| distance(npc) <= CANNON_RANGE)
+            .min_by(|&(_, a), &(_, b)| distance(a).partial_cmp(&distance(b)).unwrap())
+            .map(|(i, _)| i);
+        let target = match target {
+            Some(target) => target,
+            None => return false,
+        };
+        let npc = &mut self.npcs[target];
+        npc.take_damage(CANNON_DAMAGE);
+        if let Some(&dir) = directions_towards(cannon_x, cannon_y, npc.x, npc.y).first() {
+            npc.knock_back(dir);
+        }
+        if npc.is_dead() {
+            let report = format!("{} shot down an enemy", self.crew[member].name);
+            self.report(report);
+        }
+        self.remove_dead_npcs();
+        true
+    }
+
+    /*
+        Records something the crew did, forgetting the oldest reports.
+
+        @param report The report.
+    */
+    fn report(&mut self, report: String) {
+        self.crew_reports.push(report);
+        if self.crew_reports.len() > CREW_REPORTS_KEPT {
+            self.crew_reports.remove(0);
+        }
+    }
+
+    /*
+        Chooses a creature's behaviour and acts on it, steering it or hitting the player.
+
+        @param i The creature's index in npcs.
+        @param dt The time step in seconds.
+        @param rng Random number generator for idling and wandering.
+    */
+    fn run_ai<R: Rng>(&mut self, i: usize, dt: f64, rng: &mut R) {
+        let (player_x, player_y) = (self.player.x, self.player.y);
+        let distance = match self.player_location {
+            PlayerLocation::InWorld => {
+                let npc = &self.npcs[i];
+                Some((player_x - npc.x).hypot(player_y - npc.y))
+            }
+            PlayerLocation::OnShip => None,
+        };
+        let health = self.npcs[i].health;
+        let (state, damage) = match self.npcs[i].ai {
+            Some(ref mut ai) => {
+                let state = ai.decide(health, distance);
+                ai.enter(state, rng);
+                ai.timer -= dt;
+                (state, ai.damage)
+            }
+            None => return,
+        };
+
+        let (npc_x, npc_y) = (self.npcs[i].x, self.npcs[i].y);
+        let directions = match state {
+            AiState::Idle | AiState::Attack => vec![],
+            AiState::Wander => {
+                let npc = &self.npcs[i];
+                let wander_dir = npc.ai.as_ref().map_or(Direction::S, |ai| ai.wander_dir);
+                vec![wander_dir]
+            }
+            AiState::Chase => directions_towards(npc_x, npc_y, player_x, player_y),
+            AiState::Flee => directions_towards(player_x, player_y, npc_x, npc_y),
+        };
+        let npc = &mut self.npcs[i];
+        npc.directions = directions;
+        npc.update_self_velocity();
+        npc.update_direction();
+
+        if state == AiState::Attack && npc.start_attack() {
+            let towards = directions_towards(npc_x, npc_y, player_x, player_y);
+            self.hurt_player(damage);
+            if let Some(&dir) = towards.first() {
+                self.player.knock_back(dir);
+            }
+        }
+    }
+
+    /*
+        Damages the player, telling observers how much health they lost.
+
+        @param damage The damage.
+    */
+    fn hurt_player(&mut self, damage: i32) {
+        let health = self.player.health;
+        self.player.take_damage(damage);
+        let lost = health - self.player.health;
+        if lost > 0 {
+            self.events.publish(GameEvent::DamageTaken(lost));
+        }
+    }
+
+    /*
+        Chops a tree with a held tool, leaving logs and grass, or picks a grown grune plant.
+        The tile in front of the player is tried before the one they stand on.
+
+        @return bool Whether something was harvested.
+    */
+    pub fn harvest(&mut self) -> bool {
+        if self.player_location != PlayerLocation::InWorld {
+            return false;
+        }
+        let (x, y) = self.player_tile();
+        let (front_x, front_y) = match self.player.facing() {
+            Direction::N => (x, y - 1),
+            Direction::S => (x, y + 1),
+            Direction::W => (x - 1, y),
+            Direction::E => (x + 1, y),
+        };
+        let has_tool = match self.player.inventory.held() {
+            Some(item) => match item.item_type {
+                ItemType::Interactable(_) => true,
+                _ => false,
+            },
+            None => false,
+        };
+
+        for &(x, y) in &[(front_x, front_y), (x, y)] {
+            let mut tile = match self.map.tile(x, y) {
+                Some(tile) => tile.clone(),
+                None => continue,
+            };
+            if tile.tile_type == TileType::Tree && has_tool {
+                let mut grass = Tile::new(TileType::GrassFloor);
+                grass.texture = tile.texture;
+                self.map.set_tile(x, y, grass);
+                let logs = self.item_prototypes.get("logs").unwrap();
+                for _ in 0..LOGS_PER_TREE {
+                    self.items_in_game.push(logs.generate_clone(
+                        x as f64 * IMAGE_SIZE_SCALED,
+                        y as f64 * IMAGE_SIZE_SCALED,
+                    ));
+                }
+                return true;
+            }
+            if tile.is_ripe() {
+                tile.regrow = GRUNE_REGROW_TIME;
+                self.map.set_tile(x, y, tile);
+                let grune = ItemType::Resource(ResourceType::Grune);
+                self.give(&grune, 1);
+                self.events.publish(GameEvent::Collected(grune));
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/*
+    Determines which map tile a position is over.
+
+    @param x Some x coordinate.
+    @param y Some y coordinate.
+    @return (i32, i32) The x and y position of the tile.
+*/
+fn tile_position(x: f64, y: f64) -> (i32, i32) {
+    (
+        (x / IMAGE_SIZE_SCALED).floor() as i32,
+        (y / IMAGE_SIZE_SCALED).floor() as i32,
+    )
+}
+
+/*
+    Creates prototypes of enemies for prototyping pattern.
+
+    @return HashMap<String, Creature> A map of string to enemies.
+*/
+fn generate_enemy_prototypes() -> HashMap<String, Creature> {
+    let mut prototypes: HashMap<String, Creature> = HashMap::new();
+
+    // Bandits hit hard but run away when hurt.
+    let mut bandit = Creature::new();
+    bandit.health = 20;
+    bandit.speed = 90.0;
+    bandit.color = [1.0, 0.6, 0.6, 1.0];
+    bandit.ai = Some(Ai::new(6.0 * IMAGE_SIZE_SCALED, IMAGE_SIZE_SCALED, 1, 5));
+    prototypes.insert("bandit".to_string(), bandit);
+
+    // Wisps are fast and fragile, and never give up.
+    let mut wisp = Creature::new();
+    wisp.health = 10;
+    wisp.speed = 150.0;
+    wisp.color = [0.6, 0.8, 1.0, 0.8];
+    wisp.ai = Some(Ai::new(8.0 * IMAGE_SIZE_SCALED, IMAGE_SIZE_SCALED * 0.75, 1, 0));
+    prototypes.insert("wisp".to_string(), wisp);
+
+    prototypes
+}
+
+/*
+    Creates prototypes of items for prototyping pattern.
+
+    @return HashMap<String, Item> A map of string to items.
+*/
+fn generate_item_prototypes() -> HashMap<String, Item> {
+    let mut prototypes: HashMap<String, Item> = HashMap::new();
+    prototypes.insert(
+        "bisket".to_string(),
+        Item::new(ItemType::Food(FoodType::Bisket), 1, true, 1.0),
+    );
+    prototypes.insert(
+        "sword".to_string(),
+        Item::new(
+            ItemType::Interactable(InteractableType::Sword),
+            10,
+            true,
+            5.0,
+        ),
+    );
+    prototypes.insert(
+        "grune".to_string(),
+        Item::new(ItemType::Resource(ResourceType::Grune), 1, true, 0.5),
+    );
+    prototypes.insert(
+        "planks".to_string(),
+        Item::new(ItemType::Resource(ResourceType::Planks), 2, true, 2.0),
+    );
+    prototypes.insert(
+        "logs".to_string(),
+        Item::new(ItemType::Resource(ResourceType::Logs), 5, true, 8.0),
+    );
+    prototypes
+}